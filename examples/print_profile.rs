@@ -33,7 +33,7 @@ fn register() -> Mastodon {
     };
 
     let mut registration = Registration::new("https://mastodon.social");
-    registration.register(app).unwrap();;
+    registration.register(app).unwrap();
     let url = registration.authorise().unwrap();
 
     println!("Click this link to authorize on Mastodon: {}", url);
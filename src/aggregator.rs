@@ -0,0 +1,79 @@
+use std::thread;
+
+use entities::status::Status;
+use timeline;
+use super::{Error, Mastodon};
+
+/// Combines the home timelines of several clients, possibly on different
+/// instances, into one timeline.
+///
+/// ```no_run
+/// # extern crate mammut;
+/// # use mammut::{Data, Mastodon};
+/// # use mammut::aggregator::Aggregator;
+/// # fn main() {
+/// # let work = Mastodon::from_data(Data::from_env().unwrap());
+/// # let personal = work.clone();
+/// let aggregator = Aggregator::new(vec![work, personal]);
+/// let timeline = aggregator.home_timeline();
+///
+/// for &(client, ref error) in &timeline.errors {
+///     println!("Couldn't fetch timeline {}: {}", client, error);
+/// }
+///
+/// for status in timeline.statuses {
+///     println!("{}: {}", status.account.acct, status.content);
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Aggregator {
+    clients: Vec<Mastodon>,
+}
+
+/// The merged timeline returned by `Aggregator::home_timeline`.
+#[derive(Debug)]
+pub struct Timeline {
+    /// The statuses of all timelines, newest first. A status appearing on
+    /// several instances is only included once.
+    pub statuses: Vec<Status>,
+    /// Errors encountered fetching a timeline, along with the index of the
+    /// client which failed.
+    pub errors: Vec<(usize, Error)>,
+}
+
+impl Aggregator {
+    /// Creates an aggregator over the timelines of `clients`.
+    pub fn new(clients: Vec<Mastodon>) -> Self {
+        Aggregator { clients }
+    }
+
+    /// The clients being aggregated.
+    pub fn clients(&self) -> &[Mastodon] {
+        &self.clients
+    }
+
+    /// Fetches the home timeline of every client concurrently, and merges
+    /// them into one chronologically sorted timeline.
+    pub fn home_timeline(&self) -> Timeline {
+        let handles: Vec<_> = self.clients.iter()
+            .cloned()
+            .map(|client| thread::spawn(move || client.get_home_timeline()))
+            .collect();
+
+        let mut pages = Vec::new();
+        let mut errors = Vec::new();
+
+        for (i, handle) in handles.into_iter().enumerate() {
+            match handle.join().expect("timeline thread panicked") {
+                Ok(page) => pages.push(page),
+                Err(e) => errors.push((i, e)),
+            }
+        }
+
+        Timeline {
+            statuses: timeline::merge(pages),
+            errors,
+        }
+    }
+}
@@ -0,0 +1,206 @@
+//! Watching timelines for statuses matching keywords or regular expressions,
+//! for example to get alerted when a project is mentioned.
+//!
+//! ```no_run
+//! # extern crate mammut;
+//! # use mammut::{Data, Mastodon};
+//! # use mammut::alert::{KeywordAlert, Rule};
+//! # use mammut::timeline::Cursor;
+//! # use std::time::Duration;
+//! # fn main() {
+//! #    try().unwrap();
+//! # }
+//! # fn try() -> mammut::Result<()> {
+//! # let mastodon = Mastodon::from_data(Data::from_env()?);
+//! let mut alert = KeywordAlert::new(vec![Cursor::public()]);
+//! alert.add_rule(Rule::keyword("mammut", "mammut"));
+//! alert.add_rule(Rule::regex("crate", r"crates\.io/crates/mammut")?.languages(&["en"]));
+//!
+//! alert.run(&mastodon, Duration::from_secs(60), |alert| {
+//!     println!("{} matched {}", alert.rule, alert.status.uri);
+//! });
+//! # Ok(())
+//! # }
+//! ```
+
+use std::thread;
+use std::time::Duration;
+
+use regex::{self, Regex, RegexBuilder};
+
+use dedup::Seen;
+use entities::status::Status;
+use html;
+use timeline::{self, Cursor};
+use super::{Mastodon, Result};
+
+/// A pattern to match statuses against.
+#[derive(Debug, Clone)]
+pub struct Rule {
+    /// The name of the rule, included in its alerts.
+    pub name: String,
+    pattern: Regex,
+    languages: Vec<String>,
+    spoilers: bool,
+    hidden: bool,
+}
+
+impl Rule {
+    /// Matches statuses containing `keyword` as a whole word, ignoring case.
+    pub fn keyword<I: Into<String>>(name: I, keyword: &str) -> Self {
+        let pattern = RegexBuilder::new(&format!(r"\b{}\b", regex::escape(keyword)))
+            .case_insensitive(true)
+            .build()
+            .expect("an escaped keyword is a valid regex");
+
+        Rule::with_pattern(name.into(), pattern)
+    }
+
+    /// Matches statuses matching the regular expression `pattern`.
+    ///
+    /// # Errors
+    /// `Error::Regex` if `pattern` isn't a valid regular expression.
+    pub fn regex<I: Into<String>>(name: I, pattern: &str) -> Result<Self> {
+        Ok(Rule::with_pattern(name.into(), Regex::new(pattern)?))
+    }
+
+    fn with_pattern(name: String, pattern: Regex) -> Self {
+        Rule {
+            name,
+            pattern,
+            languages: Vec::new(),
+            spoilers: true,
+            hidden: true,
+        }
+    }
+
+    /// Only matches statuses in one of `languages`, given as ISO 639-1 codes.
+    /// Statuses without a detected language are matched as well.
+    pub fn languages(mut self, languages: &[&str]) -> Self {
+        self.languages = languages.iter().map(|language| language.to_lowercase()).collect();
+        self
+    }
+
+    /// Sets whether the content warning of a status is matched against, as
+    /// well as its content. Enabled by default.
+    pub fn match_spoiler_text(mut self, spoilers: bool) -> Self {
+        self.spoilers = spoilers;
+        self
+    }
+
+    /// Sets whether statuses behind a content warning are matched at all.
+    /// Enabled by default.
+    pub fn match_hidden(mut self, hidden: bool) -> Self {
+        self.hidden = hidden;
+        self
+    }
+
+    /// Whether `status` matches the rule. The content of reblogs is matched
+    /// rather than the reblog itself.
+    pub fn is_match(&self, status: &Status) -> bool {
+        let status = status.reblog.as_deref().unwrap_or(status);
+
+        if !status.spoiler_text.is_empty() && !self.hidden {
+            return false;
+        }
+
+        if let Some(ref language) = status.language {
+            if !self.languages.is_empty() && !self.languages.contains(&language.to_lowercase()) {
+                return false;
+            }
+        }
+
+        self.pattern.is_match(&html::to_text(&status.content)) ||
+            (self.spoilers && self.pattern.is_match(&status.spoiler_text))
+    }
+}
+
+/// A status which matched a rule.
+#[derive(Debug, Clone)]
+pub struct Alert {
+    /// The name of the rule the status matched.
+    pub rule: String,
+    /// The matching status.
+    pub status: Status,
+}
+
+/// Polls timelines for statuses matching any of its rules.
+#[derive(Debug)]
+pub struct KeywordAlert {
+    cursors: Vec<Cursor>,
+    rules: Vec<Rule>,
+    seen: Seen<String>,
+}
+
+impl KeywordAlert {
+    /// Creates an alert without rules watching the timelines of `cursors`.
+    pub fn new(cursors: Vec<Cursor>) -> Self {
+        KeywordAlert {
+            cursors,
+            rules: Vec::new(),
+            seen: Seen::new(1000),
+        }
+    }
+
+    /// Adds a rule to match statuses against.
+    pub fn add_rule(&mut self, rule: Rule) -> &mut Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// The positions in the watched timelines, for saving them with
+    /// `Cursor::save`.
+    pub fn cursors(&self) -> &[Cursor] {
+        &self.cursors
+    }
+
+    /// Matches `status` against the rules, returning an alert for each rule
+    /// it matches.
+    pub fn check(&self, status: &Status) -> Vec<Alert> {
+        self.rules.iter()
+            .filter(|rule| rule.is_match(status))
+            .map(|rule| Alert {
+                rule: rule.name.clone(),
+                status: status.clone(),
+            })
+            .collect()
+    }
+
+    /// Fetches the new statuses of every timeline, returning the alerts for
+    /// those matching a rule, oldest first. Statuses seen in several
+    /// timelines are only checked once.
+    ///
+    /// The cursors are only moved once every timeline was fetched, so if any
+    /// fails, the statuses are fetched again on the next poll.
+    pub fn poll(&mut self, mastodon: &Mastodon) -> Result<Vec<Alert>> {
+        let mut cursors = self.cursors.clone();
+        let mut pages = Vec::with_capacity(cursors.len());
+
+        for cursor in &mut cursors {
+            pages.push(mastodon.sync(cursor)?);
+        }
+
+        let mut alerts = Vec::new();
+        for status in timeline::merge(pages).iter().rev() {
+            if self.seen.insert(status.uri.clone()) {
+                alerts.extend(self.check(status));
+            }
+        }
+
+        self.cursors = cursors;
+        Ok(alerts)
+    }
+
+    /// Polls every `interval` forever, calling `alert` for each alert and
+    /// logging errors.
+    pub fn run<F: FnMut(Alert)>(&mut self, mastodon: &Mastodon, interval: Duration, mut alert: F) {
+        loop {
+            match self.poll(mastodon) {
+                Ok(alerts) => alerts.into_iter().for_each(&mut alert),
+                Err(e) => warn!("Failed to fetch timelines: {}", e),
+            }
+
+            thread::sleep(interval);
+        }
+    }
+}
@@ -1,10 +1,22 @@
 use std::fmt;
+use std::ops;
+
+use reqwest::Method;
+use serde::{Serialize, Serializer};
+use url::Url;
+
+use super::{Error, Result};
+
+/// The redirect uri for apps without a web flow, which makes the instance
+/// show the authorisation code to the user instead of redirecting.
+pub const REDIRECT_OOB: &str = "urn:ietf:wg:oauth:2.0:oob";
 
 /// Builder struct for defining your application.
 /// ```
+/// # use mammut::apps::{AppBuilder, Scopes, REDIRECT_OOB};
 /// let app = AppBuilder {
 ///     client_name: "mammut_test",
-///     redirect_uris: "urn:ietf:wg:oauth:2.0:oob",
+///     redirect_uris: REDIRECT_OOB,
 ///     scopes: Scopes::Read,
 ///     website: None,
 /// };
@@ -15,7 +27,9 @@ pub struct AppBuilder<'a> {
     /// grant permission.
     pub client_name: &'a str,
     /// Where the user should be redirected after authorization
-    /// (for no redirect, use `urn:ietf:wg:oauth:2.0:oob`)
+    /// (for no redirect, use `REDIRECT_OOB`). Several uris can be registered
+    /// by joining them with newlines, then picked with
+    /// `Registration::set_redirect`.
     pub redirect_uris: &'a str,
     /// Permission scope of the application.
     pub scopes: Scopes,
@@ -24,45 +38,252 @@ pub struct AppBuilder<'a> {
     pub website: Option<&'a str>,
 }
 
-/// Permission scope of the application.
-/// [Details on what each permission provides](//github.com/tootsuite/documentation/blob/master/Using-the-API/OAuth-details.md)
-#[derive(Debug, Clone, Copy, Serialize)]
-pub enum Scopes {
+impl<'a> AppBuilder<'a> {
+    /// Checks the app for mistakes the instance would reject with an unclear
+    /// error. Called by `Registration::register`.
+    ///
+    /// # Errors
+    /// `Error::ClientNameRequired` if `client_name` is empty, and
+    /// `Error::Url` if a redirect uri is neither a url nor `REDIRECT_OOB`.
+    pub fn validate(&self) -> Result<()> {
+        if self.client_name.trim().is_empty() {
+            return Err(Error::ClientNameRequired);
+        }
+
+        for uri in self.redirect_uris.lines() {
+            if uri != REDIRECT_OOB {
+                Url::parse(uri)?;
+            }
+        }
+
+        if let Some(website) = self.website {
+            Url::parse(website)?;
+        }
+
+        Ok(())
+    }
+}
+
+macro_rules! scopes {
+    ($($variant:ident => $name:expr,)+) => {
+        /// A single OAuth scope, such as `read` or the more fine-grained
+        /// `read:statuses`. Combine them into `Scopes` with `|`.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum Scope {
+            $(
+                #[doc = concat!("The `", $name, "` scope.")]
+                $variant,
+            )+
+        }
+
+        impl Scope {
+            /// Every scope.
+            pub const ALL: &'static [Scope] = &[$(Scope::$variant,)+];
+
+            /// The name of the scope, as requested from the instance.
+            pub fn as_str(self) -> &'static str {
+                match self {
+                    $(Scope::$variant => $name,)+
+                }
+            }
+
+            /// Parses the name of a scope, `None` if it isn't known.
+            pub fn parse(name: &str) -> Option<Scope> {
+                match name {
+                    $($name => Some(Scope::$variant),)+
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+scopes! {
+    Read => "read",
+    Write => "write",
+    Follow => "follow",
+    Push => "push",
+    Profile => "profile",
+    ReadAccounts => "read:accounts",
+    ReadBlocks => "read:blocks",
+    ReadBookmarks => "read:bookmarks",
+    ReadFavourites => "read:favourites",
+    ReadFilters => "read:filters",
+    ReadFollows => "read:follows",
+    ReadLists => "read:lists",
+    ReadMutes => "read:mutes",
+    ReadNotifications => "read:notifications",
+    ReadSearch => "read:search",
+    ReadStatuses => "read:statuses",
+    WriteAccounts => "write:accounts",
+    WriteBlocks => "write:blocks",
+    WriteBookmarks => "write:bookmarks",
+    WriteConversations => "write:conversations",
+    WriteFavourites => "write:favourites",
+    WriteFilters => "write:filters",
+    WriteFollows => "write:follows",
+    WriteLists => "write:lists",
+    WriteMedia => "write:media",
+    WriteMutes => "write:mutes",
+    WriteNotifications => "write:notifications",
+    WriteReports => "write:reports",
+    WriteStatuses => "write:statuses",
+    AdminRead => "admin:read",
+    AdminReadAccounts => "admin:read:accounts",
+    AdminReadReports => "admin:read:reports",
+    AdminReadDomainAllows => "admin:read:domain_allows",
+    AdminReadDomainBlocks => "admin:read:domain_blocks",
+    AdminReadIpBlocks => "admin:read:ip_blocks",
+    AdminReadEmailDomainBlocks => "admin:read:email_domain_blocks",
+    AdminReadCanonicalEmailBlocks => "admin:read:canonical_email_blocks",
+    AdminWrite => "admin:write",
+    AdminWriteAccounts => "admin:write:accounts",
+    AdminWriteReports => "admin:write:reports",
+    AdminWriteDomainAllows => "admin:write:domain_allows",
+    AdminWriteDomainBlocks => "admin:write:domain_blocks",
+    AdminWriteIpBlocks => "admin:write:ip_blocks",
+    AdminWriteEmailDomainBlocks => "admin:write:email_domain_blocks",
+    AdminWriteCanonicalEmailBlocks => "admin:write:canonical_email_blocks",
+}
+
+impl Scope {
+    /// The scope which grants this one along with its siblings, such as
+    /// `read` for `read:statuses`, `None` for top level scopes.
+    pub fn parent(self) -> Option<Scope> {
+        let name = self.as_str();
+        name.rfind(':').and_then(|colon| Scope::parse(&name[..colon]))
+    }
+
+    // Scopes granted by the deprecated `follow` scope.
+    fn is_follow(self) -> bool {
+        use self::Scope::*;
+        matches!(self, ReadFollows | ReadBlocks | ReadMutes | WriteFollows | WriteBlocks | WriteMutes)
+    }
+
+    fn bit(self) -> u64 {
+        1 << self as u64
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Permission scopes of the application, a set of `Scope`s.
+/// [Details on what each permission provides](https://docs.joinmastodon.org/api/oauth-scopes/)
+///
+/// The coarse scopes of earlier versions are kept as constants, such as
+/// `Scopes::Read` or `Scopes::ReadWrite`.
+///
+/// ```
+/// # use mammut::apps::{Scope, Scopes};
+/// let scopes = Scope::ReadStatuses | Scope::WriteMedia | Scope::Push;
+///
+/// assert_eq!(scopes.to_string(), "push%20read:statuses%20write:media");
+/// assert!(Scopes::Read.grants(Scope::ReadStatuses));
+/// assert!(!scopes.grants(Scope::ReadAccounts));
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Scopes {
+    bits: u64,
+}
+
+#[allow(non_upper_case_globals)]
+impl Scopes {
     /// All Permissions, equivalent to `read write follow`
-    #[serde(rename = "read write follow")]
-    All,
+    pub const All: Scopes = Scopes { bits: 1 << Scope::Read as u64 | 1 << Scope::Write as u64 | 1 << Scope::Follow as u64 };
     /// Only permission to add and remove followers.
-    #[serde(rename = "follow")]
-    Follow,
+    pub const Follow: Scopes = Scopes { bits: 1 << Scope::Follow as u64 };
     /// Read only permissions.
-    #[serde(rename = "read")]
-    Read,
+    pub const Read: Scopes = Scopes { bits: 1 << Scope::Read as u64 };
     /// Read & Follow permissions.
-    #[serde(rename = "read follow")]
-    ReadFollow,
+    pub const ReadFollow: Scopes = Scopes { bits: 1 << Scope::Read as u64 | 1 << Scope::Follow as u64 };
     /// Read & Write permissions.
-    #[serde(rename = "read write")]
-    ReadWrite,
+    pub const ReadWrite: Scopes = Scopes { bits: 1 << Scope::Read as u64 | 1 << Scope::Write as u64 };
     /// Write only permissions.
-    #[serde(rename = "write")]
-    Write,
+    pub const Write: Scopes = Scopes { bits: 1 << Scope::Write as u64 };
     /// Write & Follow permissions.
-    #[serde(rename = "write follow")]
-    WriteFollow,
+    pub const WriteFollow: Scopes = Scopes { bits: 1 << Scope::Write as u64 | 1 << Scope::Follow as u64 };
 }
 
-impl fmt::Display for Scopes {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        use self::Scopes::*;
-        write!(f, "{}", match *self {
-            All => "read%20write%20follow",
-            Follow => "follow",
-            Read => "read",
-            ReadFollow => "read%20follow",
-            ReadWrite => "read%20write",
-            Write => "write",
-            WriteFollow => "write%20follow"
-        })
+impl Scopes {
+    /// No scopes.
+    pub fn empty() -> Scopes {
+        Scopes { bits: 0 }
+    }
+
+    /// Whether there are no scopes.
+    pub fn is_empty(&self) -> bool {
+        self.bits == 0
+    }
+
+    /// Whether `scope` is one of these scopes, see `grants` for whether it
+    /// is permitted by them.
+    pub fn contains(&self, scope: Scope) -> bool {
+        self.bits & scope.bit() != 0
+    }
+
+    /// The scopes, in the order of `Scope::ALL`.
+    pub fn iter(&self) -> impl Iterator<Item = Scope> + '_ {
+        Scope::ALL.iter().cloned().filter(move |&scope| self.contains(scope))
+    }
+
+    /// Whether these scopes permit `scope`, because they contain it, its
+    /// parent such as `read` for `read:statuses`, or `follow` for the
+    /// scopes it used to grant.
+    pub fn grants(&self, scope: Scope) -> bool {
+        self.contains(scope) ||
+            scope.parent().is_some_and(|parent| self.grants(parent)) ||
+            (scope.is_follow() && self.contains(Scope::Follow))
+    }
+
+    /// Whether these scopes grant all permissions of `other`.
+    ///
+    /// ```
+    /// # use mammut::apps::{Scope, Scopes};
+    /// assert!(Scopes::All.includes(Scopes::ReadWrite));
+    /// assert!(Scopes::Read.includes(Scope::ReadStatuses | Scope::ReadLists));
+    /// assert!(!Scopes::Read.includes(Scopes::Write));
+    /// ```
+    pub fn includes(&self, other: Scopes) -> bool {
+        other.iter().all(|scope| self.grants(scope))
+    }
+
+    /// The scopes granted by both these and `other`, `None` if they have
+    /// none in common. The intersection of `read` and `read:statuses` is
+    /// `read:statuses`.
+    pub fn intersection(&self, other: Scopes) -> Option<Scopes> {
+        let bits = self.iter().chain(other.iter())
+            .filter(|&scope| self.grants(scope) && other.grants(scope))
+            .fold(0, |bits, scope| bits | scope.bit());
+
+        if bits == 0 {
+            None
+        } else {
+            Some(Scopes { bits })
+        }
+    }
+
+    /// Parses the space separated scopes granted with an access token, such
+    /// as `read write push`. Unknown scopes are ignored, `None` is returned
+    /// if none of the scopes are known.
+    pub fn from_granted(granted: &str) -> Option<Scopes> {
+        let scopes = granted.split_whitespace()
+            .filter_map(Scope::parse)
+            .fold(Scopes::empty(), |scopes, scope| scopes | scope);
+
+        if scopes.is_empty() {
+            None
+        } else {
+            Some(scopes)
+        }
+    }
+
+    // The names of the scopes, joined by `separator`.
+    fn join(&self, separator: &str) -> String {
+        self.iter().map(Scope::as_str).collect::<Vec<_>>().join(separator)
     }
 }
 
@@ -71,3 +292,103 @@ impl Default for Scopes {
         Scopes::Read
     }
 }
+
+impl From<Scope> for Scopes {
+    fn from(scope: Scope) -> Self {
+        Scopes { bits: scope.bit() }
+    }
+}
+
+impl ops::BitOr for Scopes {
+    type Output = Scopes;
+
+    fn bitor(self, other: Scopes) -> Scopes {
+        Scopes { bits: self.bits | other.bits }
+    }
+}
+
+impl ops::BitOr<Scope> for Scopes {
+    type Output = Scopes;
+
+    fn bitor(self, scope: Scope) -> Scopes {
+        self | Scopes::from(scope)
+    }
+}
+
+impl ops::BitOr for Scope {
+    type Output = Scopes;
+
+    fn bitor(self, scope: Scope) -> Scopes {
+        Scopes::from(self) | scope
+    }
+}
+
+impl fmt::Debug for Scopes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Scopes({})", self.join(" "))
+    }
+}
+
+/// The scopes separated by `%20`, for use in urls.
+impl fmt::Display for Scopes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.join("%20"))
+    }
+}
+
+/// Serialised separated by spaces, as apps are registered with.
+impl Serialize for Scopes {
+    fn serialize<S: Serializer>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.join(" "))
+    }
+}
+
+// The scope a request requires, given the path segments below the base
+// url, `None` for requests which don't require any. Deprecated scopes, such
+// as `follow`, are accounted for by `Scopes::grants`.
+pub(crate) fn required_scope(method: &Method, path: &[&str]) -> Option<Scope> {
+    use self::Scope::*;
+
+    let route = match *path {
+        ["api", _, ref route @ ..] => route,
+        _ => &[][..],
+    };
+
+    let (read, write) = match route {
+        ["instance", ..] | ["custom_emojis"] | ["apps", "verify_credentials"] => return None,
+        ["accounts", _, "follow"] | ["accounts", _, "unfollow"] | ["accounts", "relationships"] |
+        ["follow_requests", ..] | ["follows"] => (ReadFollows, WriteFollows),
+        ["accounts", _, "block"] | ["accounts", _, "unblock"] | ["blocks"] |
+        ["domain_blocks"] => (ReadBlocks, WriteBlocks),
+        ["accounts", _, "mute"] | ["accounts", _, "unmute"] | ["mutes"] |
+        ["statuses", _, "mute"] | ["statuses", _, "unmute"] => (ReadMutes, WriteMutes),
+        ["statuses", _, "favourite"] | ["statuses", _, "unfavourite"] |
+        ["favourites"] => (ReadFavourites, WriteFavourites),
+        ["statuses", _, "bookmark"] | ["statuses", _, "unbookmark"] |
+        ["bookmarks"] => (ReadBookmarks, WriteBookmarks),
+        ["timelines", "list", ..] | ["lists", ..] => (ReadLists, WriteLists),
+        ["accounts", "search"] => (ReadAccounts, WriteAccounts),
+        ["accounts", _, "statuses"] | ["statuses", ..] | ["timelines", ..] |
+        ["markers"] | ["streaming", ..] | ["polls", ..] => (ReadStatuses, WriteStatuses),
+        ["conversations", ..] => (ReadStatuses, WriteConversations),
+        ["notifications", ..] => (ReadNotifications, WriteNotifications),
+        ["filters", ..] => (ReadFilters, WriteFilters),
+        ["search"] => (ReadSearch, Write),
+        ["media", ..] => (WriteMedia, WriteMedia),
+        ["reports", ..] => (Read, WriteReports),
+        ["push", ..] => (Push, Push),
+        ["accounts", ..] | ["featured_tags", ..] | ["suggestions", ..] |
+        ["endorsements"] | ["preferences"] => (ReadAccounts, WriteAccounts),
+        ["admin", "accounts", ..] => (AdminReadAccounts, AdminWriteAccounts),
+        ["admin", "reports", ..] => (AdminReadReports, AdminWriteReports),
+        ["admin", "domain_allows", ..] => (AdminReadDomainAllows, AdminWriteDomainAllows),
+        ["admin", "domain_blocks", ..] => (AdminReadDomainBlocks, AdminWriteDomainBlocks),
+        ["admin", "ip_blocks", ..] => (AdminReadIpBlocks, AdminWriteIpBlocks),
+        ["admin", "email_domain_blocks", ..] => (AdminReadEmailDomainBlocks, AdminWriteEmailDomainBlocks),
+        ["admin", "canonical_email_blocks", ..] => (AdminReadCanonicalEmailBlocks, AdminWriteCanonicalEmailBlocks),
+        ["admin", ..] => (AdminRead, AdminWrite),
+        _ => (Read, Write),
+    };
+
+    Some(if *method == Method::Get { read } else { write })
+}
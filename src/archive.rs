@@ -0,0 +1,310 @@
+//! Archiving the statuses, favourites and bookmarks of the authenticated
+//! user, along with their media, to a directory:
+//!
+//! ```text
+//! archive/
+//!     progress.json
+//!     statuses/<id>.json
+//!     favourites/<id>.json
+//!     bookmarks/<id>.json
+//!     media/<attachment id>.<extension>
+//! ```
+//!
+//! Statuses are saved as the JSON returned by the instance, or appended to
+//! JSON Lines files in the collection's directory if `Archive::json_lines` is
+//! set. The position in each collection is saved after every page, so an
+//! interrupted archive continues where it stopped when run again.
+//!
+//! ```no_run
+//! # extern crate mammut;
+//! # use mammut::{Data, Mastodon};
+//! # use mammut::archive::Archive;
+//! # fn main() {
+//! #    try().unwrap();
+//! # }
+//! # fn try() -> mammut::Result<()> {
+//! # let mastodon = Mastodon::from_data(Data::from_env()?);
+//! let mut archive = Archive::open("archive")?;
+//! let summary = archive.run(&mastodon)?;
+//! println!("Archived {} statuses and {} media files", summary.statuses, summary.media);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fs::{self, File};
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+
+use json::{self, Value};
+
+use atomic_file;
+use entities::status::Status;
+use jsonl::{JsonLinesFile, Rotation};
+use page::Page;
+use super::{Error, Mastodon, Result};
+
+/// The collections an archive contains.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Collection {
+    /// The statuses posted by the user, including reblogs.
+    Statuses,
+    /// The statuses the user favourited.
+    Favourites,
+    /// The statuses the user bookmarked.
+    Bookmarks,
+}
+
+impl Collection {
+    /// Every collection, in the order they're archived.
+    pub const ALL: [Collection; 3] = [Collection::Statuses, Collection::Favourites, Collection::Bookmarks];
+
+    /// The name of the directory the collection is saved to.
+    pub fn name(self) -> &'static str {
+        match self {
+            Collection::Statuses => "statuses",
+            Collection::Favourites => "favourites",
+            Collection::Bookmarks => "bookmarks",
+        }
+    }
+}
+
+/// How far each collection has been archived.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Progress {
+    /// The url of the next page of statuses, `None` if not started yet.
+    pub statuses: Option<String>,
+    /// The url of the next page of favourites, `None` if not started yet.
+    pub favourites: Option<String>,
+    /// The url of the next page of bookmarks, `None` if not started yet.
+    pub bookmarks: Option<String>,
+    /// The collections which were archived completely.
+    pub done: Vec<String>,
+}
+
+impl Progress {
+    fn next(&mut self, collection: Collection) -> &mut Option<String> {
+        match collection {
+            Collection::Statuses => &mut self.statuses,
+            Collection::Favourites => &mut self.favourites,
+            Collection::Bookmarks => &mut self.bookmarks,
+        }
+    }
+
+    /// Whether `collection` was archived completely.
+    pub fn is_done(&self, collection: Collection) -> bool {
+        self.done.iter().any(|done| done == collection.name())
+    }
+}
+
+/// The number of files an archive run wrote.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Summary {
+    /// The number of statuses written.
+    pub statuses: usize,
+    /// The number of favourites written.
+    pub favourites: usize,
+    /// The number of bookmarks written.
+    pub bookmarks: usize,
+    /// The number of media attachments downloaded.
+    pub media: usize,
+}
+
+impl Summary {
+    fn count(&mut self, collection: Collection) -> &mut usize {
+        match collection {
+            Collection::Statuses => &mut self.statuses,
+            Collection::Favourites => &mut self.favourites,
+            Collection::Bookmarks => &mut self.bookmarks,
+        }
+    }
+}
+
+/// An archive in a directory on disk.
+#[derive(Debug)]
+pub struct Archive {
+    path: PathBuf,
+    progress: Progress,
+    /// The maximum size of media to download in bytes, `None` for no limit.
+    /// Larger attachments are skipped.
+    pub max_media_size: Option<u64>,
+    /// Whether to download the media of statuses. Enabled by default.
+    pub download_media: bool,
+    /// Appends statuses to JSON Lines files rotated as given, named
+    /// `<collection>/<collection>-<time>-<n>.jsonl`, instead of writing one
+    /// file per status. `None` by default.
+    ///
+    /// Statuses are then only skipped if their page was archived before:
+    /// deleting `progress.json` writes every status again, and the page being
+    /// archived when a run is interrupted is written again by the next run.
+    pub json_lines: Option<Rotation>,
+}
+
+impl Archive {
+    /// Opens the archive in the directory `path`, creating it if it doesn't
+    /// exist.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_owned();
+        fs::create_dir_all(path.join("media"))?;
+        for collection in &Collection::ALL {
+            fs::create_dir_all(path.join(collection.name()))?;
+        }
+
+        let progress = match File::open(path.join("progress.json")) {
+            Ok(file) => json::from_reader(file)?,
+            Err(ref e) if e.kind() == ErrorKind::NotFound => Progress::default(),
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(Archive {
+            path,
+            progress,
+            max_media_size: None,
+            download_media: true,
+            json_lines: None,
+        })
+    }
+
+    /// How far each collection has been archived.
+    pub fn progress(&self) -> &Progress {
+        &self.progress
+    }
+
+    /// Archives every collection which wasn't archived completely yet,
+    /// continuing where the last run stopped.
+    ///
+    /// Completed collections are skipped. To archive the statuses posted
+    /// since, delete `progress.json`: statuses and media already on disk
+    /// aren't written again.
+    pub fn run(&mut self, mastodon: &Mastodon) -> Result<Summary> {
+        let mut summary = Summary::default();
+
+        for &collection in &Collection::ALL {
+            if !self.progress.is_done(collection) {
+                self.archive(mastodon, collection, &mut summary)?;
+            }
+        }
+
+        Ok(summary)
+    }
+
+    fn archive(&mut self, mastodon: &Mastodon, collection: Collection, summary: &mut Summary)
+        -> Result<()>
+    {
+        let mut url = match self.progress.next(collection).clone() {
+            Some(url) => url,
+            None => match collection {
+                Collection::Statuses => {
+                    let me = mastodon.current_user()?;
+                    mastodon.route(&format!("/api/v1/accounts/{}/statuses", me.id))
+                },
+                Collection::Favourites => mastodon.route("/api/v1/favourites"),
+                Collection::Bookmarks => mastodon.route("/api/v1/bookmarks"),
+            },
+        };
+
+        let dir = self.path.join(collection.name());
+        let mut lines = match self.json_lines {
+            Some(rotation) => Some(JsonLinesFile::create(&dir, collection.name(), rotation)?),
+            None => None,
+        };
+
+        loop {
+            let response = mastodon.execute(&mut mastodon.client.get(&url))?;
+            let page: Page<'_, Value> = Page::new(mastodon, response)?;
+
+            for value in &page.initial_items {
+                let status: Status = json::from_value(value.clone())?;
+
+                if let Some(ref mut lines) = lines {
+                    lines.write(value)?;
+                    *summary.count(collection) += 1;
+                } else {
+                    let path = dir.join(format!("{}.json", file_name(&status.id)?));
+
+                    if !path.exists() {
+                        atomic_file::write(&path, |file| Ok(json::to_writer_pretty(file, value)?))?;
+                        *summary.count(collection) += 1;
+                    }
+                }
+
+                if self.download_media {
+                    summary.media += self.download(mastodon, &status)?;
+                }
+            }
+
+            let next = match page.next_url() {
+                Some(next) if !page.initial_items.is_empty() => next.to_string(),
+                _ => break,
+            };
+
+            if let Some(ref mut lines) = lines {
+                lines.flush()?;
+            }
+            *self.progress.next(collection) = Some(next.clone());
+            self.save()?;
+            url = next;
+        }
+
+        if let Some(ref mut lines) = lines {
+            lines.flush()?;
+        }
+        *self.progress.next(collection) = None;
+        self.progress.done.push(collection.name().to_owned());
+        self.save()
+    }
+
+    // Downloads the media of `status` and its reblog which weren't
+    // downloaded yet, returning how many were.
+    fn download(&self, mastodon: &Mastodon, status: &Status) -> Result<usize> {
+        let mut downloaded = 0;
+        let attachments = status.media_attachments.iter()
+            .chain(status.reblog.iter().flat_map(|reblog| &reblog.media_attachments));
+
+        for attachment in attachments {
+            let path = self.path.join("media").join(media_file_name(&attachment.id, &attachment.url)?);
+            if path.exists() {
+                continue;
+            }
+
+            let download = |file: &mut _| {
+                mastodon.download_attachment(attachment, file, self.max_media_size).map(|_| ())
+            };
+            match atomic_file::write(&path, download) {
+                Ok(()) => downloaded += 1,
+                Err(Error::TooLarge(size)) => warn!("Skipping attachment {} of {} bytes", attachment.url, size),
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(downloaded)
+    }
+
+    fn save(&self) -> Result<()> {
+        atomic_file::save_json(&self.path.join("progress.json"), &self.progress)
+    }
+}
+
+// The name an attachment is saved as: its id, with the extension of its url.
+fn media_file_name(id: &str, url: &str) -> Result<String> {
+    let id = file_name(id)?;
+    let name = url.split(['?', '#']).next().unwrap_or("").rsplit('/').next().unwrap_or("");
+
+    match name.rfind('.') {
+        Some(dot) if dot + 1 < name.len() && name[dot + 1..].bytes().all(|b| b.is_ascii_alphanumeric()) => {
+            Ok(format!("{}{}", id, &name[dot..]))
+        },
+        _ => Ok(id.to_owned()),
+    }
+}
+
+// Check an id returned by the instance is safe to use as a file name, so ids
+// such as `../x` can't write outside the archive.
+fn file_name(id: &str) -> Result<&str> {
+    if !id.is_empty() && id.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-') {
+        Ok(id)
+    } else {
+        Err(Error::Io(io::Error::new(io::ErrorKind::InvalidData, format!("invalid id {:?}", id))))
+    }
+}
+
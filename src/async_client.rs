@@ -0,0 +1,530 @@
+//! A client returning futures instead of blocking, for use in bots running on
+//! tokio. Only available with the `async` feature.
+//!
+//! It covers the routes without custom parameters, such as
+//! `get_home_timeline`, `get_status` or `favourite`, plus `new_status` and
+//! `download_attachment`, and
+//! doesn't support the dry-run, lenient or caching modes of the blocking
+//! client. Its other settings aren't supported either: requests are sent
+//! regardless of read-only mode and the scopes they require, responses
+//! aren't limited to a maximum size, there's no cancellation token, since
+//! dropping a future cancels its request, and the rate limit isn't tracked.
+//! Errors are reported like the blocking client's. Paginated routes return the `Pages` stream of their items, and
+//! the streaming API the `Events` stream, so they compose with the `Stream`
+//! combinators; errors end both streams.
+//!
+//! ```no_run
+//! # extern crate futures;
+//! # extern crate mammut;
+//! # extern crate tokio_core;
+//! # use futures::Future;
+//! # use mammut::Data;
+//! # use mammut::async_client::Mastodon;
+//! # use tokio_core::reactor::Core;
+//! # fn main() {
+//! #    try().unwrap();
+//! # }
+//! # fn try() -> mammut::Result<()> {
+//! let mut core = Core::new()?;
+//! let mastodon = Mastodon::from_data(Data::from_env()?, &core.handle());
+//!
+//! let timeline = mastodon.get_home_timeline()
+//!     .map(|statuses| statuses.len());
+//! println!("{} statuses", core.run(timeline)?);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! ```no_run
+//! # extern crate futures;
+//! # extern crate mammut;
+//! # extern crate tokio_core;
+//! # use futures::Stream;
+//! # use mammut::Data;
+//! # use mammut::async_client::Mastodon;
+//! # use tokio_core::reactor::Core;
+//! # fn main() {
+//! #    try().unwrap();
+//! # }
+//! # fn try() -> mammut::Result<()> {
+//! # let mut core = Core::new()?;
+//! # let mastodon = Mastodon::from_data(Data::from_env()?, &core.handle());
+//! let bots = mastodon.followers(1)
+//!     .filter(|account| account.bot)
+//!     .take(10)
+//!     .collect();
+//! println!("{} bots", core.run(bots)?.len());
+//! # Ok(())
+//! # }
+//! ```
+
+use std::borrow::Cow;
+use std::collections::{HashMap, VecDeque};
+use std::io::Write;
+use std::{mem, ops, vec};
+
+use futures::{future, Async, Future, Poll, Stream};
+use reqwest::header::{Authorization, Bearer, ContentLength, Headers};
+use reqwest::unstable::async::{Client, Decoder, RequestBuilder, Response};
+use serde::Deserialize;
+use tokio_core::reactor::Handle;
+
+use entities::prelude::*;
+use page::Links;
+use requests::PageRequest;
+use status_builder::StatusBuilder;
+use streaming::{self, Event, Stream as Timeline};
+use super::{from_body, route, ApiError, Data, Error, Result};
+
+/// A boxed future resolving to the result of a request.
+pub type BoxFuture<T> = Box<dyn Future<Item = T, Error = Error>>;
+
+macro_rules! methods {
+    ($($method:ident,)+) => {
+        $(
+            fn $method<T: for<'de> serde::Deserialize<'de> + 'static>(&self, url: String)
+            -> BoxFuture<T>
+            {
+                self.send(&mut self.client.$method(&url))
+            }
+         )+
+    };
+}
+
+macro_rules! route {
+
+    ((post multipart ($($param:ident: $typ:ty,)*)) $name:ident: $url:expr => $ret:ty, $($rest:tt)*) => {
+        // Multipart uploads aren't supported by reqwest's async client.
+        route!{$($rest)*}
+    };
+
+    (($method:ident ($($param:ident: $typ:ty,)*)) $name:ident: $url:expr => $ret:ty, $($rest:tt)*) => {
+        /// Equivalent to `/api/v1/
+        #[doc = $url]
+        /// `
+        pub fn $name(&self, $($param: $typ,)*) -> BoxFuture<$ret> {
+
+            let form_data = json!({
+                $(
+                    stringify!($param): $param,
+                )*
+            });
+
+            self.send(self.client.$method(&self.route(concat!("/api/v1/", $url)))
+                .json(&form_data))
+        }
+
+        route!{$($rest)*}
+    };
+
+    (($method:ident) $name:ident: $url:expr => $ret:ty, $($rest:tt)*) => {
+        /// Equivalent to `/api/v1/
+        #[doc = $url]
+        /// `
+        pub fn $name(&self) -> BoxFuture<$ret> {
+            self.$method(self.route(concat!("/api/v1/", $url)))
+        }
+
+        route!{$($rest)*}
+    };
+
+    () => {}
+}
+
+macro_rules! paged_routes {
+
+    ($(($method:ident) $name:ident: $url:expr => $ret:ty,)*) => {
+        $(
+            /// Equivalent to `/api/v1/
+            #[doc = $url]
+            /// `, streaming the items of every page.
+            pub fn $name(&self) -> Pages<$ret> {
+                Pages::new(self, self.route(concat!("/api/v1/", $url)))
+            }
+         )*
+    }
+
+}
+
+macro_rules! paged_routes_id {
+
+    ($(($method:ident) $name:ident: $url:expr => $ret:ty,)*) => {
+        $(
+            /// Equivalent to `/api/v1/
+            #[doc = $url]
+            /// `, streaming the items of every page.
+            pub fn $name(&self, id: u64) -> Pages<$ret> {
+                Pages::new(self, self.route(&format!(concat!("/api/v1/", $url), id)))
+            }
+         )*
+    }
+
+}
+
+macro_rules! paged_routes_request {
+
+    ($(($method:ident) $name:ident: $url:expr => $ret:ty,)*) => {
+        $(
+            /// Equivalent to `/api/v1/
+            #[doc = $url]
+            /// `, streaming the items of every page starting at the page of
+            /// `request`.
+            pub fn $name(&self, request: PageRequest) -> Pages<$ret> {
+                Pages::new(self, self.route(&format!(concat!("/api/v1/", $url, "{}"), request.to_query_string())))
+            }
+         )*
+    }
+
+}
+
+macro_rules! route_id {
+
+    ($(($method:ident) $name:ident: $url:expr => $ret:ty,)*) => {
+        $(
+            /// Equivalent to `/api/v1/
+            #[doc = $url]
+            /// `
+            pub fn $name(&self, id: u64) -> BoxFuture<$ret> {
+                self.$method(self.route(&format!(concat!("/api/v1/", $url), id)))
+            }
+         )*
+    }
+
+}
+
+/// A client for the Mastodon API returning futures, see the module
+/// documentation.
+#[derive(Clone, Debug)]
+pub struct Mastodon {
+    client: Client,
+    headers: Headers,
+    /// Raw data about your mastodon instance.
+    pub data: Data,
+}
+
+impl Mastodon {
+    /// Creates a client from the data struct, running its requests on the
+    /// event loop of `handle`.
+    pub fn from_data(data: Data, handle: &Handle) -> Self {
+        Mastodon::from_data_with_client(data, Client::new(handle))
+    }
+
+    /// Creates a client from the data struct, sending requests with
+    /// `client`.
+    pub fn from_data_with_client(data: Data, client: Client) -> Self {
+        let mut headers = Headers::new();
+        headers.set(Authorization(Bearer { token: (*data.token).to_owned() }));
+
+        Mastodon {
+            client,
+            headers,
+            data,
+        }
+    }
+
+    paged_routes! {
+        (get) blocks: "blocks" => Account,
+        (get) domain_blocks: "domain_blocks" => String,
+        (get) follow_requests: "follow_requests" => Account,
+        (get) bookmarks: "bookmarks" => Status,
+        (get) favourites: "favourites" => Status,
+        (get) mutes: "mutes" => Account,
+    }
+
+    paged_routes_id! {
+        (get) followers: "accounts/{}/followers" => Account,
+        (get) following: "accounts/{}/following" => Account,
+    }
+
+    paged_routes_request! {
+        (get) get_home_timeline_with: "timelines/home" => Status,
+        (get) notifications_with: "notifications" => Notification,
+    }
+
+    api_routes!(route);
+    api_routes_id!(route_id);
+
+    /// Streams the events of `stream`, see the blocking
+    /// `Mastodon::stream_user`. The stream connects when it's first polled,
+    /// and ends when the instance closes the connection.
+    pub fn stream(&self, stream: &Timeline) -> Events {
+        let url = self.route(&format!("/api/v1/streaming/{}", stream.path()));
+        Events::new(self.execute(&mut self.client.get(&url)))
+    }
+
+    /// Post a new status to the account.
+    pub fn new_status(&self, status: StatusBuilder) -> BoxFuture<Status> {
+        self.send(self.client.post(&self.route("/api/v1/statuses"))
+            .json(&status))
+    }
+
+    /// Downloads the file of an attachment into `writer`, resolving to the
+    /// writer and the number of bytes written, see the blocking
+    /// `Mastodon::download_attachment`. Fails with `Error::TooLarge` once the
+    /// file exceeds `max_size` bytes.
+    pub fn download_attachment<W: Write + 'static>(&self, attachment: &Attachment, writer: W, max_size: Option<u64>)
+        -> BoxFuture<(W, u64)>
+    {
+        // Media is often served from another host, so the access token isn't
+        // sent.
+        Box::new(self.client.get(&attachment.url).send()
+            .map_err(Error::from)
+            .and_then(move |response| {
+                let status = response.status();
+
+                if status.is_client_error() {
+                    return Err(Error::Client(status));
+                } else if status.is_server_error() {
+                    return Err(Error::Server(status));
+                }
+
+                if let (Some(max_size), Some(&ContentLength(length))) = (max_size, response.headers().get::<ContentLength>()) {
+                    if length > max_size {
+                        return Err(Error::TooLarge(max_size));
+                    }
+                }
+
+                Ok(response.into_body())
+            })
+            .and_then(move |body| body.map_err(Error::from).fold((writer, 0), move |(mut writer, written), chunk| {
+                let written = written + chunk.len() as u64;
+
+                match max_size {
+                    Some(max_size) if written > max_size => Err(Error::TooLarge(max_size)),
+                    _ => {
+                        writer.write_all(&chunk)?;
+                        Ok((writer, written))
+                    },
+                }
+            })))
+    }
+
+    methods![get, post, delete,];
+
+    fn send<T: for<'de> serde::Deserialize<'de> + 'static>(&self, builder: &mut RequestBuilder)
+        -> BoxFuture<T>
+    {
+        Box::new(self.execute(builder).and_then(read))
+    }
+
+    // Sends the request, resolving to the response if it was successful.
+    fn execute(&self, builder: &mut RequestBuilder) -> BoxFuture<Response> {
+        let request = match builder.headers(self.headers.clone()).build() {
+            Ok(request) => request,
+            Err(e) => return Box::new(future::err(e.into())),
+        };
+
+        Box::new(self.client.execute(request)
+            .map_err(Error::from)
+            .and_then(|response| -> BoxFuture<Response> {
+                if response.status().is_success() {
+                    return Box::new(future::ok(response));
+                }
+
+                Box::new(error_from(response).and_then(Err))
+            }))
+    }
+
+    fn route(&self, url: &str) -> String {
+        route(&self.base, url)
+    }
+}
+
+impl ops::Deref for Mastodon {
+    type Target = Data;
+
+    fn deref(&self) -> &Self::Target {
+        &self.data
+    }
+}
+
+/// The items of a paginated route and of every following page, fetching the
+/// next page when the items of the previous one are used up, like
+/// `Page::items_iter`. The first page is fetched when the stream is first
+/// polled.
+pub struct Pages<T> {
+    mastodon: Mastodon,
+    next: Option<String>,
+    fetching: Option<BoxFuture<(Vec<T>, Option<String>)>>,
+    items: vec::IntoIter<T>,
+}
+
+impl<T: for<'de> Deserialize<'de> + 'static> Pages<T> {
+    fn new(mastodon: &Mastodon, url: String) -> Self {
+        Pages {
+            mastodon: mastodon.clone(),
+            next: Some(url),
+            fetching: None,
+            items: Vec::new().into_iter(),
+        }
+    }
+
+    // Fetches the items of the page at `url` and the url of the next one.
+    fn fetch(&self, url: &str) -> BoxFuture<(Vec<T>, Option<String>)> {
+        Box::new(self.mastodon.execute(&mut self.mastodon.client.get(url))
+            .and_then(|response| {
+                let next = Links::from_headers(response.headers())
+                    .map(|links| links.next.map(|url| url.into_string()));
+
+                future::result(next).join(read(response))
+            })
+            .map(|(next, items)| (items, next)))
+    }
+}
+
+impl<T: for<'de> Deserialize<'de> + 'static> Stream for Pages<T> {
+    type Item = T;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Option<T>, Error> {
+        loop {
+            if let Some(item) = self.items.next() {
+                return Ok(Async::Ready(Some(item)));
+            }
+
+            let page = match self.fetching {
+                Some(ref mut fetching) => fetching.poll(),
+                None => match self.next.take() {
+                    Some(url) => {
+                        self.fetching = Some(self.fetch(&url));
+                        continue;
+                    },
+                    None => return Ok(Async::Ready(None)),
+                },
+            };
+
+            match page {
+                Ok(Async::NotReady) => return Ok(Async::NotReady),
+                Ok(Async::Ready((items, next))) => {
+                    self.fetching = None;
+                    if !items.is_empty() {
+                        self.items = items.into_iter();
+                        self.next = next;
+                    }
+                },
+                Err(e) => {
+                    self.fetching = None;
+                    return Err(e);
+                },
+            }
+        }
+    }
+}
+
+/// The events of a stream, returned by `Mastodon::stream`. Events are read
+/// as in the blocking `streaming::EventStream`.
+pub struct Events {
+    connecting: Option<BoxFuture<Response>>,
+    body: Option<Decoder>,
+    buffer: Vec<u8>,
+    events: VecDeque<Result<Event>>,
+}
+
+impl Events {
+    fn new(connecting: BoxFuture<Response>) -> Self {
+        Events {
+            connecting: Some(connecting),
+            body: None,
+            buffer: Vec::new(),
+            events: VecDeque::new(),
+        }
+    }
+
+    // Parses the events in the buffer which are complete, ending with a
+    // blank line, or all of them at the end of the stream.
+    fn parse(&mut self, end: bool) {
+        let mut complete = 0;
+        let mut line = 0;
+
+        for (i, &byte) in self.buffer.iter().enumerate() {
+            if byte == b'\n' {
+                if i == line || self.buffer[line..i] == *b"\r" {
+                    complete = i + 1;
+                }
+                line = i + 1;
+            }
+        }
+
+        let rest = if end { Vec::new() } else { self.buffer.split_off(complete) };
+        let events = mem::replace(&mut self.buffer, rest);
+        self.events.extend(streaming::EventStream::new(&events[..]));
+    }
+}
+
+impl Stream for Events {
+    type Item = Event;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Option<Event>, Error> {
+        loop {
+            if let Some(event) = self.events.pop_front() {
+                return event.map(|event| Async::Ready(Some(event)));
+            }
+
+            if let Some(ref mut body) = self.body {
+                match body.poll() {
+                    Ok(Async::NotReady) => return Ok(Async::NotReady),
+                    Ok(Async::Ready(Some(chunk))) => {
+                        self.buffer.extend_from_slice(&chunk);
+                        self.parse(false);
+                        continue;
+                    },
+                    Ok(Async::Ready(None)) => {},
+                    Err(e) => {
+                        self.body = None;
+                        return Err(e.into());
+                    },
+                }
+            } else {
+                let response = match self.connecting {
+                    Some(ref mut connecting) => connecting.poll(),
+                    None => return Ok(Async::Ready(None)),
+                };
+
+                match response {
+                    Ok(Async::NotReady) => return Ok(Async::NotReady),
+                    Ok(Async::Ready(response)) => {
+                        self.connecting = None;
+                        self.body = Some(response.into_body());
+                    },
+                    Err(e) => {
+                        self.connecting = None;
+                        return Err(e);
+                    },
+                }
+                continue;
+            }
+
+            self.body = None;
+            self.parse(true);
+        }
+    }
+}
+
+// Deserialise the body of a successful response.
+fn read<T: for<'de> serde::Deserialize<'de> + 'static>(response: Response) -> BoxFuture<T> {
+    Box::new(body(response).and_then(|body| from_body(&body, false)))
+}
+
+// The error a failed response contains, like the blocking client's: the API
+// error in its body along with its status, or else a client error for 4xx
+// statuses and a server error for any other.
+fn error_from(response: Response) -> BoxFuture<Error> {
+    let status = response.status();
+
+    Box::new(body(response).then(move |body| Ok(match body.and_then(|body| from_body::<ApiError>(&body, false)) {
+        Ok(error) => Error::Api(ApiError { status: Some(status), ..error }),
+        Err(_) if status.is_client_error() => Error::Client(status),
+        Err(_) => Error::Server(status),
+    })))
+}
+
+// Read the whole body of a response.
+fn body(response: Response) -> BoxFuture<Vec<u8>> {
+    Box::new(response.into_body()
+        .map_err(Error::from)
+        .fold(Vec::new(), |mut body, chunk| {
+            body.extend_from_slice(&chunk);
+            Ok::<_, Error>(body)
+        }))
+}
@@ -0,0 +1,50 @@
+// Saving files atomically: the contents are written to a temporary file next
+// to it, synced to disk, then renamed over it, so a crash can't leave the file
+// half written.
+
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+use json;
+use serde::Serialize;
+
+use super::Result;
+
+// Saves `value` as JSON to `path`.
+pub(crate) fn save_json<T: Serialize + ?Sized>(path: &Path, value: &T) -> Result<()> {
+    write(path, |writer| Ok(json::to_writer(writer, value)?))
+}
+
+// Saves the contents written by `write` to `path`. If it fails, the temporary
+// file is removed and `path` is left as it was.
+pub(crate) fn write<F>(path: &Path, write: F) -> Result<()>
+    where F: FnOnce(&mut BufWriter<File>) -> Result<()>
+{
+    let tmp = tmp_path(path);
+    let result = write_to(&tmp, write).and_then(|()| Ok(fs::rename(&tmp, path)?));
+
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+
+    result
+}
+
+fn write_to<F>(tmp: &Path, write: F) -> Result<()>
+    where F: FnOnce(&mut BufWriter<File>) -> Result<()>
+{
+    let mut writer = BufWriter::new(File::create(tmp)?);
+    write(&mut writer)?;
+
+    let file = writer.into_inner().map_err(|e| e.into_error())?;
+    file.sync_all()?;
+
+    Ok(())
+}
+
+fn tmp_path(path: &Path) -> PathBuf {
+    let mut tmp = path.to_owned().into_os_string();
+    tmp.push(".tmp");
+    tmp.into()
+}
@@ -0,0 +1,131 @@
+// Reading the bodies of responses, within the maximum size set with
+// `Mastodon::set_max_response_size`.
+
+use std::io::{self, BufReader, Read};
+
+use json;
+use reqwest::Response;
+use reqwest::header::ContentLength;
+use serde::Deserialize;
+
+use super::{ApiError, Error, Result};
+
+// The number of bytes kept of a body deserialised from the response as it's
+// read, for retrying it as an `ApiError`. Errors are much smaller than this.
+const RECORDED: usize = 4096;
+
+// Read the whole body of a response.
+pub fn read(mut response: Response, max_size: Option<u64>) -> Result<Vec<u8>> {
+    check_length(&response, max_size)?;
+
+    let mut vec = Vec::new();
+    let max_size = match max_size {
+        Some(max_size) => max_size,
+        None => {
+            response.read_to_end(&mut vec)?;
+            return Ok(vec);
+        },
+    };
+
+    // The length header may be missing or wrong, so read one byte more than
+    // allowed to detect larger bodies.
+    response.take(max_size + 1).read_to_end(&mut vec)?;
+
+    if vec.len() as u64 > max_size {
+        Err(Error::TooLarge(max_size))
+    } else {
+        Ok(vec)
+    }
+}
+
+// Deserialise the body of a response as it's read, rather than reading all
+// of it into memory first, so large pages don't need twice the memory.
+pub fn deserialise<T: for<'de> Deserialize<'de>>(response: Response, max_size: Option<u64>) -> Result<T> {
+    check_length(&response, max_size)?;
+
+    let mut reader = BufReader::new(Recording::new(response, max_size));
+
+    match json::from_reader(&mut reader) {
+        Ok(t) => Ok(t),
+        Err(e) => {
+            let recording = reader.into_inner();
+
+            if let (true, Some(max_size)) = (recording.too_large, max_size) {
+                return Err(Error::TooLarge(max_size));
+            }
+
+            // If deserializing into the desired type fails try again to see
+            // if this is an error response.
+            if let Some(body) = recording.into_body() {
+                if let Ok(error) = json::from_slice::<ApiError>(&body) {
+                    return Err(Error::Api(error));
+                }
+            }
+
+            Err(e.into())
+        },
+    }
+}
+
+fn check_length(response: &Response, max_size: Option<u64>) -> Result<()> {
+    if let (Some(&ContentLength(length)), Some(max_size)) = (response.headers().get::<ContentLength>(), max_size) {
+        if length > max_size {
+            return Err(Error::TooLarge(max_size));
+        }
+    }
+
+    Ok(())
+}
+
+// Keeps the first `RECORDED` bytes read, and fails reads past `max_size`.
+struct Recording<R> {
+    inner: R,
+    recorded: Vec<u8>,
+    total: u64,
+    max_size: Option<u64>,
+    too_large: bool,
+}
+
+impl<R: Read> Recording<R> {
+    fn new(inner: R, max_size: Option<u64>) -> Self {
+        Recording {
+            inner,
+            recorded: Vec::new(),
+            total: 0,
+            max_size,
+            too_large: false,
+        }
+    }
+
+    // The whole body, if it's no longer than `RECORDED` bytes.
+    fn into_body(mut self) -> Option<Vec<u8>> {
+        let mut buf = [0; 512];
+
+        while self.total as usize == self.recorded.len() {
+            match self.read(&mut buf) {
+                Ok(0) => return Some(self.recorded),
+                Ok(_) => {},
+                Err(_) => return None,
+            }
+        }
+
+        None
+    }
+}
+
+impl<R: Read> Read for Recording<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.total += n as u64;
+
+        if self.max_size.is_some_and(|max_size| self.total > max_size) {
+            self.too_large = true;
+            return Err(io::Error::other("the response exceeds the maximum size"));
+        }
+
+        let recorded = n.min(RECORDED.saturating_sub(self.recorded.len()));
+        self.recorded.extend_from_slice(&buf[..recorded]);
+
+        Ok(n)
+    }
+}
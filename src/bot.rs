@@ -0,0 +1,304 @@
+//! A small framework for bots answering commands in mentions, such as
+//! `@dicebot !roll 2d6`.
+//!
+//! ```no_run
+//! # extern crate mammut;
+//! # use mammut::{Data, Mastodon};
+//! # use mammut::bot::Bot;
+//! # use std::time::Duration;
+//! # fn main() {
+//! #    try().unwrap();
+//! # }
+//! # fn try() -> mammut::Result<()> {
+//! let mut bot = Bot::new(Mastodon::from_data(Data::from_env()?));
+//!
+//! bot.command("ping", |_, _| Ok(Some("pong".to_owned())));
+//! bot.command("echo", |_, command| Ok(Some(command.args.to_owned())));
+//!
+//! bot.run(Duration::from_secs(30));
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{HashMap, VecDeque};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use dedup::Seen;
+use html;
+use entities::notification::{Notification, NotificationType};
+use entities::status::Status;
+use status_builder::StatusBuilder;
+use super::{Mastodon, Result};
+
+/// A command found in a mention.
+#[derive(Debug)]
+pub struct Command<'a> {
+    /// The name of the command, without the prefix.
+    pub name: &'a str,
+    /// The text following the command name, trimmed.
+    pub args: &'a str,
+    /// The status mentioning the bot.
+    pub status: &'a Status,
+}
+
+/// Handles a command, returning the text to reply with, if any.
+pub type Handler = Box<dyn FnMut(&Mastodon, &Command) -> Result<Option<String>> + Send>;
+
+/// Dispatches commands in mentions to their handlers, and posts the replies.
+pub struct Bot {
+    mastodon: Mastodon,
+    handlers: HashMap<String, Handler>,
+    seen: Seen<String>,
+    since_id: Option<String>,
+    last_command: HashMap<String, Instant>,
+    /// The character commands start with, `!` by default.
+    pub prefix: char,
+    /// The time an account has to wait between two commands, ten seconds by
+    /// default. Commands sent sooner are ignored.
+    pub cooldown: Duration,
+}
+
+impl Bot {
+    /// Creates a bot without any commands posting as the account of
+    /// `mastodon`.
+    pub fn new(mastodon: Mastodon) -> Self {
+        Bot {
+            mastodon,
+            handlers: HashMap::new(),
+            seen: Seen::new(1000),
+            since_id: None,
+            last_command: HashMap::new(),
+            prefix: '!',
+            cooldown: Duration::from_secs(10),
+        }
+    }
+
+    /// The client the bot posts with.
+    pub fn mastodon(&self) -> &Mastodon {
+        &self.mastodon
+    }
+
+    /// Registers the handler of the command `name`, replacing any previous
+    /// handler of the command.
+    pub fn command<F>(&mut self, name: &str, handler: F) -> &mut Self
+        where F: FnMut(&Mastodon, &Command) -> Result<Option<String>> + Send + 'static
+    {
+        self.handlers.insert(name.to_lowercase(), Box::new(handler));
+        self
+    }
+
+    /// Handles a notification, such as one received from a stream. Returns
+    /// the reply posted, if any.
+    ///
+    /// Notifications which aren't mentions, or which were handled before, are
+    /// ignored, so it's safe to pass notifications from both polling and a
+    /// stream.
+    pub fn handle_notification(&mut self, notification: &Notification) -> Result<Option<Status>> {
+        if notification.notification_type != NotificationType::Mention ||
+            !self.seen.is_new_notification(notification)
+        {
+            return Ok(None);
+        }
+
+        match notification.status {
+            Some(ref status) => self.handle_status(status),
+            None => Ok(None),
+        }
+    }
+
+    /// Runs the command in a status mentioning the bot, replying to the
+    /// status with the handler's response. Returns the reply posted, if any.
+    pub fn handle_status(&mut self, status: &Status) -> Result<Option<Status>> {
+        let text = html::to_text(&status.content);
+        let prefix = self.prefix;
+        let (name, args) = match text.split_whitespace()
+            .position(|word| word.starts_with(prefix) && word.len() > prefix.len_utf8())
+        {
+            Some(i) => {
+                let mut rest = text.split_whitespace().skip(i);
+                let name = rest.next().unwrap_or("")[prefix.len_utf8()..].to_lowercase();
+                (name, rest.collect::<Vec<_>>().join(" "))
+            },
+            None => return Ok(None),
+        };
+
+        let handler = match self.handlers.get_mut(&name) {
+            Some(handler) => handler,
+            None => return Ok(None),
+        };
+
+        let now = Instant::now();
+        if let Some(last) = self.last_command.get(&status.account.acct) {
+            if now.duration_since(*last) < self.cooldown {
+                debug!("Ignoring !{} from {}, sent during the cooldown", name, status.account.acct);
+                return Ok(None);
+            }
+        }
+        self.last_command.insert(status.account.acct.clone(), now);
+
+        let command = Command {
+            name: &name,
+            args: &args,
+            status,
+        };
+
+        let reply = match handler(&self.mastodon, &command)? {
+            Some(reply) => reply,
+            None => return Ok(None),
+        };
+
+        self.mastodon.reply_to(status, StatusBuilder::new(reply))
+    }
+
+    /// Fetches the mentions received since the last poll and handles them,
+    /// returning the result of each. The first poll only records the newest
+    /// mention, so the bot doesn't answer mentions sent before it started.
+    pub fn poll(&mut self) -> Result<Vec<Result<Option<Status>>>> {
+        let mut url = self.mastodon.route("/api/v1/notifications?types[]=mention");
+
+        if let Some(ref since_id) = self.since_id {
+            url += "&since_id=";
+            url += since_id;
+        }
+
+        let notifications: Vec<Notification> = self.mastodon.get(url)?;
+        let first_poll = self.since_id.is_none();
+
+        if let Some(newest) = notifications.first() {
+            self.since_id = Some(newest.id.clone());
+        }
+
+        if first_poll {
+            for notification in &notifications {
+                self.seen.insert(notification.id.clone());
+            }
+
+            return Ok(Vec::new());
+        }
+
+        // Oldest first, so commands are answered in the order they were sent.
+        Ok(notifications.iter()
+            .rev()
+            .map(|notification| self.handle_notification(notification))
+            .collect())
+    }
+
+    /// Polls for mentions every `interval` forever, logging errors.
+    pub fn run(&mut self, interval: Duration) {
+        loop {
+            match self.poll() {
+                Ok(results) => {
+                    for result in results {
+                        if let Err(e) = result {
+                            warn!("Failed to handle a command: {}", e);
+                        }
+                    }
+                },
+                Err(e) => warn!("Failed to fetch mentions: {}", e),
+            }
+
+            thread::sleep(interval);
+        }
+    }
+}
+
+/// An endless iterator over the statuses mentioning the authenticated
+/// account, returned by `Mastodon::poll_mentions`.
+#[derive(Debug)]
+pub struct Mentions<'a> {
+    mastodon: &'a Mastodon,
+    interval: Duration,
+    since_id: Option<String>,
+    pending: VecDeque<Status>,
+    polled: bool,
+    primed: bool,
+}
+
+impl<'a> Iterator for Mentions<'a> {
+    type Item = Result<Status>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(status) = self.pending.pop_front() {
+                return Some(Ok(status));
+            }
+
+            if self.polled {
+                thread::sleep(self.interval);
+            }
+
+            self.polled = true;
+
+            let mut url = self.mastodon.route("/api/v1/notifications?types[]=mention");
+            if let Some(ref since_id) = self.since_id {
+                url += "&since_id=";
+                url += since_id;
+            }
+
+            let notifications: Vec<Notification> = match self.mastodon.get(url) {
+                Ok(notifications) => notifications,
+                Err(e) => return Some(Err(e)),
+            };
+
+            if let Some(newest) = notifications.first() {
+                self.since_id = Some(newest.id.clone());
+            }
+
+            if !self.primed {
+                self.primed = true;
+            } else {
+                self.pending.extend(notifications.into_iter()
+                    .rev()
+                    .filter(|notification| notification.notification_type == NotificationType::Mention)
+                    .filter_map(|notification| notification.status));
+            }
+        }
+    }
+}
+
+impl Mastodon {
+    /// Polls the notifications every `interval` for new mentions, yielding
+    /// the mentioning statuses oldest first. Mentions received before the
+    /// first poll are skipped. See `stream_mentions` for receiving them from
+    /// the streaming API instead.
+    ///
+    /// ```no_run
+    /// # extern crate mammut;
+    /// # use mammut::{Data, Mastodon};
+    /// # use std::time::Duration;
+    /// # fn main() {
+    /// # let mastodon = Mastodon::from_data(Data::from_env().unwrap());
+    /// for status in mastodon.poll_mentions(Duration::from_secs(30)) {
+    ///     match status {
+    ///         Ok(status) => println!("{}: {}", status.account.acct, status.content),
+    ///         Err(e) => println!("Failed to fetch mentions: {}", e),
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn poll_mentions(&self, interval: Duration) -> Mentions<'_> {
+        Mentions {
+            mastodon: self,
+            interval,
+            since_id: None,
+            pending: VecDeque::new(),
+            polled: false,
+            primed: false,
+        }
+    }
+
+    /// Posts `reply` as a reply to `status`, see `StatusBuilder::reply_to`.
+    ///
+    /// To avoid two bots replying to each other forever, nothing is posted
+    /// and `None` returned if `status` was posted by an account marked as a
+    /// bot, or by the authenticated account itself.
+    pub fn reply_to(&self, status: &Status, reply: StatusBuilder) -> Result<Option<Status>> {
+        if status.account.bot || status.account.id == self.current_user()?.id {
+            debug!("Not replying to {}, posted by a bot", status.uri);
+            return Ok(None);
+        }
+
+        self.new_status(reply.reply_to(status)?).map(Some)
+    }
+}
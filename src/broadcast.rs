@@ -0,0 +1,96 @@
+//! Posting the same status from several accounts, possibly on different
+//! instances.
+//!
+//! ```no_run
+//! # extern crate mammut;
+//! # use mammut::{Data, Mastodon, StatusBuilder};
+//! # use mammut::broadcast;
+//! # fn main() {
+//! # let work = Mastodon::from_data(Data::from_env().unwrap());
+//! # let personal = work.clone();
+//! let status = StatusBuilder::new("Mammut 0.10 is out!".into());
+//!
+//! for delivery in broadcast::broadcast_with_media(status, &["release.png"], &[work, personal]) {
+//!     if let Err(ref e) = delivery.result {
+//!         println!("Couldn't post from client {}: {}", delivery.client, e);
+//!     }
+//! }
+//! # }
+//! ```
+
+use std::path::Path;
+use std::thread;
+
+use entities::status::Status;
+use outbox::idempotency_key;
+use status_builder::StatusBuilder;
+use super::{Mastodon, Result};
+
+/// The outcome of posting a status from one client.
+#[derive(Debug)]
+pub struct Delivery {
+    /// The index of the client in the slice passed to `broadcast`.
+    pub client: usize,
+    /// The `Idempotency-Key` the status was posted with. Retrying with
+    /// `Mastodon::new_status_with_key`, this key and `status` won't post it
+    /// twice.
+    pub key: String,
+    /// The status as posted from this client, with the ids of the media
+    /// uploaded to its instance. `None` if uploading the media failed.
+    pub status: Option<StatusBuilder>,
+    /// The posted status, or the error encountered uploading the media or
+    /// posting it.
+    pub result: Result<Status>,
+}
+
+/// Posts `status` from each of `clients` concurrently, returning the outcome
+/// for each client in order.
+///
+/// The media ids of a status are only valid on the instance they were
+/// uploaded to, so use `broadcast_with_media` for statuses with media.
+pub fn broadcast(status: StatusBuilder, clients: &[Mastodon]) -> Vec<Delivery> {
+    broadcast_with_media::<&Path>(status, &[], clients)
+}
+
+/// Uploads the files at `media` to the instance of each of `clients`, and
+/// posts `status` with them attached, concurrently. Returns the outcome for
+/// each client in order.
+pub fn broadcast_with_media<P: AsRef<Path>>(status: StatusBuilder, media: &[P], clients: &[Mastodon])
+    -> Vec<Delivery>
+{
+    let media: Vec<String> = media.iter()
+        .map(|path| path.as_ref().to_string_lossy().into_owned())
+        .collect();
+
+    let handles: Vec<_> = clients.iter()
+        .cloned()
+        .enumerate()
+        .map(|(i, client)| {
+            let mut status = status.clone();
+            let media = media.clone();
+            let key = idempotency_key(i);
+
+            thread::spawn(move || {
+                if !media.is_empty() {
+                    match upload(&client, &media) {
+                        Ok(ids) => status.media_ids = Some(ids),
+                        Err(e) => return Delivery { client: i, key, status: None, result: Err(e) },
+                    }
+                }
+
+                let result = client.new_status_with_key(status.clone(), &key);
+                Delivery { client: i, key, status: Some(status), result }
+            })
+        })
+        .collect();
+
+    handles.into_iter()
+        .map(|handle| handle.join().expect("broadcast thread panicked"))
+        .collect()
+}
+
+fn upload(client: &Mastodon, media: &[String]) -> Result<Vec<u64>> {
+    media.iter()
+        .map(|path| Ok(client.media(path.clone().into())?.id.parse()?))
+        .collect()
+}
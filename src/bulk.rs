@@ -0,0 +1,218 @@
+use std::collections::HashSet;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use chrono::Utc;
+
+use entities::account::Account;
+use super::{Error, Mastodon, Result};
+
+/// Options for following many accounts with `Mastodon::follow_many`.
+#[derive(Debug, Clone, Copy)]
+pub struct FollowOptions {
+    /// Time to wait between following two accounts.
+    pub delay: Duration,
+    /// Whether to wait for the rate limit to reset once it's exhausted,
+    /// instead of failing the remaining follows.
+    pub wait_for_rate_limit: bool,
+}
+
+impl Default for FollowOptions {
+    fn default() -> Self {
+        FollowOptions {
+            delay: Duration::from_secs(1),
+            wait_for_rate_limit: true,
+        }
+    }
+}
+
+/// The number of accounts `Mastodon::get_accounts` fetches at once.
+pub const GET_ACCOUNTS_CONCURRENCY: usize = 4;
+
+/// How the authenticated user's followers and follows overlap, returned by
+/// `Mastodon::relationship_report`.
+#[derive(Debug, Clone, Default)]
+pub struct RelationshipReport {
+    /// Accounts that follow the user and that the user follows.
+    pub mutuals: Vec<Account>,
+    /// Accounts the user follows that don't follow the user back.
+    pub not_followed_by: Vec<Account>,
+    /// Accounts following the user that the user doesn't follow back.
+    pub not_following: Vec<Account>,
+}
+
+impl Mastodon {
+    /// Resolves and follows each `user@domain` handle, returning the followed
+    /// account or the error encountered for each handle, in order.
+    ///
+    /// ```no_run
+    /// # extern crate mammut;
+    /// # use mammut::{Data, Mastodon};
+    /// # use mammut::bulk::FollowOptions;
+    /// # fn main() {
+    /// # let mastodon = Mastodon::from_data(Data::from_env().unwrap());
+    /// let handles = ["Gargron@mastodon.social", "rust@mastodon.technology"];
+    ///
+    /// for (handle, result) in handles.iter().zip(mastodon.follow_many(&handles, FollowOptions::default())) {
+    ///     if let Err(e) = result {
+    ///         println!("Couldn't follow {}: {}", handle, e);
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn follow_many(&self, handles: &[&str], options: FollowOptions)
+        -> Vec<Result<Account>>
+    {
+        let mut results = Vec::with_capacity(handles.len());
+
+        for (i, handle) in handles.iter().enumerate() {
+            if i > 0 {
+                thread::sleep(options.delay);
+            }
+
+            if options.wait_for_rate_limit {
+                if let Some(wait) = self.rate_limit().and_then(|limit| limit.wait()) {
+                    thread::sleep(wait);
+                }
+            }
+
+            results.push(self.resolve_and_follow(handle));
+        }
+
+        results
+    }
+
+    /// Fetches the accounts with `ids`, `GET_ACCOUNTS_CONCURRENCY` at a time
+    /// as the API can't fetch several accounts at once. Returns the account
+    /// or the error encountered for each id, in order.
+    ///
+    /// Once the rate limit is about to be exhausted, fetching pauses until
+    /// it's reset.
+    ///
+    /// ```no_run
+    /// # extern crate mammut;
+    /// # use mammut::{Data, Mastodon};
+    /// # fn main() {
+    /// # let mastodon = Mastodon::from_data(Data::from_env().unwrap());
+    /// let ids = [1, 2, 3];
+    ///
+    /// for (id, result) in ids.iter().zip(mastodon.get_accounts(&ids)) {
+    ///     match result {
+    ///         Ok(account) => println!("{}: {}", id, account.acct),
+    ///         Err(e) => println!("Couldn't fetch {}: {}", id, e),
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn get_accounts(&self, ids: &[u64]) -> Vec<Result<Account>> {
+        let next = AtomicUsize::new(0);
+        let results = Mutex::new((0..ids.len()).map(|_| None).collect::<Vec<_>>());
+
+        let (next, results_ref) = (&next, &results);
+
+        thread::scope(|scope| {
+            for _ in 0..GET_ACCOUNTS_CONCURRENCY.min(ids.len()) {
+                // Clients can't be shared between threads, but clones share
+                // the rate limit.
+                let mastodon = self.clone();
+
+                scope.spawn(move || loop {
+                    let i = next.fetch_add(1, Ordering::SeqCst);
+                    let id = match ids.get(i) {
+                        Some(&id) => id,
+                        None => break,
+                    };
+
+                    // Leave a request for every worker, which may all be
+                    // sending one.
+                    if let Some(limit) = mastodon.rate_limit() {
+                        if limit.remaining <= GET_ACCOUNTS_CONCURRENCY as u64 {
+                            thread::sleep((limit.reset - Utc::now()).to_std().unwrap_or_default());
+                        }
+                    }
+
+                    let account = mastodon.get_account(id);
+                    results_ref.lock().unwrap()[i] = Some(account);
+                });
+            }
+        });
+
+        results.into_inner().unwrap()
+            .into_iter()
+            .map(|result| result.expect("every id is fetched"))
+            .collect()
+    }
+
+    /// Cross-references the authenticated user's followers and follows.
+    pub fn relationship_report(&self) -> Result<RelationshipReport> {
+        let me: u64 = self.current_user()?.id.parse()?;
+        let mut report = RelationshipReport::default();
+        let mut following = HashSet::new();
+
+        for chunk in self.following(me)?.all()?.chunks(40) {
+            let ids = chunk.iter()
+                .map(|account| account.id.parse())
+                .collect::<::std::result::Result<Vec<u64>, _>>()?;
+            let relationships = self.relationships(&ids)?;
+
+            for account in chunk {
+                let followed_by = relationships.iter()
+                    .any(|relationship| relationship.id == account.id && relationship.followed_by);
+
+                following.insert(account.id.clone());
+
+                if followed_by {
+                    report.mutuals.push(account.clone());
+                } else {
+                    report.not_followed_by.push(account.clone());
+                }
+            }
+        }
+
+        report.not_following = self.followers(me)?.all()?
+            .into_iter()
+            .filter(|account| !following.contains(&account.id))
+            .collect();
+
+        Ok(report)
+    }
+
+    /// Looks up the account of a `user@domain` handle, fetching it from its
+    /// instance if this one doesn't know it yet.
+    ///
+    /// # Errors
+    /// `Error::NotFound` if no account has the handle.
+    pub fn resolve_handle(&self, handle: &str) -> Result<Account> {
+        let handle = handle.trim_start_matches('@');
+
+        self.search_accounts_with(handle, Some(1))?
+            .into_iter()
+            .find(|account| is_handle(account, handle))
+            .ok_or_else(|| Error::NotFound(handle.to_owned()))
+    }
+
+    fn resolve_and_follow(&self, handle: &str) -> Result<Account> {
+        let account = self.resolve_handle(handle)?;
+        self.follow(account.id.parse()?)?;
+        Ok(account)
+    }
+}
+
+// Whether `handle` refers to `account`. Local accounts don't include the
+// domain in `acct`, so these are matched on the domain of their profile url.
+fn is_handle(account: &Account, handle: &str) -> bool {
+    if account.acct.eq_ignore_ascii_case(handle) {
+        return true;
+    }
+
+    let mut parts = handle.splitn(2, '@');
+    let (username, domain) = match (parts.next(), parts.next()) {
+        (Some(username), Some(domain)) => (username, domain),
+        _ => return false,
+    };
+
+    account.acct.eq_ignore_ascii_case(username) &&
+        account.url.to_lowercase().contains(&format!("//{}/", domain.to_lowercase()))
+}
@@ -0,0 +1,63 @@
+//! Fans events out to several consumers over channels, so that for example a
+//! GUI thread and a logging thread can both consume the same stream.
+//!
+//! ```
+//! # use std::thread;
+//! # use mammut::bus::Bus;
+//! let bus = Bus::new();
+//! let gui = bus.subscribe();
+//! let logger = bus.subscribe();
+//!
+//! let handle = thread::spawn(move || {
+//!     for event in logger {
+//!         println!("log: {}", event);
+//!     }
+//! });
+//!
+//! bus.publish("hello");
+//! assert_eq!(gui.recv().unwrap(), "hello");
+//!
+//! // Dropping the bus ends the subscribers' iterators.
+//! drop(bus);
+//! handle.join().unwrap();
+//! ```
+
+use std::sync::Mutex;
+use std::sync::mpsc::{self, Receiver, Sender};
+
+/// Publishes events to every subscribed receiver.
+#[derive(Debug, Default)]
+pub struct Bus<T: Clone> {
+    subscribers: Mutex<Vec<Sender<T>>>,
+}
+
+impl<T: Clone> Bus<T> {
+    /// Creates a bus without subscribers.
+    pub fn new() -> Self {
+        Bus {
+            subscribers: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Returns a receiver for every event published from now on. The
+    /// subscription ends when the receiver is dropped.
+    pub fn subscribe(&self) -> Receiver<T> {
+        let (sender, receiver) = mpsc::channel();
+        self.subscribers.lock().unwrap().push(sender);
+        receiver
+    }
+
+    /// Sends a clone of `event` to every subscriber, returning the number of
+    /// subscribers it was sent to.
+    pub fn publish(&self, event: T) -> usize {
+        let mut subscribers = self.subscribers.lock().unwrap();
+        subscribers.retain(|subscriber| subscriber.send(event.clone()).is_ok());
+        subscribers.len()
+    }
+
+    /// The number of subscribers, including ones which have been dropped
+    /// since the last event was published.
+    pub fn subscribers(&self) -> usize {
+        self.subscribers.lock().unwrap().len()
+    }
+}
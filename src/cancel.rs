@@ -0,0 +1,90 @@
+//! Cancelling requests, for example the timeline loads of a view the user
+//! navigated away from.
+//!
+//! ```
+//! # extern crate mammut;
+//! # use mammut::{Data, Error, Mastodon};
+//! # use mammut::cancel::CancellationToken;
+//! # fn main() {
+//! # let data = Data {
+//! #     base: "https://example.com".into(),
+//! #     client_id: "".into(),
+//! #     client_secret: "".into(),
+//! #     redirect: "".into(),
+//! #     token: "".into(),
+//! # };
+//! # let mastodon = Mastodon::from_data(data);
+//! let token = CancellationToken::new();
+//! let view = mastodon.with_cancellation(&token);
+//!
+//! {
+//!     let _guard = token.guard();
+//!     // Load the timeline with `view`, on this or another thread...
+//! }
+//!
+//! // Dropping the guard cancelled the token.
+//! match view.get_home_timeline() {
+//!     Err(Error::Cancelled) => {},
+//!     other => panic!("{:?}", other),
+//! }
+//! # }
+//! ```
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// A flag cancelling the requests of clients returned by
+/// `Mastodon::with_cancellation` once set. Clones share the flag.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Creates a token which isn't cancelled.
+    pub fn new() -> Self {
+        CancellationToken::default()
+    }
+
+    /// Cancels the requests of the clients using the token. Requests not
+    /// sent yet fail with `Error::Cancelled` without being sent, and the
+    /// responses of requests in flight are discarded with the same error
+    /// once they arrive, as blocking requests can't be interrupted.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// Whether the token was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// A guard cancelling the token when dropped, for tying the requests to
+    /// a scope such as a view.
+    pub fn guard(&self) -> CancelGuard {
+        CancelGuard {
+            token: Some(self.clone()),
+        }
+    }
+}
+
+/// Cancels its token when dropped, returned by `CancellationToken::guard`.
+#[derive(Debug)]
+pub struct CancelGuard {
+    token: Option<CancellationToken>,
+}
+
+impl CancelGuard {
+    /// Drops the guard without cancelling the token.
+    pub fn disarm(mut self) {
+        self.token.take();
+    }
+}
+
+impl Drop for CancelGuard {
+    fn drop(&mut self) {
+        if let Some(ref token) = self.token {
+            token.cancel();
+        }
+    }
+}
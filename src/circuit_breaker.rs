@@ -0,0 +1,71 @@
+use std::time::{Duration, Instant};
+
+use super::{Error, Result};
+
+/// Stops sending requests to an instance which keeps failing, enabled with
+/// `Mastodon::set_circuit_breaker`.
+///
+/// After `threshold` consecutive failures, connection errors or `5xx`
+/// responses, the breaker opens, and requests fail with `Error::CircuitOpen`
+/// without being sent until `cooldown` has passed. The next request is then
+/// sent again: if it succeeds the breaker closes, if it fails it opens for
+/// another `cooldown`.
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker {
+    threshold: u32,
+    cooldown: Duration,
+    failures: u32,
+    open_until: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    /// Creates a closed breaker, opening after `threshold` consecutive
+    /// failures for `cooldown`.
+    pub fn new(threshold: u32, cooldown: Duration) -> Self {
+        CircuitBreaker {
+            threshold: threshold.max(1),
+            cooldown,
+            failures: 0,
+            open_until: None,
+        }
+    }
+
+    /// The number of consecutive failures so far.
+    pub fn failures(&self) -> u32 {
+        self.failures
+    }
+
+    /// Whether requests are currently short-circuited.
+    pub fn is_open(&self) -> bool {
+        self.open_until.is_some_and(|until| Instant::now() < until)
+    }
+
+    /// Checks whether a request may be sent.
+    ///
+    /// # Errors
+    /// `Error::CircuitOpen`, with the time left until requests are sent
+    /// again, while the breaker is open.
+    pub fn check(&self) -> Result<()> {
+        match self.open_until {
+            Some(until) if Instant::now() < until => {
+                Err(Error::CircuitOpen(until.duration_since(Instant::now())))
+            },
+            _ => Ok(()),
+        }
+    }
+
+    /// Records the outcome of a request, opening the breaker once
+    /// `threshold` requests in a row failed.
+    pub fn record(&mut self, failed: bool) {
+        if !failed {
+            self.failures = 0;
+            self.open_until = None;
+            return;
+        }
+
+        self.failures = self.failures.saturating_add(1);
+        if self.failures >= self.threshold {
+            self.open_until = Some(Instant::now() + self.cooldown);
+        }
+    }
+}
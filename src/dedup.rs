@@ -0,0 +1,86 @@
+//! Deduplicates entities received from several sources, such as polling
+//! `notifications` while also consuming a stream.
+//!
+//! ```
+//! # use mammut::dedup::Seen;
+//! let mut seen = Seen::new(2);
+//!
+//! assert!(seen.insert("1".to_string()));
+//! assert!(!seen.insert("1".to_string()));
+//! assert!(seen.insert("2".to_string()));
+//! assert!(seen.insert("3".to_string()));
+//! // "1" was forgotten to stay within the capacity.
+//! assert!(seen.insert("1".to_string()));
+//! ```
+
+use std::collections::{HashSet, VecDeque};
+use std::hash::Hash;
+
+use entities::notification::Notification;
+
+/// A set of recently seen keys, bounded to `capacity` keys by forgetting the
+/// oldest ones.
+#[derive(Debug, Clone)]
+pub struct Seen<K: Hash + Eq + Clone> {
+    capacity: usize,
+    order: VecDeque<K>,
+    keys: HashSet<K>,
+}
+
+impl<K: Hash + Eq + Clone> Seen<K> {
+    /// Creates an empty set remembering at most `capacity` keys.
+    pub fn new(capacity: usize) -> Self {
+        Seen {
+            capacity: capacity.max(1),
+            order: VecDeque::with_capacity(capacity),
+            keys: HashSet::with_capacity(capacity),
+        }
+    }
+
+    /// Marks `key` as seen, returning whether it wasn't seen before.
+    pub fn insert(&mut self, key: K) -> bool {
+        if self.keys.contains(&key) {
+            return false;
+        }
+
+        if self.order.len() == self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.keys.remove(&oldest);
+            }
+        }
+
+        self.order.push_back(key.clone());
+        self.keys.insert(key);
+        true
+    }
+
+    /// Whether `key` has been seen.
+    pub fn contains(&self, key: &K) -> bool {
+        self.keys.contains(key)
+    }
+
+    /// The number of remembered keys.
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    /// Whether no keys are remembered.
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+}
+
+impl Seen<String> {
+    /// Whether the notification wasn't seen before, marking it as seen.
+    pub fn is_new_notification(&mut self, notification: &Notification) -> bool {
+        self.insert(notification.id.clone())
+    }
+
+    /// Removes the notifications which were seen before, marking the rest as
+    /// seen.
+    pub fn notifications(&mut self, notifications: Vec<Notification>) -> Vec<Notification> {
+        notifications.into_iter()
+            .filter(|notification| self.is_new_notification(notification))
+            .collect()
+    }
+}
@@ -0,0 +1,44 @@
+use chrono::prelude::*;
+use reqwest::header::Headers;
+
+/// An endpoint the instance reported as deprecated through the `Deprecation`
+/// or `Sunset` response headers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Deprecation {
+    /// The method and path of the deprecated endpoint, with ids replaced by
+    /// `:id`, eg. `GET /api/v1/accounts/:id`.
+    pub endpoint: String,
+    /// The value of the `Deprecation` header, `true` or the date the endpoint
+    /// was deprecated.
+    pub deprecation: Option<String>,
+    /// When the endpoint is scheduled to be removed, from the `Sunset`
+    /// header.
+    pub sunset: Option<DateTime<Utc>>,
+}
+
+impl Deprecation {
+    /// Reads the `Deprecation` and `Sunset` headers of a response from
+    /// `endpoint`, returns `None` if neither is present.
+    pub fn from_headers(endpoint: &str, headers: &Headers) -> Option<Self> {
+        let header = |name: &str| {
+            headers.get_raw(name)
+                .and_then(|raw| raw.one())
+                .and_then(|value| String::from_utf8(value.to_vec()).ok())
+        };
+
+        let deprecation = header("Deprecation");
+        let sunset = header("Sunset");
+
+        if deprecation.is_none() && sunset.is_none() {
+            return None;
+        }
+
+        Some(Deprecation {
+            endpoint: endpoint.to_owned(),
+            deprecation,
+            sunset: sunset
+                .and_then(|date| DateTime::parse_from_rfc2822(&date).ok())
+                .map(|date| date.with_timezone(&Utc)),
+        })
+    }
+}
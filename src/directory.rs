@@ -0,0 +1,91 @@
+//! Listing instances from the [joinmastodon.org](https://joinmastodon.org)
+//! server directory, for letting users pick an instance to register with.
+//!
+//! ```no_run
+//! # extern crate mammut;
+//! # use mammut::directory;
+//! # fn main() {
+//! #    try().unwrap();
+//! # }
+//! # fn try() -> mammut::Result<()> {
+//! let mut servers = directory::servers()?;
+//! servers.retain(|server| server.languages.iter().any(|language| language == "de"));
+//!
+//! for server in servers.iter().take(10) {
+//!     println!("{}: {} users last week", server.domain, server.last_week_users);
+//! }
+//!
+//! let registration = servers[0].registration();
+//! # Ok(())
+//! # }
+//! ```
+
+use reqwest::Client;
+
+use registration::Registration;
+use super::Result;
+
+/// The url of the joinmastodon.org server directory.
+pub const DIRECTORY_URL: &str = "https://api.joinmastodon.org/servers";
+
+/// An instance listed in a server directory.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Server {
+    /// The domain of the instance, eg. `mastodon.social`.
+    pub domain: String,
+    /// The version of Mastodon the instance is running.
+    #[serde(default)]
+    pub version: String,
+    /// A short description of the instance.
+    #[serde(default)]
+    pub description: String,
+    /// The main language of the instance, as an ISO 639-1 code.
+    #[serde(default)]
+    pub language: String,
+    /// The languages of the instance, as ISO 639-1 codes.
+    #[serde(default)]
+    pub languages: Vec<String>,
+    /// The region the instance is run in, eg. `europe`.
+    #[serde(default)]
+    pub region: String,
+    /// The main category of the instance, eg. `general` or `tech`.
+    #[serde(default)]
+    pub category: String,
+    /// The categories of the instance.
+    #[serde(default)]
+    pub categories: Vec<String>,
+    /// The url of the instance's thumbnail, proxied by the directory.
+    pub proxied_thumbnail: Option<String>,
+    /// The number of accounts on the instance.
+    #[serde(default)]
+    pub total_users: u64,
+    /// The number of accounts active in the last week.
+    #[serde(default)]
+    pub last_week_users: u64,
+    /// Whether new accounts have to be approved by a moderator.
+    #[serde(default)]
+    pub approval_required: bool,
+}
+
+impl Server {
+    /// The url of the instance, eg. `https://mastodon.social`.
+    pub fn base(&self) -> String {
+        format!("https://{}", self.domain)
+    }
+
+    /// Starts registering an app with the instance.
+    pub fn registration(&self) -> Registration {
+        Registration::new(self.base())
+    }
+}
+
+/// Fetches the instances listed in the joinmastodon.org server directory.
+pub fn servers() -> Result<Vec<Server>> {
+    servers_from(DIRECTORY_URL)
+}
+
+/// Fetches the instances listed in the server directory at `url`, which has
+/// to return a JSON array in the format of joinmastodon.org.
+pub fn servers_from(url: &str) -> Result<Vec<Server>> {
+    Ok(Client::new().get(url).send()?.error_for_status()?.json()?)
+}
@@ -0,0 +1,150 @@
+// Responses for the requests skipped in dry-run mode. The routes returning an
+// entity get a placeholder built from the route and the request body, such as
+// the relationship after following or the status posted, so code handling
+// the response runs as it would against the instance.
+
+use chrono::Utc;
+use json::{self, Value};
+use reqwest::Method;
+use serde::de::DeserializeOwned;
+
+use entities::account::Account;
+use super::{Error, Result};
+
+// Synthesise the response to the request to `path`, the segments below the
+// base url, with the JSON `body`. `me` is the authenticated account, if it's
+// known, to post placeholder statuses as. Routes returning `Empty` or a list
+// get an empty value, routes returning an entity without a placeholder fail
+// with `Error::DryRun`.
+pub fn synthesise<T: DeserializeOwned>(method: &Method,
+                                       path: &[&str],
+                                       body: Option<&Value>,
+                                       me: Option<&Account>)
+    -> Result<T>
+{
+    json::from_value(placeholder(method, path, body, me))
+        .or_else(|_| json::from_value(json!([])))
+        .map_err(|_| Error::DryRun)
+}
+
+fn placeholder(method: &Method,
+               path: &[&str],
+               body: Option<&Value>,
+               me: Option<&Account>)
+    -> Value
+{
+    let route = match *path {
+        ["api", _, ref route @ ..] => route,
+        _ => return json!({}),
+    };
+    let field = |name: &str| body.and_then(|body| body.get(name)).cloned().unwrap_or(Value::Null);
+
+    match (method, route) {
+        (&Method::Post, ["accounts", id, action]) => match *action {
+            "follow" | "unfollow" | "block" | "unblock" | "mute" | "unmute" => json!({
+                "id": id,
+                "following": *action == "follow",
+                "followed_by": false,
+                "blocking": *action == "block",
+                "muting": *action == "mute",
+                "requested": false,
+                "muting_notifications": *action == "mute",
+                "domain_blocking": false,
+                "showing_reblogs": true,
+                "notifying": false,
+            }),
+            _ => json!({}),
+        },
+        (&Method::Patch, ["accounts", "update_credentials"]) => account(me),
+        (&Method::Post, ["statuses"]) => {
+            let mut status = status("0", me);
+            status["content"] = json!(field("status").as_str().unwrap_or_default());
+            status["in_reply_to_id"] = json!(field("in_reply_to_id").as_u64().map(|id| id.to_string()));
+            status["sensitive"] = json!(field("sensitive").as_bool().unwrap_or(false));
+            status["spoiler_text"] = json!(field("spoiler_text").as_str().unwrap_or_default());
+            if !field("visibility").is_null() {
+                status["visibility"] = field("visibility");
+            }
+            status
+        },
+        (&Method::Post, ["statuses", id, action]) => {
+            let mut status = status(id, me);
+            match *action {
+                "reblog" | "unreblog" => status["reblogged"] = json!(*action == "reblog"),
+                "favourite" | "unfavourite" => status["favourited"] = json!(*action == "favourite"),
+                "pin" | "unpin" => status["pinned"] = json!(*action == "pin"),
+                _ => {},
+            }
+            status
+        },
+        (&Method::Post, ["lists"]) => json!({ "id": "0", "title": field("title") }),
+        (&Method::Put, ["lists", id]) => json!({ "id": id, "title": field("title") }),
+        (&Method::Post, ["media"]) => json!({
+            "id": "0",
+            "type": "unknown",
+            "url": "",
+            "remote_url": null,
+            "preview_url": "",
+            "text_url": null,
+            "meta": null,
+            "description": null,
+        }),
+        _ => json!({}),
+    }
+}
+
+// A status with the id `id` and without any content, by `me`.
+fn status(id: &str, me: Option<&Account>) -> Value {
+    json!({
+        "id": id,
+        "uri": "",
+        "url": null,
+        "account": account(me),
+        "in_reply_to_id": null,
+        "in_reply_to_account_id": null,
+        "reblog": null,
+        "content": "",
+        "created_at": Utc::now(),
+        "emojis": [],
+        "reblogs_count": 0,
+        "favourites_count": 0,
+        "reblogged": null,
+        "favourited": null,
+        "sensitive": false,
+        "spoiler_text": "",
+        "visibility": "public",
+        "media_attachments": [],
+        "mentions": [],
+        "tags": [],
+        "application": null,
+        "language": null,
+        "pinned": null,
+    })
+}
+
+// The authenticated account, or an empty one if it isn't known.
+fn account(me: Option<&Account>) -> Value {
+    if let Some(value) = me.and_then(|me| json::to_value(me).ok()) {
+        return value;
+    }
+
+    json!({
+        "acct": "",
+        "avatar": "",
+        "avatar_static": "",
+        "created_at": Utc::now(),
+        "display_name": "",
+        "followers_count": 0,
+        "following_count": 0,
+        "header": "",
+        "header_static": "",
+        "id": "0",
+        "locked": false,
+        "note": "",
+        "statuses_count": 0,
+        "url": "",
+        "username": "",
+        "source": null,
+        "moved": null,
+    })
+}
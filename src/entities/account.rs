@@ -6,7 +6,7 @@ use ::Result;
 use std::path::Path;
 
 /// A struct representing an Account.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Account {
     /// Equals `username` for local users, includes `@domain` for remote ones.
     pub acct: String,
@@ -39,30 +39,65 @@ pub struct Account {
     pub url: String,
     /// The username of the account.
     pub username: String,
+    /// Whether the account is marked as being run by a bot.
+    #[serde(default)]
+    pub bot: bool,
     /// An extra attribute given from `verify_credentials` giving defaults about
     /// a user
     pub source: Option<Source>,
     /// If the owner decided to switch accounts, new account is in
     /// this attribute
-    pub moved: Option<String>,
+    pub moved: Option<Box<Account>>,
 }
 
 /// An extra object given from `verify_credentials` giving defaults about a user
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Source {
-    privacy: ::status_builder::Visibility,
-    sensitive: bool,
-    note: String,
+    /// The default visibility of new statuses.
+    pub privacy: ::status_builder::Visibility,
+    /// Whether new statuses are marked sensitive by default.
+    pub sensitive: bool,
+    /// Plain text version of the account's biography.
+    pub note: String,
+    /// The default language of new statuses, as an ISO 639-1 code.
+    #[serde(default)]
+    pub language: Option<String>,
+    /// The profile metadata fields, with their values as entered rather than
+    /// rendered as HTML.
+    #[serde(default)]
+    pub fields: Vec<SourceField>,
+    /// The number of pending follow requests.
+    #[serde(default)]
+    pub follow_requests_count: u64,
+}
+
+/// A profile metadata field as entered by the user.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SourceField {
+    /// The label of the field.
+    pub name: String,
+    /// The plain text value of the field.
+    pub value: String,
+    /// When the link in the value was verified to link back to the profile.
+    #[serde(default)]
+    pub verified_at: Option<DateTime<Utc>>,
 }
 
+/// Data used to update the profile of the authenticated account.
+#[derive(Debug, Default)]
 pub struct CredientialsBuilder<'a> {
-    display_name: Option<&'a str>,
-    note: Option<&'a str>,
-    avatar: Option<&'a Path>,
-    header: Option<&'a Path>,
+    /// The new display name.
+    pub display_name: Option<&'a str>,
+    /// The new biography.
+    pub note: Option<&'a str>,
+    /// Path to the new avatar image.
+    pub avatar: Option<&'a Path>,
+    /// Path to the new header image.
+    pub header: Option<&'a Path>,
 }
 
 impl<'a> CredientialsBuilder<'a> {
+    /// Convert the changes into the multipart form sent to the instance.
     pub fn into_form(self) -> Result<Form> {
         let mut form = Form::new();
         macro_rules! add_to_form {
@@ -3,7 +3,7 @@ use serde::{Deserialize, Deserializer};
 use super::Empty;
 
 /// A struct representing a media attachment.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Attachment {
     /// ID of the attachment.
     pub id: String,
@@ -36,37 +36,98 @@ fn empty_as_none<'de, D: Deserializer<'de>>(val: D)
         Meta(Meta),
     }
 
-    Ok(match EmptyOrMeta::deserialize(val)? {
-        EmptyOrMeta::Empty(_) => None,
-        EmptyOrMeta::Meta(m) => Some(m),
+    Ok(match Option::<EmptyOrMeta>::deserialize(val)? {
+        None | Some(EmptyOrMeta::Empty(_)) => None,
+        Some(EmptyOrMeta::Meta(m)) => Some(m),
     })
 }
 
 /// Information about the attachment itself.
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Meta {
     /// Original version.
-    original: ImageDetails,
+    pub original: ImageDetails,
     /// Smaller version.
-    small: ImageDetails,
+    pub small: ImageDetails,
+    /// The point of the image to keep in view when it is cropped.
+    #[serde(default)]
+    pub focus: Option<Focus>,
+}
+
+/// The focal point of an image, from -1.0 to 1.0 on each axis with `(0, 0)`
+/// in its centre, and `(-1, 1)` in its top left corner.
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Copy, PartialEq)]
+pub struct Focus {
+    /// The horizontal position, from the left edge at -1.0 to the right edge
+    /// at 1.0.
+    pub x: f32,
+    /// The vertical position, from the bottom edge at -1.0 to the top edge at
+    /// 1.0.
+    pub y: f32,
+}
+
+impl Focus {
+    /// Computes the part of a `width` by `height` image to show in a
+    /// thumbnail with the aspect ratio `aspect` (width / height), keeping the
+    /// focal point in view the way the Mastodon web interface does.
+    ///
+    /// ```
+    /// # use mammut::entities::attachment::Focus;
+    /// let focus = Focus { x: 1.0, y: 0.0 };
+    /// let crop = focus.crop(400, 200, 1.0);
+    /// assert_eq!((crop.x, crop.y, crop.width, crop.height), (200.0, 0.0, 200.0, 200.0));
+    /// ```
+    pub fn crop(&self, width: u64, height: u64, aspect: f64) -> Crop {
+        let (width, height) = (width as f64, height as f64);
+        let (crop_width, crop_height) = if width / height > aspect {
+            (height * aspect, height)
+        } else {
+            (width, width / aspect)
+        };
+
+        // Matches the `object-position` the web interface derives from the
+        // focal point, which positions the same fraction of the image at the
+        // same fraction of the thumbnail.
+        let x = (f64::from(self.x.clamp(-1.0, 1.0)) + 1.0) / 2.0;
+        let y = (1.0 - f64::from(self.y.clamp(-1.0, 1.0))) / 2.0;
+
+        Crop {
+            x: (width - crop_width) * x,
+            y: (height - crop_height) * y,
+            width: crop_width,
+            height: crop_height,
+        }
+    }
+}
+
+/// A rectangle of an image, in pixels from its top left corner.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Crop {
+    /// Distance of the left edge from the left of the image.
+    pub x: f64,
+    /// Distance of the top edge from the top of the image.
+    pub y: f64,
+    /// Width of the rectangle.
+    pub width: f64,
+    /// Height of the rectangle.
+    pub height: f64,
 }
 
 /// Dimensions of an attachement.
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct ImageDetails {
     /// width of attachment.
-    width: u64,
+    pub width: u64,
     /// height of attachment.
-    height: u64,
+    pub height: u64,
     /// A string of `widthxheight`.
-    size: String,
+    pub size: String,
     /// The aspect ratio of the attachment.
-    aspect: f64,
-
+    pub aspect: f64,
 }
 
 /// The type of media attachment.
-#[derive(Debug, Deserialize, Clone, Copy)]
+#[derive(Debug, Deserialize, Serialize, Clone, Copy)]
 pub enum MediaType {
     /// An image.
     #[serde(rename = "image")]
@@ -0,0 +1,50 @@
+//! Module containing everything related to hashtags featured on a profile.
+
+use chrono::prelude::*;
+use serde::{Deserialize, Deserializer};
+
+/// A hashtag featured on the profile of the user.
+#[derive(Debug, Clone, Deserialize)]
+pub struct FeaturedTag {
+    /// The ID of the featured tag.
+    pub id: String,
+    /// The hashtag, not including the preceding `#`.
+    pub name: String,
+    /// URL of the user's statuses with the hashtag.
+    pub url: Option<String>,
+    /// The number of the user's statuses with the hashtag.
+    #[serde(deserialize_with = "number_or_string")]
+    pub statuses_count: u64,
+    /// The day the hashtag was last used by the user.
+    #[serde(default, deserialize_with = "date")]
+    pub last_status_at: Option<NaiveDate>,
+}
+
+// Older instances send the count as a string.
+fn number_or_string<'de, D: Deserializer<'de>>(val: D) -> Result<u64, D::Error> {
+    use serde::de::Error;
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum NumberOrString {
+        Number(u64),
+        String(String),
+    }
+
+    match NumberOrString::deserialize(val)? {
+        NumberOrString::Number(n) => Ok(n),
+        NumberOrString::String(s) => s.parse().map_err(D::Error::custom),
+    }
+}
+
+// Older instances send a full timestamp rather than a date.
+fn date<'de, D: Deserializer<'de>>(val: D) -> Result<Option<NaiveDate>, D::Error> {
+    use serde::de::Error;
+
+    match Option::<String>::deserialize(val)? {
+        Some(s) => NaiveDate::parse_from_str(s.get(..10).unwrap_or(&s), "%Y-%m-%d")
+            .map(Some)
+            .map_err(D::Error::custom),
+        None => Ok(None),
+    }
+}
@@ -0,0 +1,22 @@
+//! Module containing everything related to identity proofs.
+
+use chrono::prelude::*;
+
+/// A proof that an account belongs to the same person as an account on
+/// another service, such as Keybase. Identity proofs were removed in
+/// Mastodon 3.5.
+#[derive(Debug, Clone, Deserialize)]
+pub struct IdentityProof {
+    /// The name of the service, eg. `Keybase`.
+    pub provider: String,
+    /// The username on the service.
+    #[serde(rename = "provider_username")]
+    pub username: String,
+    /// When the proof was last verified.
+    #[serde(rename = "updated_at")]
+    pub verified_at: DateTime<Utc>,
+    /// The url of the proof on the service.
+    pub proof_url: String,
+    /// The url of the profile on the service.
+    pub profile_url: String,
+}
@@ -1,5 +1,7 @@
 //! Module containing everything related to an instance.
 
+use super::account::Account;
+
 /// A struct containing info of an instance.
 #[derive(Debug, Clone, Deserialize)]
 pub struct Instance {
@@ -16,4 +18,131 @@ pub struct Instance {
     pub version: String,
     /// `streaming_api`
     pub urls: Vec<String>,
+    /// The number of users, statuses and known instances.
+    #[serde(default)]
+    pub stats: InstanceStats,
+    /// The url of the banner image of the instance.
+    #[serde(default)]
+    pub thumbnail: Option<String>,
+    /// The languages the instance is primarily used in, as ISO 639-1 codes.
+    #[serde(default)]
+    pub languages: Vec<String>,
+    /// Whether new accounts can be registered.
+    #[serde(default)]
+    pub registrations: bool,
+    /// The account to contact about the instance, if the administrators set
+    /// one.
+    #[serde(default)]
+    pub contact_account: Option<Account>,
+    /// The limits of statuses and media attachments, `None` for instances
+    /// older than Mastodon 3.4.
+    #[serde(default)]
+    pub configuration: Option<InstanceConfiguration>,
+}
+
+/// Statistics about an instance.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct InstanceStats {
+    /// The number of users of the instance.
+    pub user_count: u64,
+    /// The number of statuses posted on the instance.
+    pub status_count: u64,
+    /// The number of other instances the instance knows.
+    pub domain_count: u64,
+}
+
+/// The limits the instance enforces on posting. The `Default` values are the
+/// limits of instances which don't report them.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct InstanceConfiguration {
+    /// Limits of statuses.
+    pub statuses: StatusesConfiguration,
+    /// Limits of media attachments.
+    pub media_attachments: MediaAttachmentsConfiguration,
+    /// Limits of polls.
+    pub polls: PollsConfiguration,
+}
+
+impl InstanceConfiguration {
+    /// Whether media of the MIME type `mime_type` can be uploaded.
+    pub fn supports_media_type(&self, mime_type: &str) -> bool {
+        self.media_attachments.supports_media_type(mime_type)
+    }
+}
+
+/// Limits of statuses.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct StatusesConfiguration {
+    /// The maximum number of characters of a status, including its content
+    /// warning.
+    pub max_characters: usize,
+    /// The maximum number of media attachments of a status.
+    pub max_media_attachments: usize,
+    /// The number of characters each link counts as, whatever its length.
+    pub characters_reserved_per_url: usize,
+}
+
+impl Default for StatusesConfiguration {
+    fn default() -> Self {
+        StatusesConfiguration {
+            max_characters: 500,
+            max_media_attachments: 4,
+            characters_reserved_per_url: 23,
+        }
+    }
+}
+
+/// Limits of media attachments.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct MediaAttachmentsConfiguration {
+    /// The MIME types which can be uploaded, empty if unknown.
+    pub supported_mime_types: Vec<String>,
+    /// The maximum size of images in bytes.
+    pub image_size_limit: Option<u64>,
+    /// The maximum number of pixels of images.
+    pub image_matrix_limit: Option<u64>,
+    /// The maximum size of videos in bytes.
+    pub video_size_limit: Option<u64>,
+    /// The maximum frame rate of videos.
+    pub video_frame_rate_limit: Option<u64>,
+    /// The maximum number of pixels of videos.
+    pub video_matrix_limit: Option<u64>,
+}
+
+impl MediaAttachmentsConfiguration {
+    /// Whether media of the MIME type `mime_type` can be uploaded. Any type is
+    /// assumed to be supported if the instance doesn't list them.
+    pub fn supports_media_type(&self, mime_type: &str) -> bool {
+        let supported = &self.supported_mime_types;
+        supported.is_empty() || supported.iter().any(|supported| supported.eq_ignore_ascii_case(mime_type))
+    }
+}
+
+/// Limits of polls.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct PollsConfiguration {
+    /// The maximum number of options of a poll.
+    pub max_options: usize,
+    /// The maximum number of characters of each option.
+    pub max_characters_per_option: usize,
+    /// The minimum time a poll can be open for, in seconds.
+    pub min_expiration: u64,
+    /// The maximum time a poll can be open for, in seconds.
+    pub max_expiration: u64,
+}
+
+impl Default for PollsConfiguration {
+    fn default() -> Self {
+        PollsConfiguration {
+            max_options: 4,
+            max_characters_per_option: 50,
+            min_expiration: 300,
+            max_expiration: 2_629_746,
+        }
+    }
 }
@@ -1,5 +1,10 @@
+//! Module containing everything related to lists of accounts.
+
+/// A list of accounts.
 #[derive(Clone, Debug, Deserialize)]
 pub struct List {
-    id: String,
-    title: String,
+    /// The ID of the list.
+    pub id: String,
+    /// The title of the list.
+    pub title: String,
 }
@@ -0,0 +1,37 @@
+//! Module containing everything related to timeline read markers.
+
+use chrono::prelude::*;
+use super::notification::Notification;
+
+/// The read position of the authenticated user in a timeline.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Marker {
+    /// The ID of the most recently viewed entity.
+    pub last_read_id: String,
+    /// Incremented every time the marker is saved.
+    pub version: u64,
+    /// The time the marker was last saved.
+    pub updated_at: DateTime<Utc>,
+}
+
+/// The saved markers of the home and notification timelines.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Markers {
+    /// Marker of the home timeline, if one has been saved.
+    pub home: Option<Marker>,
+    /// Marker of the notifications, if one has been saved.
+    pub notifications: Option<Marker>,
+}
+
+/// Notifications newer than the notifications marker.
+#[derive(Debug, Clone)]
+pub struct UnreadNotifications {
+    /// The unread notifications, newest first.
+    pub notifications: Vec<Notification>,
+    /// The number of unread notifications.
+    pub count: usize,
+    /// The marker the notifications were compared against. `None` if no
+    /// marker has been saved, in which case only the most recent page of
+    /// notifications is returned.
+    pub marker: Option<Marker>,
+}
@@ -2,14 +2,20 @@ pub mod account;
 pub mod attachment;
 pub mod card;
 pub mod context;
+pub mod featured_tag;
+pub mod identity_proof;
 pub mod instance;
 pub mod list;
+pub mod marker;
 pub mod mention;
 pub mod notification;
 pub mod relationship;
+pub mod relationship_severance_event;
 pub mod report;
 pub mod search_result;
 pub mod status;
+pub mod status_edit;
+pub mod suggestion;
 
 /// An empty JSON object.
 #[derive(Deserialize)]
@@ -19,16 +25,29 @@ pub mod prelude {
     //! The purpose of this module is to alleviate imports of many common structs
     //! by adding a glob import to the top of mastodon heavy modules:
     pub use super::Empty;
-    pub use super::account::{Account, CredientialsBuilder, Source};
+    pub use super::account::{Account, CredientialsBuilder, Source, SourceField};
     pub use super::attachment::{Attachment, MediaType};
     pub use super::card::Card;
     pub use super::context::Context;
-    pub use super::instance::Instance;
+    pub use super::featured_tag::FeaturedTag;
+    pub use super::identity_proof::IdentityProof;
+    pub use super::instance::{
+        Instance,
+        InstanceConfiguration,
+        InstanceStats,
+        MediaAttachmentsConfiguration,
+        PollsConfiguration,
+        StatusesConfiguration,
+    };
     pub use super::list::List;
+    pub use super::marker::{Marker, Markers, UnreadNotifications};
     pub use super::mention::Mention;
-    pub use super::notification::Notification;
+    pub use super::notification::{Notification, NotificationType};
     pub use super::relationship::Relationship;
+    pub use super::relationship_severance_event::{RelationshipSeveranceEvent, RelationshipSeveranceEventType};
     pub use super::report::Report;
-    pub use super::search_result::SearchResult;
-    pub use super::status::{Application, Emoji, Status};
+    pub use super::search_result::{Resolved, SearchResult};
+    pub use super::status::{Application, Emoji, Status, Tag};
+    pub use super::status_edit::StatusEdit;
+    pub use super::suggestion::{Suggestion, SuggestionSource};
 }
@@ -2,6 +2,8 @@
 
 use chrono::prelude::*;
 use super::account::Account;
+use super::relationship_severance_event::RelationshipSeveranceEvent;
+use super::report::Report;
 use super::status::Status;
 
 /// A struct containing info about a notification.
@@ -18,10 +20,23 @@ pub struct Notification {
     pub account: Account,
     /// The Status associated with the notification, if applicable.
     pub status: Option<Status>,
+    /// The event which severed relationships, for `SeveredRelationships`
+    /// notifications.
+    pub event: Option<RelationshipSeveranceEvent>,
+    /// The report filed, for `AdminReport` notifications.
+    pub report: Option<Report>,
+}
+
+impl Notification {
+    /// Whether the notification is of one of `types`, for filtering
+    /// notifications locally on instances which don't support filtering them.
+    pub fn is_any_of(&self, types: &[NotificationType]) -> bool {
+        types.contains(&self.notification_type)
+    }
 }
 
 /// The type of notification.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
 pub enum NotificationType {
     /// Someone mentioned the application client in another status.
     #[serde(rename = "mention")]
@@ -35,4 +50,36 @@ pub enum NotificationType {
     /// Someone followed the application client.
     #[serde(rename = "follow")]
     Follow,
+    /// Follows of the application client were removed by a moderation
+    /// action.
+    #[serde(rename = "severed_relationships")]
+    SeveredRelationships,
+    /// An account signed up, sent to moderators. The account is the new
+    /// account.
+    #[serde(rename = "admin.sign_up")]
+    AdminSignUp,
+    /// An account was reported, sent to moderators. The account is the one
+    /// filing the report.
+    #[serde(rename = "admin.report")]
+    AdminReport,
+    /// A type this version doesn't know, such as the ones added by newer
+    /// instances.
+    #[serde(other)]
+    Unknown,
+}
+
+impl NotificationType {
+    /// The name of the type used by the API.
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            NotificationType::Mention => "mention",
+            NotificationType::Reblog => "reblog",
+            NotificationType::Favourite => "favourite",
+            NotificationType::Follow => "follow",
+            NotificationType::SeveredRelationships => "severed_relationships",
+            NotificationType::AdminSignUp => "admin.sign_up",
+            NotificationType::AdminReport => "admin.report",
+            NotificationType::Unknown => "unknown",
+        }
+    }
 }
@@ -20,4 +20,16 @@ pub struct Relationship {
     pub muting_notifications: bool,
     /// Whether the user is currently blocking the accounts's domain
     pub domain_blocking: bool,
+    /// Whether the account's boosts are shown on the home timeline, `true`
+    /// for instances before Mastodon 2.1, which don't send it.
+    #[serde(default = "showing_reblogs")]
+    pub showing_reblogs: bool,
+    /// Whether the user is notified of the account's new statuses, only
+    /// sent since Mastodon 3.3.
+    #[serde(default)]
+    pub notifying: bool,
+}
+
+fn showing_reblogs() -> bool {
+    true
 }
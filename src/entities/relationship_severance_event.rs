@@ -0,0 +1,43 @@
+//! Module containing everything related to relationships severed by a
+//! moderation action.
+
+use chrono::prelude::*;
+
+/// A moderation action which removed follows of the user, such as an
+/// instance blocking a domain.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RelationshipSeveranceEvent {
+    /// The ID of the event.
+    pub id: String,
+    /// The kind of moderation action.
+    #[serde(rename = "type")]
+    pub event_type: RelationshipSeveranceEventType,
+    /// Whether the list of severed relationships is unavailable because the
+    /// underlying data has been purged.
+    pub purged: bool,
+    /// Name of the target of the moderation action, a domain name or account
+    /// handle.
+    pub target_name: String,
+    /// Number of followers that were removed.
+    #[serde(default)]
+    pub followers_count: u64,
+    /// Number of follows that were removed.
+    #[serde(default)]
+    pub following_count: u64,
+    /// The time the event happened.
+    pub created_at: DateTime<Utc>,
+}
+
+/// The kind of moderation action which severed relationships.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum RelationshipSeveranceEventType {
+    /// A moderator blocked a domain.
+    #[serde(rename = "domain_block")]
+    DomainBlock,
+    /// The user blocked a domain.
+    #[serde(rename = "user_domain_block")]
+    UserDomainBlock,
+    /// A moderator suspended an account.
+    #[serde(rename = "account_suspension")]
+    AccountSuspension,
+}
@@ -1,10 +1,46 @@
 //! module containing information about a finished report of a user.
 
+use chrono::prelude::*;
+use serde::{Deserialize, Deserializer};
+
+use super::account::Account;
+
 /// A struct containing info about a report.
 #[derive(Debug, Clone, Deserialize)]
 pub struct Report {
     /// The ID of the report.
     pub id: String,
-    /// The action taken in response to the report.
-    pub action_taken: String,
+    /// Whether an action was taken in response to the report.
+    #[serde(deserialize_with = "bool_or_string")]
+    pub action_taken: bool,
+    /// The reason given for the report.
+    #[serde(default)]
+    pub comment: String,
+    /// The category of the report, eg. `spam` or `violation`.
+    #[serde(default)]
+    pub category: Option<String>,
+    /// The time the report was filed.
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+    /// The IDs of the reported statuses.
+    #[serde(default)]
+    pub status_ids: Option<Vec<String>>,
+    /// The reported account, included in `admin.report` notifications.
+    #[serde(default)]
+    pub target_account: Option<Account>,
+}
+
+// Older instances send whether an action was taken as a string.
+fn bool_or_string<'de, D: Deserializer<'de>>(val: D) -> Result<bool, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum BoolOrString {
+        Bool(bool),
+        String(String),
+    }
+
+    Ok(match BoolOrString::deserialize(val)? {
+        BoolOrString::Bool(b) => b,
+        BoolOrString::String(s) => s == "true",
+    })
 }
@@ -1,14 +1,68 @@
 //! A module containing info relating to a search result.
 
-use super::prelude::{Account, Status};
+use serde::{Deserialize, Deserializer};
+
+use super::prelude::{Account, Status, Tag};
 
 /// A struct containing results of a search.
 #[derive(Debug, Clone, Deserialize)]
 pub struct SearchResult {
     /// An array of matched Accounts.
+    #[serde(default)]
     pub accounts: Vec<Account>,
     /// An array of matched Statuses.
+    #[serde(default)]
     pub statuses: Vec<Status>,
-    /// An array of matched hashtags, as strings.
-    pub hashtags: Vec<String>,
+    /// An array of matched hashtags. Instances only sending the names of
+    /// hashtags, as with the removed v1 API, leave their urls empty.
+    #[serde(default, deserialize_with = "tags")]
+    pub hashtags: Vec<Tag>,
+}
+
+impl SearchResult {
+    /// The status or account found by searching for its url, preferring one
+    /// whose url or uri is `url` over any other result.
+    pub fn resolved(self, url: &str) -> Option<Resolved> {
+        let SearchResult { mut accounts, mut statuses, .. } = self;
+
+        let status = statuses.iter()
+            .position(|status| status.uri == url || status.url.as_deref() == Some(url));
+        let account = accounts.iter().position(|account| account.url == url);
+
+        // The url of the entity may differ from the one searched for when it
+        // redirects, so fall back to the first result.
+        match (status, account) {
+            (Some(i), _) => Some(Resolved::Status(Box::new(statuses.swap_remove(i)))),
+            (None, Some(i)) => Some(Resolved::Account(Box::new(accounts.swap_remove(i)))),
+            (None, None) if !statuses.is_empty() => Some(Resolved::Status(Box::new(statuses.swap_remove(0)))),
+            (None, None) if !accounts.is_empty() => Some(Resolved::Account(Box::new(accounts.swap_remove(0)))),
+            (None, None) => None,
+        }
+    }
+}
+
+/// A status or account found by its url, see `Mastodon::resolve_url`.
+#[derive(Debug, Clone)]
+pub enum Resolved {
+    /// The url is a status.
+    Status(Box<Status>),
+    /// The url is a profile.
+    Account(Box<Account>),
+}
+
+fn tags<'de, D: Deserializer<'de>>(val: D) -> Result<Vec<Tag>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum TagOrName {
+        Tag(Tag),
+        Name(String),
+    }
+
+    Ok(Vec::<TagOrName>::deserialize(val)?
+        .into_iter()
+        .map(|tag| match tag {
+            TagOrName::Tag(tag) => tag,
+            TagOrName::Name(name) => Tag { name, url: String::new() },
+        })
+        .collect())
 }
@@ -1,11 +1,15 @@
 //! Module containing all info relating to a status.
 
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::iter;
+
 use chrono::prelude::*;
 use super::prelude::*;
 use status_builder::Visibility;
 
 /// A status from the instance.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Status {
     /// The ID of the status.
     pub id: String,
@@ -47,9 +51,11 @@ pub struct Status {
     pub visibility: Visibility,
     /// An array of attachments.
     pub media_attachments: Vec<Attachment>,
-    /// An array of mentions.
+    /// The accounts mentioned in the status.
+    #[serde(default)]
     pub mentions: Vec<Mention>,
-    /// An array of tags.
+    /// The hashtags used in the status.
+    #[serde(default)]
     pub tags: Vec<Tag>,
     /// Name of application used to post status.
     pub application: Option<Application>,
@@ -59,8 +65,98 @@ pub struct Status {
     pub pinned: Option<bool>,
 }
 
+impl Status {
+    /// The mention a link in the content points to, given the link's `href`,
+    /// for opening the mentioned account when the link is tapped.
+    pub fn mention_linked_by(&self, href: &str) -> Option<&Mention> {
+        self.mentions.iter().find(|mention| mention.url == href)
+    }
+
+    /// The hashtag a link in the content points to, given the link's `href`,
+    /// for opening the hashtag's timeline when the link is tapped. Hashtags
+    /// are matched ignoring case, as instances link to them as written.
+    pub fn tag_linked_by(&self, href: &str) -> Option<&Tag> {
+        self.tags.iter().find(|tag| {
+            tag.url.eq_ignore_ascii_case(href) ||
+                (href.contains("/tags/") &&
+                 href.rsplit('/').next().is_some_and(|name| name.eq_ignore_ascii_case(&tag.name)))
+        })
+    }
+
+    /// The accounts a reply to the status mentions, as the web UI does: the
+    /// author first, then the other mentioned accounts in order, each once
+    /// and leaving out the replying account with the id `own_id`. Replies to
+    /// a reblog reply to the reblogged status.
+    ///
+    /// ```no_run
+    /// # extern crate mammut;
+    /// # use mammut::{Data, Mastodon, StatusBuilder};
+    /// # fn main() {
+    /// #    try().unwrap();
+    /// # }
+    /// # fn try() -> mammut::Result<()> {
+    /// # let mastodon = Mastodon::from_data(Data::from_env()?);
+    /// # let status = mastodon.get_home_timeline()?.remove(0);
+    /// let prefix = status.reply_prefix(&mastodon.current_user()?.id);
+    /// let mut reply = StatusBuilder::new(prefix + "Sounds good!");
+    /// reply.in_reply_to_id = Some(status.id.parse()?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn reply_mentions(&self, own_id: &str) -> Vec<&str> {
+        let status = self.reblog.as_deref().unwrap_or(self);
+        let mut accts: Vec<&str> = Vec::new();
+
+        let accounts = iter::once((&*status.account.id, &*status.account.acct))
+            .chain(status.mentions.iter().map(|mention| (&*mention.id, &*mention.acct)));
+
+        for (id, acct) in accounts {
+            if id != own_id && !accts.contains(&acct) {
+                accts.push(acct);
+            }
+        }
+
+        accts
+    }
+
+    /// The text a reply to the status starts with, such as
+    /// `@Gargron @mammut@example.com `, see `reply_mentions`. Empty if the
+    /// replying account is the only one mentioned.
+    pub fn reply_prefix(&self, own_id: &str) -> String {
+        self.reply_mentions(own_id)
+            .iter()
+            .map(|acct| format!("@{} ", acct))
+            .collect()
+    }
+}
+
+/// Statuses are ordered by the time they were created, then by id. Ids of
+/// statuses from different instances aren't comparable, so ties between these
+/// are broken arbitrarily.
+impl Ord for Status {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.created_at.cmp(&other.created_at)
+            .then_with(|| self.id.len().cmp(&other.id.len()))
+            .then_with(|| self.id.cmp(&other.id))
+    }
+}
+
+impl PartialOrd for Status {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Status {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Status {}
+
 /// A mention of another user.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Mention {
     /// URL of user's profile (can be remote).
     pub url: String,
@@ -73,7 +169,7 @@ pub struct Mention {
 }
 
 /// Struct representing an emoji within text.
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Emoji {
     /// The shortcode of the emoji
     pub shortcode: String,
@@ -81,10 +177,39 @@ pub struct Emoji {
     pub static_url: String,
     /// URL to the emoji image
     pub url: String,
+    /// Whether the emoji should be offered in emoji pickers.
+    #[serde(default = "visible_in_picker")]
+    pub visible_in_picker: bool,
+    /// The category to list the emoji under in emoji pickers.
+    #[serde(default)]
+    pub category: Option<String>,
+}
+
+fn visible_in_picker() -> bool {
+    true
+}
+
+impl Emoji {
+    /// Groups emojis by their category, keeping the order of the emojis
+    /// within each category. Uncategorised emojis are grouped under `None`,
+    /// which sorts before the named categories.
+    pub fn group_by_category<I>(emojis: I) -> BTreeMap<Option<String>, Vec<Emoji>>
+        where I: IntoIterator<Item = Emoji>
+    {
+        let mut groups = BTreeMap::new();
+
+        for emoji in emojis {
+            groups.entry(emoji.category.clone())
+                .or_insert_with(Vec::new)
+                .push(emoji);
+        }
+
+        groups
+    }
 }
 
 /// Hashtags in the status.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Tag {
     /// The hashtag, not including the preceding `#`.
     pub name: String,
@@ -93,7 +218,7 @@ pub struct Tag {
 }
 
 /// Application details.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Application {
     /// Name of the application.
     pub name: String,
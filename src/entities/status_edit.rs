@@ -0,0 +1,43 @@
+//! Module containing everything related to the edit history of statuses.
+
+use chrono::prelude::*;
+use super::prelude::*;
+
+/// A revision of a status, returned by `Mastodon::status_history`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct StatusEdit {
+    /// The content of the revision, as HTML.
+    pub content: String,
+    /// The content warning of the revision.
+    pub spoiler_text: String,
+    /// Whether the media of the revision were marked as sensitive.
+    pub sensitive: bool,
+    /// The time the revision was posted, the first one being the original
+    /// status.
+    pub created_at: DateTime<Utc>,
+    /// The account which posted the status.
+    pub account: Account,
+    /// The media attachments of the revision.
+    #[serde(default)]
+    pub media_attachments: Vec<Attachment>,
+    /// The custom emojis used in the revision.
+    #[serde(default)]
+    pub emojis: Vec<Emoji>,
+    /// The poll of the revision, if it had one.
+    #[serde(default)]
+    pub poll: Option<StatusEditPoll>,
+}
+
+/// The options of a poll in a revision of a status.
+#[derive(Debug, Clone, Deserialize)]
+pub struct StatusEditPoll {
+    /// The options of the poll.
+    pub options: Vec<StatusEditPollOption>,
+}
+
+/// An option of a poll in a revision of a status.
+#[derive(Debug, Clone, Deserialize)]
+pub struct StatusEditPollOption {
+    /// The text of the option.
+    pub title: String,
+}
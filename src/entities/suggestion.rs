@@ -0,0 +1,41 @@
+//! Module containing everything related to follow suggestions.
+
+use super::account::Account;
+
+/// An account suggested to follow, along with why it's suggested.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Suggestion {
+    /// The main reason the account is suggested. Deprecated by Mastodon in
+    /// favour of `sources`.
+    pub source: SuggestionSource,
+    /// All reasons the account is suggested, since Mastodon 4.3.
+    #[serde(default)]
+    pub sources: Vec<SuggestionSource>,
+    /// The suggested account.
+    pub account: Account,
+}
+
+/// Why an account is suggested.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SuggestionSource {
+    /// The account was picked by the staff of the instance.
+    Staff,
+    /// The user has interacted with the account before.
+    PastInteractions,
+    /// The account is popular on the instance.
+    Global,
+    /// The account was featured by the staff.
+    Featured,
+    /// The account is one of the most followed on the instance.
+    MostFollowed,
+    /// The account is one of the most interacted with on the instance.
+    MostInteractions,
+    /// The account is similar to accounts the user recently followed.
+    SimilarToRecentlyFollowed,
+    /// The account is followed by accounts the user follows.
+    FriendsOfFriends,
+    /// A reason this version of mammut doesn't know.
+    #[serde(other)]
+    Unknown,
+}
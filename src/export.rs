@@ -0,0 +1,127 @@
+//! Writes CSV files in the format of the Mastodon web UI's exporter, so they
+//! can be imported on another instance.
+//!
+//! ```no_run
+//! # extern crate mammut;
+//! # use std::fs::File;
+//! # use mammut::{Data, Mastodon};
+//! # fn main() {
+//! #    try().unwrap();
+//! # }
+//! # fn try() -> mammut::Result<()> {
+//! # let mastodon = Mastodon::from_data(Data::from_env()?);
+//! mammut::export::following(&mastodon, File::create("following_accounts.csv")?)?;
+//! mammut::export::blocks(&mastodon, File::create("blocked_accounts.csv")?)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::io::Write;
+
+use url::Url;
+
+use entities::account::Account;
+use entities::relationship::Relationship;
+use super::{Mastodon, Result};
+
+/// Writes the accounts followed by the authenticated user, along with
+/// whether their boosts are shown and new statuses notified.
+pub fn following<W: Write>(mastodon: &Mastodon, writer: W) -> Result<()> {
+    let me = mastodon.current_user()?;
+    let accounts = mastodon.following(me.id.parse()?)?.all()?;
+
+    follows(mastodon, &accounts, writer)
+}
+
+/// Writes the accounts following the authenticated user. The web UI doesn't
+/// export followers, so this uses the format of the follows export.
+pub fn followers<W: Write>(mastodon: &Mastodon, writer: W) -> Result<()> {
+    let me = mastodon.current_user()?;
+    let accounts = mastodon.followers(me.id.parse()?)?.all()?;
+
+    follows(mastodon, &accounts, writer)
+}
+
+fn follows<W: Write>(mastodon: &Mastodon, accounts: &[Account], mut writer: W) -> Result<()> {
+    let domain = domain(mastodon)?;
+
+    writeln!(writer, "Account address,Show boosts,Notify on new posts,Languages")?;
+    with_relationships(mastodon, accounts, |account, relationship| {
+        let (show_boosts, notify) = relationship
+            .map_or((true, false), |relationship| (relationship.showing_reblogs, relationship.notifying));
+
+        writeln!(writer, "{},{},{},", address(account, &domain), show_boosts, notify)?;
+        Ok(())
+    })
+}
+
+/// Writes the accounts blocked by the authenticated user.
+pub fn blocks<W: Write>(mastodon: &Mastodon, mut writer: W) -> Result<()> {
+    let domain = domain(mastodon)?;
+
+    for account in mastodon.blocks()?.all()? {
+        writeln!(writer, "{}", address(&account, &domain))?;
+    }
+
+    Ok(())
+}
+
+/// Writes the accounts muted by the authenticated user, along with whether
+/// their notifications are muted as well.
+pub fn mutes<W: Write>(mastodon: &Mastodon, mut writer: W) -> Result<()> {
+    let domain = domain(mastodon)?;
+    let accounts = mastodon.mutes()?.all()?;
+
+    writeln!(writer, "Account address,Hide notifications")?;
+    with_relationships(mastodon, &accounts, |account, relationship| {
+        let hide_notifications = relationship
+            .map(|relationship| relationship.muting_notifications)
+            .unwrap_or(true);
+
+        writeln!(writer, "{},{}", address(account, &domain), hide_notifications)?;
+        Ok(())
+    })
+}
+
+/// Writes the URIs of the statuses bookmarked by the authenticated user.
+pub fn bookmarks<W: Write>(mastodon: &Mastodon, mut writer: W) -> Result<()> {
+    for status in mastodon.bookmarks()?.all()? {
+        writeln!(writer, "{}", status.uri)?;
+    }
+
+    Ok(())
+}
+
+// Calls `f` with each account and the relationship to it, fetched 40 at a
+// time, `None` if the instance didn't return one.
+fn with_relationships<F>(mastodon: &Mastodon, accounts: &[Account], mut f: F) -> Result<()>
+    where F: FnMut(&Account, Option<&Relationship>) -> Result<()>
+{
+    for chunk in accounts.chunks(40) {
+        let ids = chunk.iter()
+            .map(|account| account.id.parse())
+            .collect::<::std::result::Result<Vec<u64>, _>>()?;
+        let relationships = mastodon.relationships(&ids)?;
+
+        for account in chunk {
+            f(account, relationships.iter().find(|relationship| relationship.id == account.id))?;
+        }
+    }
+
+    Ok(())
+}
+
+pub(crate) fn domain(mastodon: &Mastodon) -> Result<String> {
+    let base = Url::parse(&mastodon.base)?;
+    Ok(base.host_str().unwrap_or_default().to_owned())
+}
+
+// The `user@domain` address of an account, local accounts don't include the
+// domain in `acct`.
+pub(crate) fn address(account: &Account, domain: &str) -> String {
+    if account.acct.contains('@') {
+        account.acct.clone()
+    } else {
+        format!("{}@{}", account.acct, domain)
+    }
+}
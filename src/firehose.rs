@@ -0,0 +1,232 @@
+//! Collecting every public status of an instance, for example for research.
+//!
+//! The firehose reads the public stream, and fetches the public timeline
+//! after connecting and every `backfill_interval`, so statuses missed while
+//! the stream was disconnected are collected too. Each status is passed to
+//! the sinks once.
+//!
+//! ```no_run
+//! # extern crate mammut;
+//! # use mammut::{Data, Mastodon};
+//! # use mammut::firehose::{Callback, Firehose};
+//! # use mammut::jsonl::{JsonLinesFile, Rotation};
+//! # fn main() {
+//! #    try().unwrap();
+//! # }
+//! # fn try() -> mammut::Result<()> {
+//! # let mastodon = Mastodon::from_data(Data::from_env()?);
+//! let mut firehose = Firehose::new(&mastodon);
+//! firehose.add_sink(JsonLinesFile::create("firehose", "public", Rotation::default())?);
+//! firehose.add_sink(Callback(|status: &mammut::entities::status::Status| {
+//!     println!("{}", status.uri);
+//!     Ok(())
+//! }));
+//!
+//! firehose.run()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::cmp::Ordering;
+use std::io::{self, Write};
+use std::sync::mpsc::Sender;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use dedup::Seen;
+use entities::status::Status;
+#[cfg(feature = "metrics")]
+use instrument;
+use jsonl::{JsonLines, JsonLinesFile};
+use markers::cmp_ids;
+use streaming::Event;
+use timeline::Cursor;
+use super::{Error, Mastodon, Result};
+
+/// Receives the statuses collected by a `Firehose`.
+pub trait Sink {
+    /// Handles a new status. An error stops the firehose.
+    fn send(&mut self, status: &Status) -> Result<()>;
+}
+
+/// Sends statuses to a channel. The firehose stops once the receiver is
+/// dropped.
+impl Sink for Sender<Status> {
+    fn send(&mut self, status: &Status) -> Result<()> {
+        Sender::send(self, status.clone())
+            .map_err(|_| Error::Io(io::Error::new(io::ErrorKind::BrokenPipe, "receiver dropped")))
+    }
+}
+
+/// Calls a closure with each status.
+#[derive(Debug, Clone)]
+pub struct Callback<F>(pub F);
+
+impl<F: FnMut(&Status) -> Result<()>> Sink for Callback<F> {
+    fn send(&mut self, status: &Status) -> Result<()> {
+        (self.0)(status)
+    }
+}
+
+/// Writes each status as a line, flushing after each.
+impl<W: Write> Sink for JsonLines<W> {
+    fn send(&mut self, status: &Status) -> Result<()> {
+        self.write(status)?;
+        self.flush()
+    }
+}
+
+/// Writes each status as a line of the current file, flushing after each.
+impl Sink for JsonLinesFile {
+    fn send(&mut self, status: &Status) -> Result<()> {
+        self.write(status)?;
+        self.flush()
+    }
+}
+
+/// Streams the public statuses of an instance to sinks, see the module
+/// documentation.
+pub struct Firehose<'a> {
+    mastodon: &'a Mastodon,
+    cursor: Cursor,
+    seen: Seen<String>,
+    sinks: Vec<Box<dyn Sink + 'a>>,
+    /// Whether to only collect the statuses of the instance's own users.
+    /// Disabled by default.
+    pub local: bool,
+    /// The time between fetching the public timeline while the stream is
+    /// connected, one minute by default. The timeline is fetched when an
+    /// event arrives after this interval, and after each reconnect.
+    pub backfill_interval: Duration,
+    /// The time to wait before reconnecting after the stream ended, or it or
+    /// a backfill failed, five seconds by default.
+    pub reconnect_delay: Duration,
+}
+
+impl<'a> Firehose<'a> {
+    /// Creates a firehose of the federated timeline without any sinks. The
+    /// last 10000 statuses are remembered to pass each to the sinks once.
+    pub fn new(mastodon: &'a Mastodon) -> Self {
+        Firehose {
+            mastodon,
+            cursor: Cursor::public(),
+            seen: Seen::new(10_000),
+            sinks: Vec::new(),
+            local: false,
+            backfill_interval: Duration::from_secs(60),
+            reconnect_delay: Duration::from_secs(5),
+        }
+    }
+
+    /// Adds a sink receiving every status collected from now on.
+    pub fn add_sink<S: Sink + 'a>(&mut self, sink: S) -> &mut Self {
+        self.sinks.push(Box::new(sink));
+        self
+    }
+
+    /// Passes `status` to the sinks unless a status with the same URI was
+    /// passed before, returning whether it was new.
+    pub fn push(&mut self, status: &Status) -> Result<bool> {
+        if !self.seen.insert(status.uri.clone()) {
+            return Ok(false);
+        }
+
+        let newer = self.cursor.position.as_ref()
+            .is_none_or(|position| cmp_ids(&status.id, position) == Ordering::Greater);
+        if newer {
+            self.cursor.position = Some(status.id.clone());
+        }
+
+        for sink in &mut self.sinks {
+            sink.send(status)?;
+        }
+
+        Ok(true)
+    }
+
+    /// Fetches the statuses of the public timeline newer than the newest one
+    /// collected, or the latest page at first, and passes the new ones to
+    /// the sinks oldest first. Returns the number of new statuses.
+    pub fn backfill(&mut self) -> Result<usize> {
+        match self.try_backfill() {
+            Ok(new) => Ok(new),
+            Err(Failure::Request(e)) | Err(Failure::Sink(e)) => Err(e),
+        }
+    }
+
+    /// Collects statuses until a sink fails, reconnecting the stream
+    /// `reconnect_delay` after it ends and backfilling after each reconnect.
+    /// Failed requests are logged and retried after `reconnect_delay`.
+    pub fn run(&mut self) -> Result<()> {
+        let stream = if self.local { "public/local" } else { "public" };
+        let mut reconnect = false;
+
+        loop {
+            if reconnect {
+                #[cfg(feature = "metrics")]
+                instrument::record_reconnect(stream);
+            }
+            reconnect = true;
+
+            match self.connect() {
+                Ok(()) => debug!("Stream {} ended, reconnecting", stream),
+                Err(Failure::Request(e)) => warn!("Stream {} failed, reconnecting: {}", stream, e),
+                Err(Failure::Sink(e)) => return Err(e),
+            }
+            thread::sleep(self.reconnect_delay);
+        }
+    }
+
+    // Backfills, then passes the streamed statuses to the sinks until the
+    // stream ends.
+    fn connect(&mut self) -> ::std::result::Result<(), Failure> {
+        self.try_backfill()?;
+        let mut backfilled_at = Instant::now();
+
+        let events = if self.local {
+            self.mastodon.stream_local()
+        } else {
+            self.mastodon.stream_public()
+        };
+
+        for event in events.map_err(Failure::Request)? {
+            if let Event::Update(status) = event.map_err(Failure::Request)? {
+                self.push(&status).map_err(Failure::Sink)?;
+            }
+
+            if backfilled_at.elapsed() >= self.backfill_interval {
+                self.try_backfill()?;
+                backfilled_at = Instant::now();
+            }
+        }
+
+        Ok(())
+    }
+
+    fn try_backfill(&mut self) -> ::std::result::Result<usize, Failure> {
+        let timeline = if self.local { Cursor::local() } else { Cursor::public() };
+        if self.cursor.timeline != timeline.timeline {
+            self.cursor = Cursor { position: self.cursor.position.take(), ..timeline };
+        }
+
+        let mut cursor = self.cursor.clone();
+        let statuses = self.mastodon.sync(&mut cursor).map_err(Failure::Request)?;
+        let mut new = 0;
+
+        for status in statuses.iter().rev() {
+            if self.push(status).map_err(Failure::Sink)? {
+                new += 1;
+            }
+        }
+
+        debug!("Backfilled {} statuses", new);
+        Ok(new)
+    }
+}
+
+// Why collecting stopped: requests are retried, failed sinks stop the
+// firehose.
+enum Failure {
+    Request(Error),
+    Sink(Error),
+}
@@ -0,0 +1,61 @@
+// Reports the requests of clients to the `metrics` facade, for exporting
+// them to Prometheus or another backend set up by the application, and as
+// `tracing` spans.
+
+use std::time::Duration;
+
+#[cfg(feature = "tracing")]
+use reqwest::Method;
+use reqwest::StatusCode;
+#[cfg(feature = "tracing")]
+use tracing::{field, Span};
+
+// Records a request to `endpoint`, `status` being `None` if no response was
+// received.
+#[cfg(feature = "metrics")]
+pub fn record_request(endpoint: &str, status: Option<StatusCode>, latency: Duration) {
+    let status = status
+        .map(|status| status.as_u16().to_string())
+        .unwrap_or_else(|| "error".to_owned());
+
+    counter!("mammut_requests_total", "endpoint" => endpoint.to_owned(), "status" => status)
+        .increment(1);
+    histogram!("mammut_request_duration_seconds", "endpoint" => endpoint.to_owned())
+        .record(latency.as_secs_f64());
+}
+
+// Records a reconnect to the streaming API.
+#[cfg(feature = "metrics")]
+pub fn record_reconnect(stream: &str) {
+    counter!("mammut_stream_reconnects_total", "stream" => stream.to_owned()).increment(1);
+}
+
+#[cfg(feature = "metrics")]
+pub fn record_rate_limit(remaining: u64) {
+    gauge!("mammut_rate_limit_remaining").set(remaining as f64);
+}
+
+// The span of a request to `endpoint`. Span names have to be static, so the
+// endpoint is its `otel.name`, which OpenTelemetry exporters name it after.
+#[cfg(feature = "tracing")]
+pub fn span(method: &Method, endpoint: &str) -> Span {
+    info_span!(
+        "mammut_request",
+        otel.name = endpoint,
+        endpoint,
+        method = %method,
+        status = field::Empty,
+        duration_ms = field::Empty,
+    )
+}
+
+// Records the outcome of the request of `span`, `status` being `None` if no
+// response was received.
+#[cfg(feature = "tracing")]
+pub fn record_span(span: &Span, status: Option<StatusCode>, latency: Duration) {
+    if let Some(status) = status {
+        span.record("status", status.as_u16());
+    }
+
+    span.record("duration_ms", latency.as_secs_f64() * 1000.0);
+}
@@ -0,0 +1,156 @@
+//! Sharing the accounts of statuses by the same author, so jobs holding many
+//! statuses in memory keep one copy of each account instead of one per
+//! status.
+//!
+//! ```no_run
+//! # extern crate mammut;
+//! # use mammut::{Data, Mastodon};
+//! # use mammut::intern::Interner;
+//! # fn main() {
+//! #    try().unwrap();
+//! # }
+//! # fn try() -> mammut::Result<()> {
+//! # let mastodon = Mastodon::from_data(Data::from_env()?);
+//! let mut interner = Interner::new();
+//! let mut statuses = interner.intern_statuses(mastodon.get_home_timeline()?);
+//! statuses.extend(interner.intern_statuses(mastodon.get_public_timeline(false)?));
+//!
+//! for status in &statuses {
+//!     println!("{}: {}", status.account().acct, status.status().content);
+//! }
+//! println!("{} statuses by {} accounts", statuses.len(), interner.len());
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::mem;
+use std::sync::Arc;
+
+use entities::account::Account;
+use entities::status::Status;
+
+/// The accounts seen so far, by id.
+///
+/// The first copy of an account seen is kept, so with timelines, which are
+/// newest first, the statuses of an author share the copy from their newest
+/// status.
+#[derive(Debug, Clone, Default)]
+pub struct Interner {
+    accounts: HashMap<String, Arc<Account>>,
+}
+
+/// A status whose account, and the account of the status it reblogs, are
+/// shared with the other statuses interned by the same `Interner`.
+#[derive(Debug, Clone)]
+pub struct InternedStatus {
+    status: Status,
+    account: Arc<Account>,
+    reblog_account: Option<Arc<Account>>,
+}
+
+impl Interner {
+    /// Creates an interner without any accounts.
+    pub fn new() -> Self {
+        Interner::default()
+    }
+
+    /// Returns the account of the same id seen before, or remembers `account`
+    /// if it's the first.
+    pub fn intern(&mut self, account: Account) -> Arc<Account> {
+        self.accounts.entry(account.id.clone())
+            .or_insert_with(|| Arc::new(account))
+            .clone()
+    }
+
+    /// Interns the account of `status` and of the status it reblogs.
+    pub fn intern_status(&mut self, mut status: Status) -> InternedStatus {
+        let account = self.intern(take_account(&mut status));
+        let reblog_account = status.reblog.as_mut()
+            .map(|reblog| self.intern(take_account(reblog)));
+
+        InternedStatus {
+            status,
+            account,
+            reblog_account,
+        }
+    }
+
+    /// Interns the accounts of `statuses`.
+    pub fn intern_statuses(&mut self, statuses: Vec<Status>) -> Vec<InternedStatus> {
+        statuses.into_iter().map(|status| self.intern_status(status)).collect()
+    }
+
+    /// The number of distinct accounts seen.
+    pub fn len(&self) -> usize {
+        self.accounts.len()
+    }
+
+    /// Whether no accounts were seen yet.
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+    }
+
+    /// Forgets the accounts seen, so statuses interned afterwards get fresh
+    /// copies.
+    pub fn clear(&mut self) {
+        self.accounts.clear();
+    }
+}
+
+impl InternedStatus {
+    /// The account which posted the status.
+    pub fn account(&self) -> &Arc<Account> {
+        &self.account
+    }
+
+    /// The account which posted the status reblogged, if it's a reblog.
+    pub fn reblog_account(&self) -> Option<&Arc<Account>> {
+        self.reblog_account.as_ref()
+    }
+
+    /// The status itself. Its `account`, and that of its `reblog`, only keep
+    /// their `id`, see `account` and `reblog_account` for the rest.
+    pub fn status(&self) -> &Status {
+        &self.status
+    }
+
+    /// The status with copies of its accounts put back.
+    pub fn into_status(self) -> Status {
+        let mut status = self.status;
+        status.account = (*self.account).clone();
+
+        if let (Some(reblog), Some(account)) = (status.reblog.as_mut(), self.reblog_account) {
+            reblog.account = (*account).clone();
+        }
+
+        status
+    }
+}
+
+// Moves the account out of `status`, leaving one with only its id, which
+// doesn't allocate anything else.
+fn take_account(status: &mut Status) -> Account {
+    let placeholder = Account {
+        acct: String::new(),
+        avatar: String::new(),
+        avatar_static: String::new(),
+        created_at: status.account.created_at,
+        display_name: String::new(),
+        followers_count: 0,
+        following_count: 0,
+        header: String::new(),
+        header_static: String::new(),
+        id: status.account.id.clone(),
+        locked: false,
+        note: String::new(),
+        statuses_count: 0,
+        url: String::new(),
+        username: String::new(),
+        bot: false,
+        source: None,
+        moved: None,
+    };
+
+    mem::replace(&mut status.account, placeholder)
+}
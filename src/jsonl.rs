@@ -0,0 +1,195 @@
+//! Writing entities as JSON Lines, one JSON object per line, which tools such
+//! as `jq`, pandas or BigQuery read directly.
+//!
+//! ```no_run
+//! # extern crate mammut;
+//! # use mammut::{Data, Mastodon};
+//! # use mammut::jsonl::{JsonLinesFile, Rotation};
+//! # use std::time::Duration;
+//! # fn main() {
+//! #    try().unwrap();
+//! # }
+//! # fn try() -> mammut::Result<()> {
+//! # let mastodon = Mastodon::from_data(Data::from_env()?);
+//! let rotation = Rotation {
+//!     max_size: Some(100 * 1024 * 1024),
+//!     max_age: Some(Duration::from_secs(60 * 60)),
+//! };
+//! let mut file = JsonLinesFile::create("collected", "home", rotation)?;
+//!
+//! for status in mastodon.get_home_timeline()? {
+//!     file.write(&status)?;
+//! }
+//! file.flush()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use json;
+use serde::Serialize;
+
+use super::Result;
+
+/// Writes entities to a writer, one per line.
+#[derive(Debug)]
+pub struct JsonLines<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> JsonLines<W> {
+    /// Writes the entities to `writer`. Wrap files in a `BufWriter`.
+    pub fn new(writer: W) -> Self {
+        JsonLines { writer }
+    }
+
+    /// Writes `entity` as a line.
+    pub fn write<T: Serialize>(&mut self, entity: &T) -> Result<()> {
+        json::to_writer(&mut self.writer, entity)?;
+        self.writer.write_all(b"\n")?;
+        Ok(())
+    }
+
+    /// Flushes the writer.
+    pub fn flush(&mut self) -> Result<()> {
+        Ok(self.writer.flush()?)
+    }
+
+    /// Returns the writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// When a `JsonLinesFile` starts a new file. Files are only rotated between
+/// lines, so a file can grow past `max_size` by one line.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Rotation {
+    /// The size in bytes after which to start a new file, `None` for no
+    /// limit.
+    pub max_size: Option<u64>,
+    /// The time after which to start a new file, `None` for no limit.
+    pub max_age: Option<Duration>,
+}
+
+/// Writes entities to JSON Lines files in a directory, starting a new file
+/// according to its `Rotation`. Files are named
+/// `<prefix>-<UTC time the file was started>-<n>.jsonl`, so they sort by the
+/// time they were started, and are only created once an entity is written.
+#[derive(Debug)]
+pub struct JsonLinesFile {
+    dir: PathBuf,
+    prefix: String,
+    rotation: Rotation,
+    current: Option<Current>,
+    files: usize,
+}
+
+#[derive(Debug)]
+struct Current {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    size: u64,
+    started: Instant,
+}
+
+impl JsonLinesFile {
+    /// Writes files starting with `prefix` to the directory `dir`, creating
+    /// it if it doesn't exist.
+    pub fn create<P: AsRef<Path>>(dir: P, prefix: &str, rotation: Rotation) -> Result<Self> {
+        let dir = dir.as_ref().to_owned();
+        fs::create_dir_all(&dir)?;
+
+        Ok(JsonLinesFile {
+            dir,
+            prefix: prefix.to_owned(),
+            rotation,
+            current: None,
+            files: 0,
+        })
+    }
+
+    /// Writes `entity` as a line of the current file, starting a new one
+    /// first if the current file is due to be rotated.
+    pub fn write<T: Serialize>(&mut self, entity: &T) -> Result<()> {
+        let line = json::to_vec(entity)?;
+
+        if self.is_due() {
+            self.rotate()?;
+        }
+
+        let current = match self.current {
+            Some(ref mut current) => current,
+            None => {
+                self.current = Some(self.start()?);
+                self.current.as_mut().unwrap()
+            },
+        };
+
+        current.writer.write_all(&line)?;
+        current.writer.write_all(b"\n")?;
+        current.size += line.len() as u64 + 1;
+
+        Ok(())
+    }
+
+    /// Flushes the current file.
+    pub fn flush(&mut self) -> Result<()> {
+        match self.current {
+            Some(ref mut current) => Ok(current.writer.flush()?),
+            None => Ok(()),
+        }
+    }
+
+    /// Flushes and closes the current file, so the next entity is written to
+    /// a new one.
+    pub fn rotate(&mut self) -> Result<()> {
+        self.flush()?;
+        self.current = None;
+        Ok(())
+    }
+
+    /// The path of the file being written, `None` before the first entity
+    /// and after rotating.
+    pub fn path(&self) -> Option<&Path> {
+        self.current.as_ref().map(|current| &*current.path)
+    }
+
+    fn is_due(&self) -> bool {
+        let current = match self.current {
+            Some(ref current) => current,
+            None => return false,
+        };
+
+        self.rotation.max_size.is_some_and(|max_size| current.size >= max_size) ||
+            self.rotation.max_age.is_some_and(|max_age| current.started.elapsed() >= max_age)
+    }
+
+    fn start(&mut self) -> Result<Current> {
+        let name = format!("{}-{}-{}.jsonl", self.prefix, Utc::now().format("%Y%m%dT%H%M%SZ"), self.files);
+        let path = self.dir.join(name);
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+        self.files += 1;
+
+        Ok(Current {
+            path,
+            writer: BufWriter::new(file),
+            size,
+            started: Instant::now(),
+        })
+    }
+}
+
+impl Drop for JsonLinesFile {
+    fn drop(&mut self) {
+        if let Err(e) = self.flush() {
+            warn!("Failed to flush {:?}: {}", self.path(), e);
+        }
+    }
+}
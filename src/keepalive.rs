@@ -0,0 +1,86 @@
+//! Checking the access token of a long-running client periodically, so a
+//! revoked token is noticed promptly rather than at the next user action.
+//!
+//! ```no_run
+//! # extern crate mammut;
+//! # use mammut::{Data, Mastodon};
+//! # use std::time::Duration;
+//! # fn main() {
+//! #    try().unwrap();
+//! # }
+//! # fn try() -> mammut::Result<()> {
+//! let mastodon = Mastodon::from_data(Data::from_env()?);
+//! let keepalive = mastodon.keepalive(Duration::from_secs(300), |e| {
+//!     println!("The access token stopped working: {}", e);
+//! });
+//!
+//! // Pings until `keepalive` is dropped or stopped.
+//! keepalive.stop();
+//! # Ok(())
+//! # }
+//! ```
+
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use super::{Error, Mastodon};
+
+/// A background thread calling `verify_credentials` on an interval, returned
+/// by `Mastodon::keepalive`. The thread is stopped when this is dropped.
+#[derive(Debug)]
+pub struct Keepalive {
+    stop: Option<Sender<()>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Keepalive {
+    /// Stops the thread, waiting for a ping in progress to finish.
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        // Dropping the sender wakes the thread up.
+        self.stop.take();
+
+        if let Some(handle) = self.handle.take() {
+            if handle.join().is_err() {
+                warn!("The keepalive failure callback panicked");
+            }
+        }
+    }
+}
+
+impl Drop for Keepalive {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+impl Mastodon {
+    /// Calls `verify_credentials` every `interval` on a background thread,
+    /// calling `on_failure` with the error whenever it fails, for example
+    /// with an `Error::Api` of kind `ApiErrorKind::TokenRevoked`. The account
+    /// cached by `current_user` is refreshed by every successful ping.
+    pub fn keepalive<F>(&self, interval: Duration, mut on_failure: F) -> Keepalive
+        where F: FnMut(Error) + Send + 'static
+    {
+        let mastodon = self.clone();
+        let (stop, stopped) = mpsc::channel::<()>();
+
+        let handle = thread::spawn(move || {
+            while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+                if let Err(e) = mastodon.refresh_current_user() {
+                    debug!("Keepalive ping failed: {}", e);
+                    on_failure(e);
+                }
+            }
+        });
+
+        Keepalive {
+            stop: Some(stop),
+            handle: Some(handle),
+        }
+    }
+}
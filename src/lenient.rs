@@ -0,0 +1,137 @@
+// Lenient deserialisation, used when the client is in lenient mode. Values
+// which fail to deserialise are replaced with a default for their type, or
+// dropped from the list containing them, until the entity deserialises or no
+// more repairs can be made.
+
+use std::collections::HashMap;
+
+use json::{self, Value};
+use serde::de::DeserializeOwned;
+use serde_path_to_error::{self as path_to_error, Segment};
+
+// Gives up on values which are somehow still failing after this many repairs.
+const MAX_REPAIRS: usize = 64;
+
+pub fn from_slice<T: DeserializeOwned>(bytes: &[u8]) -> json::Result<T> {
+    let error = match json::from_slice(bytes) {
+        Ok(t) => return Ok(t),
+        Err(e) => e,
+    };
+
+    let mut value: Value = match json::from_slice(bytes) {
+        Ok(value) => value,
+        // Not JSON at all, so nothing can be repaired.
+        Err(_) => return Err(error),
+    };
+    let mut attempts = HashMap::new();
+
+    for _ in 0..MAX_REPAIRS {
+        let e = match path_to_error::deserialize(value.clone()) {
+            Ok(t) => return Ok(t),
+            Err(e) => e,
+        };
+
+        let path: Vec<Segment> = e.path().iter().cloned().collect();
+        let message = e.inner().to_string();
+        let attempt = attempts.entry(e.path().to_string()).or_insert(0);
+        warn!("lenient deserialisation: repairing `{}`: {}", e.path(), message);
+
+        if !repair(&mut value, &path, &message, *attempt) {
+            return Err(e.into_inner());
+        }
+
+        *attempt += 1;
+    }
+
+    Err(error)
+}
+
+// Repairs the value at `path`. Successive attempts at the same path first
+// try a default for the expected type, then null for optional fields, then
+// drop the nearest entity in a list containing the value.
+fn repair(value: &mut Value, path: &[Segment], message: &str, attempt: usize) -> bool {
+    if let Some(field) = missing_field(message) {
+        if let Some(&mut Value::Object(ref mut map)) = get_mut(value, path) {
+            map.insert(field.to_owned(), Value::Null);
+            return true;
+        }
+    }
+
+    match attempt {
+        0 => match (get_mut(value, path), default_for(message)) {
+            (Some(target), Some(default)) => {
+                *target = default;
+                true
+            }
+            (Some(target), None) => {
+                *target = Value::Null;
+                true
+            }
+            (None, _) => false,
+        },
+        1 => match get_mut(value, path) {
+            Some(target) if !target.is_null() => {
+                *target = Value::Null;
+                true
+            }
+            _ => remove_from_list(value, path),
+        },
+        _ => remove_from_list(value, path),
+    }
+}
+
+fn missing_field(message: &str) -> Option<&str> {
+    let rest = &message[message.find("missing field `")? + "missing field `".len()..];
+    Some(&rest[..rest.find('`')?])
+}
+
+fn default_for(message: &str) -> Option<Value> {
+    let expected = &message[message.find("expected ")? + "expected ".len()..];
+
+    Some(if expected.starts_with("a string") {
+        Value::String(String::new())
+    } else if expected.starts_with("a boolean") {
+        Value::Bool(false)
+    } else if expected.starts_with('u') || expected.starts_with('i') || expected.starts_with('f') {
+        Value::from(0)
+    } else if expected.starts_with("a sequence") {
+        Value::Array(Vec::new())
+    } else if expected.starts_with("struct") || expected.starts_with("a map") {
+        Value::Object(json::Map::new())
+    } else {
+        return None;
+    })
+}
+
+// Removes the entity containing `path` from the closest list around it.
+fn remove_from_list(value: &mut Value, path: &[Segment]) -> bool {
+    let index = match path.iter().rposition(|segment| matches!(*segment, Segment::Seq { .. })) {
+        Some(index) => index,
+        None => return false,
+    };
+
+    let position = match path[index] {
+        Segment::Seq { index } => index,
+        _ => unreachable!(),
+    };
+
+    match get_mut(value, &path[..index]) {
+        Some(&mut Value::Array(ref mut list)) if position < list.len() => {
+            list.remove(position);
+            true
+        }
+        _ => false,
+    }
+}
+
+fn get_mut<'a>(mut value: &'a mut Value, path: &[Segment]) -> Option<&'a mut Value> {
+    for segment in path {
+        value = match *segment {
+            Segment::Seq { index } => value.get_mut(index)?,
+            Segment::Map { ref key } => value.get_mut(key.as_str())?,
+            Segment::Enum { .. } | Segment::Unknown => return Some(value),
+        };
+    }
+
+    Some(value)
+}
@@ -35,11 +35,25 @@
 #![cfg_attr(test, deny(warnings))]
 #![cfg_attr(test, deny(missing_docs))]
 
+#[macro_use] extern crate log;
 #[macro_use] extern crate serde_derive;
 #[macro_use] extern crate serde_json as json;
 extern crate chrono;
+#[cfg(feature = "image")]
+extern crate image;
+#[cfg(feature = "metrics")]
+#[macro_use] extern crate metrics;
+#[cfg(feature = "tracing")]
+#[macro_use(info_span)] extern crate tracing;
+#[cfg(feature = "async")]
+extern crate futures;
+#[cfg(feature = "async")]
+extern crate tokio_core;
+extern crate rand;
+extern crate regex;
 extern crate reqwest;
 extern crate serde;
+extern crate serde_path_to_error;
 extern crate url;
 
 /// Registering your App
@@ -52,25 +66,112 @@ pub mod entities;
 pub mod registration;
 /// Handling multiple pages of entities.
 pub mod page;
+/// Tracking the rate limit of the instance.
+pub mod rate_limit;
+/// Detecting deprecated endpoints.
+pub mod deprecation;
+/// The status and headers of responses.
+pub mod meta;
+/// Operating on many accounts at once.
+pub mod bulk;
+/// Keeping the members of lists in sync.
+pub mod lists;
+/// Exporting follows, blocks, mutes, and bookmarks as CSV.
+pub mod export;
+/// Archiving statuses, favourites and bookmarks with their media.
+pub mod archive;
+/// Moving follows, lists and bookmarks to a new account.
+pub mod migration;
+/// Combining the timelines of several accounts.
+pub mod aggregator;
+/// Posting the same status from several accounts.
+pub mod broadcast;
+/// Merging, deduplicating and syncing timelines.
+pub mod timeline;
+/// Following accounts which moved to another instance.
+pub mod moved;
+/// Running queued requests within the rate limit.
+pub mod scheduler;
+/// Keeping statuses on disk until they can be posted, or are due.
+pub mod outbox;
+/// Deduplicating entities received more than once.
+pub mod dedup;
+/// Fanning events out to several consumers.
+pub mod bus;
+/// Building requests with many optional parameters.
+pub mod requests;
+/// Downloading media and profile images.
+pub mod media;
+/// Writing bots which answer commands.
+pub mod bot;
+/// Watching hashtags for new statuses.
+pub mod watcher;
+/// Alerting on statuses matching keywords.
+pub mod alert;
+/// Collecting stats about the calls of a client.
+pub mod stats;
+/// Short-circuiting requests to instances which keep failing.
+pub mod circuit_breaker;
+/// Picking an instance from a server directory.
+pub mod directory;
+/// Keeping the notifications read marker in sync.
+pub mod markers;
+/// Checking the access token periodically.
+pub mod keepalive;
+/// Cancelling requests which are no longer needed.
+pub mod cancel;
+/// Receiving events from the streaming API.
+pub mod streaming;
+/// Sharing repeated accounts between statuses.
+pub mod intern;
+/// Collecting the public statuses of an instance.
+pub mod firehose;
+/// Writing entities as JSON Lines.
+pub mod jsonl;
+
+mod atomic_file;
+mod body;
+mod dry_run;
+mod html;
+#[cfg(any(feature = "metrics", feature = "tracing"))]
+mod instrument;
+mod lenient;
 
 use std::borrow::Cow;
+use std::collections::HashMap;
+use std::env;
 use std::error::Error as StdError;
 use std::fmt;
 use std::io::Error as IoError;
+use std::mem;
+use std::num::ParseIntError;
 use std::ops;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use json::Error as SerdeError;
+use regex::Error as RegexError;
 use reqwest::Error as HttpError;
-use reqwest::{Client, Response, StatusCode};
-use reqwest::header::{Authorization, Bearer, Headers};
-use url::Url;
+use reqwest::{Client, ClientBuilder, Method, RedirectPolicy, Request, RequestBuilder, Response, StatusCode};
+use reqwest::header::{Authorization, Bearer, Headers, Location};
 use url::ParseError as UrlError;
+use url::form_urlencoded;
+use url::Url;
 
 use entities::prelude::*;
 pub use status_builder::StatusBuilder;
 use page::Page;
-
+use deprecation::Deprecation;
+use rate_limit::RateLimit;
+use meta::ResponseMeta;
+use stats::Stats;
+use circuit_breaker::CircuitBreaker;
+use cancel::CancellationToken;
+use apps::Scopes;
+
+pub use apps::REDIRECT_OOB;
 pub use registration::Registration;
+pub use requests::{PageRequest, RequestOptions, SearchRequest, StatusesRequest};
 /// Convience type over `std::result::Result` with `Error` as the error type.
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -80,16 +181,56 @@ macro_rules! methods {
             fn $method<T: for<'de> serde::Deserialize<'de>>(&self, url: String)
             -> Result<T>
             {
-                let response = self.client.$method(&url)
-                    .headers(self.headers.clone())
-                    .send()?;
-
-                deserialise(response)
+                self.send(&mut self.client.$method(&url))
             }
          )+
     };
 }
 
+macro_rules! paged_routes_request {
+
+    (($method:ident) $name:ident: $url:expr => $ret:ty, $($rest:tt)*) => {
+        /// Equivalent to `/api/v1/
+        #[doc = $url]
+        /// `, starting at the page of `request`.
+        ///
+        #[doc = "# Errors"]
+        /// If `access_token` is not set.
+        pub fn $name(&self, request: PageRequest) -> Result<Page<'_, $ret>> {
+            let url = self.route(&format!(concat!("/api/v1/", $url, "{}"), request.to_query_string()));
+            let response = self.execute(&mut self.client.$method(&url))?;
+
+            Page::new(self, response)
+        }
+
+        paged_routes_request!{$($rest)*}
+    };
+
+    () => {}
+}
+
+macro_rules! paged_routes_request_id {
+
+    (($method:ident) $name:ident: $url:expr => $ret:ty, $($rest:tt)*) => {
+        /// Equivalent to `/api/v1/
+        #[doc = $url]
+        /// `, starting at the page of `request`.
+        ///
+        #[doc = "# Errors"]
+        /// If `access_token` is not set.
+        pub fn $name(&self, id: u64, request: PageRequest) -> Result<Page<'_, $ret>> {
+            let url = self.route(&format!(concat!("/api/v1/", $url, "{}"), id, request.to_query_string()));
+            let response = self.execute(&mut self.client.$method(&url))?;
+
+            Page::new(self, response)
+        }
+
+        paged_routes_request_id!{$($rest)*}
+    };
+
+    () => {}
+}
+
 macro_rules! paged_routes {
 
     (($method:ident) $name:ident: $url:expr => $ret:ty, $($rest:tt)*) => {
@@ -99,17 +240,38 @@ macro_rules! paged_routes {
         ///
         #[doc = "# Errors"]
         /// If `access_token` is not set.
-        pub fn $name(&self) -> Result<Page<$ret>> {
+        pub fn $name(&self) -> Result<Page<'_, $ret>> {
             let url = self.route(concat!("/api/v1/", $url));
-            let response = self.client.$method(&url)
-                .headers(self.headers.clone())
-                .send()?;
+            let response = self.execute(&mut self.client.$method(&url))?;
 
             Page::new(self, response)
         }
 
-        route!{$($rest)*}
+        paged_routes!{$($rest)*}
     };
+
+    () => {}
+}
+
+macro_rules! paged_routes_id {
+
+    ($(($method:ident) $name:ident: $url:expr => $ret:ty,)*) => {
+        $(
+            /// Equivalent to `/api/v1/
+            #[doc = $url]
+            /// `
+            ///
+            #[doc = "# Errors"]
+            /// If `access_token` is not set.
+            pub fn $name(&self, id: u64) -> Result<Page<'_, $ret>> {
+                let url = self.route(&format!(concat!("/api/v1/", $url), id));
+                let response = self.execute(&mut self.client.$method(&url))?;
+
+                Page::new(self, response)
+            }
+         )*
+    }
+
 }
 
 macro_rules! route {
@@ -129,20 +291,8 @@ macro_rules! route {
                 .file(stringify!($param), $param.as_ref())?
             )*;
 
-            let response = self.client.post(&self.route(concat!("/api/v1/", $url)))
-                .headers(self.headers.clone())
-                .multipart(form_data)
-                .send()?;
-
-            let status = response.status().clone();
-
-            if status.is_client_error() {
-                return Err(Error::Client(status));
-            } else if status.is_server_error() {
-                return Err(Error::Server(status));
-            }
-
-            deserialise(response)
+            self.send(self.client.post(&self.route(concat!("/api/v1/", $url)))
+                .multipart(form_data))
         }
 
         route!{$($rest)*}
@@ -163,20 +313,7 @@ macro_rules! route {
                 )*
             });
 
-            let response = self.client.$method(&self.route(concat!("/api/v1/", $url)))
-                .headers(self.headers.clone())
-                .json(&form_data)
-                .send()?;
-
-            let status = response.status().clone();
-
-            if status.is_client_error() {
-                return Err(Error::Client(status));
-            } else if status.is_server_error() {
-                return Err(Error::Server(status));
-            }
-
-            deserialise(response)
+            self.send_json(&mut self.client.$method(&self.route(concat!("/api/v1/", $url))), &form_data)
         }
 
         route!{$($rest)*}
@@ -217,11 +354,87 @@ macro_rules! route_id {
 
 }
 
+// The routes without pagination, passed to `$route`, so the blocking and
+// async clients share them.
+macro_rules! api_routes {
+    ($route:ident) => {
+        $route! {
+            (get) featured_tags: "featured_tags" => Vec<FeaturedTag>,
+            (get) featured_tag_suggestions: "featured_tags/suggestions" => Vec<Tag>,
+            (get) get_home_timeline: "timelines/home" => Vec<Status>,
+            (get) instance: "instance" => Instance,
+            (get) verify_app_credentials: "apps/verify_credentials" => Application,
+            (get) instance_translation_languages: "instance/translation_languages" => HashMap<String, Vec<String>>,
+            (get) get_emojis: "custom_emojis" => Vec<Emoji>,
+            (get) notifications: "notifications" => Vec<Notification>,
+            (get) reports: "reports" => Vec<Report>,
+            (get) verify_credentials: "accounts/verify_credentials" => Account,
+            (post (account_id: u64, status_ids: Vec<u64>, comment: String,)) report: "reports" => Report,
+            (post (name: String,)) feature_tag: "featured_tags" => FeaturedTag,
+            (post (uri: Cow<'static, str>,)) follows: "follows" => Account,
+            (post multipart (file: Cow<'static, str>,)) media: "media" => Attachment,
+            (post) clear_notifications: "notifications/clear" => Empty,
+        }
+    }
+}
+
+// The routes taking an id, passed to `$route_id`, see `api_routes`.
+macro_rules! api_routes_id {
+    ($route_id:ident) => {
+        $route_id! {
+            (get) get_account: "accounts/{}" => Account,
+            (post) follow: "accounts/{}/follow" => Relationship,
+            (post) unfollow: "accounts/{}/unfollow" => Relationship,
+            (post) block: "accounts/{}/block" => Relationship,
+            (post) unblock: "accounts/{}/unblock" => Relationship,
+            (post) mute: "accounts/{}/mute" => Relationship,
+            (post) unmute: "accounts/{}/unmute" => Relationship,
+            (get) get_notification: "notifications/{}" => Notification,
+            (get) get_status: "statuses/{}" => Status,
+            (get) get_context: "statuses/{}/context" => Context,
+            (get) get_card: "statuses/{}/card" => Card,
+            (get) status_history: "statuses/{}/history" => Vec<StatusEdit>,
+            (post) reblog: "statuses/{}/reblog" => Status,
+            (post) unreblog: "statuses/{}/unreblog" => Status,
+            (post) favourite: "statuses/{}/favourite" => Status,
+            (post) unfavourite: "statuses/{}/unfavourite" => Status,
+            (post) bookmark: "statuses/{}/bookmark" => Status,
+            (post) unbookmark: "statuses/{}/unbookmark" => Status,
+            (post) authorize_follow_request: "follow_requests/{}/authorize" => Relationship,
+            (post) reject_follow_request: "follow_requests/{}/reject" => Relationship,
+            (post) dismiss_notification: "notifications/{}/dismiss" => Empty,
+            (delete) delete_status: "statuses/{}" => Empty,
+            (delete) unfeature_tag: "featured_tags/{}" => Empty,
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+pub mod async_client;
+
 /// Your mastodon application client, handles all requests to and from Mastodon.
 #[derive(Clone, Debug)]
 pub struct Mastodon {
     client: Client,
+    // The client for `redirect_target`, built on first use.
+    no_redirects: Arc<Mutex<Option<Client>>>,
     headers: Headers,
+    dry_run: bool,
+    read_only: bool,
+    scopes: Option<Scopes>,
+    check_scopes: bool,
+    cancellation: Option<CancellationToken>,
+    max_response_size: Option<u64>,
+    lenient: bool,
+    animate_images: bool,
+    images: Arc<Mutex<HashMap<String, Vec<u8>>>>,
+    current_user: Arc<Mutex<Option<Account>>>,
+    rate_limit: Arc<Mutex<Option<RateLimit>>>,
+    last_response: Arc<Mutex<Option<ResponseMeta>>>,
+    deprecations: Arc<Mutex<HashMap<String, Deprecation>>>,
+    stats: Arc<Mutex<Option<Stats>>>,
+    circuit_breaker: Arc<Mutex<Option<CircuitBreaker>>>,
+    relationships: Arc<Mutex<Option<HashMap<String, Relationship>>>>,
     /// Raw data about your mastodon instance.
     pub data: Data
 }
@@ -230,7 +443,9 @@ pub struct Mastodon {
 /// to authenticate on every run.
 #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 pub struct Data {
-    /// Base url of instance eg. `https://mastodon.social`.
+    /// Base url of instance eg. `https://mastodon.social`, or including a
+    /// path for instances not served from the root of their domain, eg.
+    /// `https://example.com/mastodon`.
     pub base: Cow<'static, str>,
     /// The client's id given by the instance.
     pub client_id: Cow<'static, str>,
@@ -242,6 +457,62 @@ pub struct Data {
     pub token: Cow<'static, str>,
 }
 
+impl Data {
+    /// Reads the data from the `MASTODON_BASE`, `MASTODON_CLIENT_ID`,
+    /// `MASTODON_CLIENT_SECRET`, and `MASTODON_ACCESS_TOKEN` environment
+    /// variables. `MASTODON_REDIRECT` is optional and defaults to
+    /// `urn:ietf:wg:oauth:2.0:oob`.
+    ///
+    /// ```no_run
+    /// # use mammut::{Data, Mastodon};
+    /// let mastodon = Mastodon::from_data(Data::from_env().unwrap());
+    /// ```
+    ///
+    /// # Errors
+    /// If a required variable is missing or isn't valid unicode.
+    pub fn from_env() -> Result<Self> {
+        fn var(name: &'static str) -> Result<Cow<'static, str>> {
+            env::var(name).map(Cow::from).map_err(|_| Error::EnvVar(name))
+        }
+
+        let data = Data {
+            base: var("MASTODON_BASE")?,
+            client_id: var("MASTODON_CLIENT_ID")?,
+            client_secret: var("MASTODON_CLIENT_SECRET")?,
+            redirect: var("MASTODON_REDIRECT")
+                .unwrap_or(Cow::Borrowed(REDIRECT_OOB)),
+            token: var("MASTODON_ACCESS_TOKEN")?,
+        };
+
+        data.validate()?;
+        Ok(data)
+    }
+
+    /// Checks the base url is an absolute url with a host, such as
+    /// `https://mastodon.social` rather than `mastodon.social`.
+    ///
+    /// ```
+    /// # use mammut::Data;
+    /// let mut data = Data {
+    ///     base: "mastodon.social".into(),
+    ///     client_id: "".into(),
+    ///     client_secret: "".into(),
+    ///     redirect: "".into(),
+    ///     token: "".into(),
+    /// };
+    /// assert!(data.validate().is_err());
+    ///
+    /// data.base = "https://mastodon.social".into();
+    /// assert!(data.validate().is_ok());
+    /// ```
+    ///
+    /// # Errors
+    /// `Error::Url` if the base url is invalid.
+    pub fn validate(&self) -> Result<()> {
+        validate_base(&self.base)
+    }
+}
+
 /// enum of possible errors encountered using the mastodon API.
 #[derive(Debug, Deserialize)]
 #[serde(untagged)]
@@ -262,21 +533,83 @@ pub enum Error {
     /// Wrapper around the `url::ParseError` struct.
     #[serde(skip_deserializing)]
     Url(UrlError),
+    /// Wrapper around the `std::num::ParseIntError` struct, when an ID
+    /// returned by the instance isn't numeric.
+    #[serde(skip_deserializing)]
+    ParseInt(ParseIntError),
+    /// Wrapper around the `image::ImageError` struct, when an image to
+    /// upload can't be downscaled.
+    #[cfg(feature = "image")]
+    #[serde(skip_deserializing)]
+    Image(image::ImageError),
+    /// Wrapper around the `regex::Error` struct.
+    #[serde(skip_deserializing)]
+    Regex(RegexError),
+    /// No account or status matching the given handle or url was found.
+    #[serde(skip_deserializing)]
+    NotFound(String),
     /// Missing Client Id.
     #[serde(skip_deserializing)]
     ClientIdRequired,
+    /// Missing Client Name when registering an app.
+    #[serde(skip_deserializing)]
+    ClientNameRequired,
     /// Missing Client Secret.
     #[serde(skip_deserializing)]
     ClientSecretRequired,
     /// Missing Access Token.
     #[serde(skip_deserializing)]
     AccessTokenRequired,
+    /// The instance doesn't support uploading media of this MIME type, `None`
+    /// if the type wasn't recognised.
+    #[serde(skip_deserializing)]
+    UnsupportedMediaType(Option<String>),
+    /// A download or response was larger than the maximum size, in bytes.
+    #[serde(skip_deserializing)]
+    TooLarge(u64),
+    /// A status exceeds the limits of the instance.
+    #[serde(skip_deserializing)]
+    InvalidStatus(String),
+    /// The redirect uri passed to `Registration::set_redirect` isn't one the
+    /// app was registered with.
+    #[serde(skip_deserializing)]
+    RedirectNotRegistered(String),
+    /// Missing redirect uri, since the app was registered without one.
+    #[serde(skip_deserializing)]
+    RedirectRequired,
+    /// The `state` passed to the OAuth redirect url doesn't match the one
+    /// in the authorisation url.
+    #[serde(skip_deserializing)]
+    StateMismatch,
+    /// Missing or invalid environment variable, containing its name.
+    #[serde(skip_deserializing)]
+    EnvVar(&'static str),
+    /// The request wasn't sent because the circuit breaker is open after
+    /// too many failures, containing the time left until requests are sent
+    /// again.
+    #[serde(skip_deserializing)]
+    CircuitOpen(Duration),
     /// Generic client error.
     #[serde(skip_deserializing)]
     Client(StatusCode),
     /// Generic server error.
     #[serde(skip_deserializing)]
     Server(StatusCode),
+    /// The request wasn't sent because the client is in dry-run mode, and no
+    /// placeholder response could be synthesised for the route.
+    #[serde(skip_deserializing)]
+    DryRun,
+    /// The request wasn't sent because the client is in read-only mode and
+    /// the request would modify the account, containing its method and url.
+    #[serde(skip_deserializing)]
+    ReadOnly(String),
+    /// The request wasn't sent because the scopes granted to the access
+    /// token don't permit it, containing the scope it requires.
+    #[serde(skip_deserializing)]
+    InsufficientScope(Scopes),
+    /// The request was cancelled with a `CancellationToken`.
+    #[serde(skip_deserializing)]
+    Cancelled,
 }
 
 impl fmt::Display for Error {
@@ -285,6 +618,7 @@ impl fmt::Display for Error {
     }
 }
 
+#[allow(deprecated)]
 impl StdError for Error {
     fn description(&self) -> &str {
         match *self {
@@ -295,12 +629,30 @@ impl StdError for Error {
             Error::Http(ref e) => e.description(),
             Error::Io(ref e) => e.description(),
             Error::Url(ref e) => e.description(),
+            Error::Regex(ref e) => e.description(),
+            #[cfg(feature = "image")]
+            Error::Image(ref e) => e.description(),
+            Error::ParseInt(ref e) => e.description(),
+            Error::NotFound(ref s) => s,
             Error::Client(ref status) | Error::Server(ref status) => {
                 status.canonical_reason().unwrap_or("Unknown Status code")
             },
             Error::ClientIdRequired => "ClientIdRequired",
+            Error::ClientNameRequired => "ClientNameRequired",
             Error::ClientSecretRequired => "ClientSecretRequired",
             Error::AccessTokenRequired => "AccessTokenRequired",
+            Error::InvalidStatus(ref reason) => reason,
+            Error::TooLarge(_) => "TooLarge",
+            Error::UnsupportedMediaType(_) => "UnsupportedMediaType",
+            Error::RedirectNotRegistered(_) => "RedirectNotRegistered",
+            Error::RedirectRequired => "RedirectRequired",
+            Error::StateMismatch => "StateMismatch",
+            Error::EnvVar(name) => name,
+            Error::CircuitOpen(_) => "CircuitOpen",
+            Error::DryRun => "DryRun",
+            Error::ReadOnly(ref request) => request,
+            Error::InsufficientScope(_) => "InsufficientScope",
+            Error::Cancelled => "Cancelled",
         }
     }
 }
@@ -312,6 +664,70 @@ pub struct ApiError {
     pub error: String,
     /// The description of the error.
     pub error_description: Option<String>,
+    /// The status of the response the error was read from, `None` if the
+    /// instance sent it with a successful status.
+    #[serde(skip)]
+    pub status: Option<StatusCode>,
+}
+
+impl ApiError {
+    /// Classifies the error by its `error` string, so it can be matched on.
+    pub fn kind(&self) -> ApiErrorKind {
+        use ApiErrorKind::*;
+
+        match &*self.error {
+            "The access token is invalid" | "invalid_token" => InvalidToken,
+            "The access token expired" => TokenExpired,
+            "The access token was revoked" => TokenRevoked,
+            "This method requires an authenticated user" => AuthenticationRequired,
+            "This action is outside the authorized scopes" | "insufficient_scope" => InsufficientScope,
+            "Record not found" => RecordNotFound,
+            "This action is not allowed" => NotAllowed,
+            "Too many requests" => TooManyRequests,
+            "Remote data could not be fetched" => RemoteDataUnavailable,
+            "invalid_grant" => InvalidGrant,
+            "invalid_client" => InvalidClient,
+            "invalid_scope" => InvalidScope,
+            e if e.starts_with("Validation failed") => ValidationFailed,
+            _ => Unknown,
+        }
+    }
+}
+
+/// The kinds of error the Mastodon API is known to return, see
+/// `ApiError::kind`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ApiErrorKind {
+    /// The access token doesn't exist.
+    InvalidToken,
+    /// The access token expired.
+    TokenExpired,
+    /// The access token was revoked by the user.
+    TokenRevoked,
+    /// The route requires an access token for a user.
+    AuthenticationRequired,
+    /// The access token wasn't granted the scopes the route requires.
+    InsufficientScope,
+    /// The requested entity doesn't exist, or isn't visible to the user.
+    RecordNotFound,
+    /// The user isn't permitted to do this, such as deleting another user's
+    /// status.
+    NotAllowed,
+    /// The rate limit is exhausted.
+    TooManyRequests,
+    /// The instance couldn't fetch a remote entity.
+    RemoteDataUnavailable,
+    /// The authorisation code or credentials are invalid.
+    InvalidGrant,
+    /// The client id or secret is invalid.
+    InvalidClient,
+    /// The requested scopes are invalid.
+    InvalidScope,
+    /// The submitted data failed validation, the `error` string describes
+    /// which fields failed.
+    ValidationFailed,
+    /// An error not known to mammut.
+    Unknown,
 }
 
 impl Mastodon {
@@ -330,110 +746,539 @@ impl Mastodon {
                 client_secret: client_secret.into(),
                 redirect: redirect.into(),
                 token: token.into(),
-
             };
 
-            let mut headers = Headers::new();
-            headers.set(Authorization(Bearer { token: (*data.token).to_owned() }));
-
-            Mastodon {
-                client: client,
-                headers: headers,
-                data: data,
-            }
+            Mastodon::from_data_with_client(data, client)
         }
 
-    /// Creates a mastodon instance from the data struct.
+    /// Creates a mastodon instance from the data struct. The base url isn't
+    /// checked, so an invalid one only fails once requests are sent, see
+    /// `try_from_data`.
     pub fn from_data(data: Data) -> Self {
+        Mastodon::from_data_with_client(data, Client::new())
+    }
+
+    /// Creates a mastodon instance from the data struct, checking the base
+    /// url first.
+    ///
+    /// # Errors
+    /// `Error::Url` if the base url isn't an absolute url with a host, see
+    /// `Data::validate`.
+    pub fn try_from_data(data: Data) -> Result<Self> {
+        data.validate()?;
+        Ok(Mastodon::from_data(data))
+    }
+
+    /// Creates a mastodon instance from the data struct, sending requests
+    /// with `client`, for example to configure its timeouts or proxy.
+    ///
+    /// Connections are kept alive and reused by `client`, and clones of it
+    /// share the same pool, so pass clones of one client to several
+    /// instances crawling the same instance. The pool itself can't be tuned
+    /// with the version of reqwest mammut uses.
+    pub fn from_data_with_client(data: Data, client: Client) -> Self {
         let mut headers = Headers::new();
         headers.set(Authorization(Bearer { token: (*data.token).to_owned() }));
 
         Mastodon {
-            client: Client::new(),
-            headers: headers,
-            data: data,
+            client,
+            no_redirects: Arc::default(),
+            headers,
+            dry_run: false,
+            read_only: false,
+            scopes: None,
+            check_scopes: false,
+            cancellation: None,
+            max_response_size: None,
+            lenient: false,
+            animate_images: true,
+            images: Arc::default(),
+            current_user: Arc::default(),
+            rate_limit: Arc::default(),
+            last_response: Arc::default(),
+            deprecations: Arc::default(),
+            stats: Arc::default(),
+            circuit_breaker: Arc::default(),
+            relationships: Arc::default(),
+            data,
+        }
+    }
+
+    /// Sets how redirects are followed, replacing the HTTP client with one
+    /// built from `builder` using `policy`. A client passed to
+    /// `from_data_with_client` is replaced as well, so configure `builder`
+    /// the same way to keep its timeouts or proxy. Redirects are followed up
+    /// to 10 times by default.
+    ///
+    /// With `RedirectPolicy::none()`, responses redirecting elsewhere fail to
+    /// deserialise, so it's mostly useful for inspecting redirects with
+    /// `last_response`. See `redirect_target` for following a single url.
+    ///
+    /// ```no_run
+    /// # extern crate mammut;
+    /// # extern crate reqwest;
+    /// # use mammut::{Data, Mastodon};
+    /// # use reqwest::{Client, RedirectPolicy};
+    /// # fn main() {
+    /// #    try().unwrap();
+    /// # }
+    /// # fn try() -> mammut::Result<()> {
+    /// let mut mastodon = Mastodon::from_data(Data::from_env()?);
+    /// mastodon.set_redirect_policy(Client::builder(), RedirectPolicy::limited(3))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_redirect_policy(&mut self, mut builder: ClientBuilder, policy: RedirectPolicy)
+        -> Result<()>
+    {
+        self.client = builder.redirect(policy).build()?;
+        Ok(())
+    }
+
+    /// Requests `url` without following redirects, returning where it
+    /// redirects to, or `None` if it doesn't. Useful for finding where a
+    /// media url or instance domain points to without downloading it.
+    pub fn redirect_target(&self, url: &str) -> Result<Option<Url>> {
+        let client = {
+            let mut no_redirects = self.no_redirects.lock().unwrap();
+            match *no_redirects {
+                Some(ref client) => client.clone(),
+                None => {
+                    let client = Client::builder().redirect(RedirectPolicy::none()).build()?;
+                    *no_redirects = Some(client.clone());
+                    client
+                },
+            }
+        };
+        let response = client.head(url).send()?;
+
+        if !response.status().is_redirection() {
+            return Ok(None);
         }
+
+        match response.headers().get::<Location>() {
+            Some(location) => Ok(Some(response.url().join(location)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// A clone of the client sending its requests with `options`, for
+    /// changing the headers of a few calls without reconfiguring the client.
+    /// The clone keeps the HTTP client and settings, and shares the rate
+    /// limit, stats and caches with the client.
+    ///
+    /// ```no_run
+    /// # extern crate mammut;
+    /// # use mammut::{Data, Mastodon, RequestOptions};
+    /// # fn main() {
+    /// #    try().unwrap();
+    /// # }
+    /// # fn try() -> mammut::Result<()> {
+    /// # let mastodon = Mastodon::from_data(Data::from_env()?);
+    /// let traced = mastodon.with_options(RequestOptions::new().header("X-Request-Id", "1234"));
+    /// let instance = traced.instance()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_options(&self, options: RequestOptions) -> Mastodon {
+        let mut mastodon = self.clone();
+        mastodon.headers.extend(options.headers.iter());
+        mastodon
+    }
+
+    /// Sets the languages server-generated text, such as error descriptions,
+    /// should be in, most preferred first, as ISO 639-1 codes. An empty
+    /// slice removes the `Accept-Language` header again.
+    ///
+    /// ```
+    /// # extern crate mammut;
+    /// # use mammut::{Data, Mastodon};
+    /// # fn main() {
+    /// # let data = Data {
+    /// #     base: "".into(),
+    /// #     client_id: "".into(),
+    /// #     client_secret: "".into(),
+    /// #     redirect: "".into(),
+    /// #     token: "".into(),
+    /// # };
+    /// let mut mastodon = Mastodon::from_data(data);
+    /// mastodon.set_accept_language(&["de", "en"]);
+    /// assert_eq!(mastodon.accept_language().as_deref(), Some("de, en;q=0.9"));
+    /// # }
+    /// ```
+    pub fn set_accept_language(&mut self, languages: &[&str]) {
+        if languages.is_empty() {
+            self.headers.remove_raw("Accept-Language");
+            return;
+        }
+
+        let value = languages.iter()
+            .enumerate()
+            .map(|(i, language)| match 10usize.saturating_sub(i).max(1) {
+                10 => language.to_string(),
+                q => format!("{};q=0.{}", language, q),
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        self.headers.set_raw("Accept-Language", value);
+    }
+
+    /// The `Accept-Language` header sent with requests, if set.
+    pub fn accept_language(&self) -> Option<String> {
+        self.headers.get_raw("Accept-Language")
+            .and_then(|raw| raw.one())
+            .map(|value| String::from_utf8_lossy(value).into_owned())
+    }
+
+    /// Enables or disables dry-run mode. While enabled, requests which would
+    /// modify the account (anything but `GET`) are logged instead of being
+    /// sent, and a synthesised successful response is returned.
+    ///
+    /// Writes returning an entity get a placeholder built from the request,
+    /// such as the relationship after `follow`, or a status with the text
+    /// passed to `new_status` and the id 0, posted by `current_user` if it
+    /// was fetched before. Routes for which no entity can be synthesised
+    /// return `Error::DryRun` instead.
+    pub fn set_dry_run(&mut self, dry_run: bool) {
+        self.dry_run = dry_run;
+    }
+
+    /// Whether the client is in dry-run mode.
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    /// Enables or disables read-only mode. While enabled, requests which
+    /// would modify the account (anything but `GET`) fail with
+    /// `Error::ReadOnly` without being sent, even in dry-run mode. Useful for
+    /// monitoring tools which must never change the account.
+    pub fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+    }
+
+    /// Whether the client is in read-only mode.
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// The scopes granted to the access token, if known. Set when the token
+    /// is created with `Registration::create_access_token`; clients created
+    /// from `Data` have to set them with `set_scopes`.
+    pub fn scopes(&self) -> Option<Scopes> {
+        self.scopes
+    }
+
+    /// Sets the scopes granted to the access token, see `scopes`.
+    pub fn set_scopes(&mut self, scopes: Option<Scopes>) {
+        self.scopes = scopes;
+    }
+
+    /// Enables or disables checking requests against the granted scopes.
+    /// While enabled, requests the scopes don't permit, such as `new_status`
+    /// with `Scopes::Read`, fail with `Error::InsufficientScope` without
+    /// being sent. Requests aren't checked while the scopes are unknown.
+    pub fn set_check_scopes(&mut self, check_scopes: bool) {
+        self.check_scopes = check_scopes;
+    }
+
+    /// Whether requests are checked against the granted scopes.
+    pub fn is_checking_scopes(&self) -> bool {
+        self.check_scopes
+    }
+
+    /// A clone of the client only permitted to send requests within
+    /// `scopes`, as well as the scopes granted to its token, for handing to
+    /// code which shouldn't be able to do more. Other requests fail with
+    /// `Error::InsufficientScope`, all of them if none of `scopes` were
+    /// granted.
+    ///
+    /// ```
+    /// # extern crate mammut;
+    /// # use mammut::{Data, Error, Mastodon, StatusBuilder};
+    /// # use mammut::apps::{Scope, Scopes};
+    /// # fn main() {
+    /// # let data = Data {
+    /// #     base: "https://example.com".into(),
+    /// #     client_id: "".into(),
+    /// #     client_secret: "".into(),
+    /// #     redirect: "".into(),
+    /// #     token: "".into(),
+    /// # };
+    /// let mastodon = Mastodon::from_data(data);
+    /// let reader = mastodon.restricted_to(Scopes::Read);
+    ///
+    /// match reader.new_status(StatusBuilder::new("Hello".to_owned())) {
+    ///     Err(Error::InsufficientScope(scopes)) => assert_eq!(scopes, Scope::WriteStatuses.into()),
+    ///     other => panic!("{:?}", other),
+    /// }
+    /// # }
+    /// ```
+    pub fn restricted_to(&self, scopes: Scopes) -> Mastodon {
+        let mut mastodon = self.clone();
+        mastodon.check_scopes = true;
+        mastodon.scopes = Some(match self.scopes {
+            Some(granted) => granted.intersection(scopes).unwrap_or_else(Scopes::empty),
+            None => scopes,
+        });
+
+        mastodon
+    }
+
+    /// A clone of the client whose requests fail with `Error::Cancelled` once
+    /// `token` is cancelled, see the `cancel` module. The clone shares the
+    /// rate limit, stats and caches with the client.
+    pub fn with_cancellation(&self, token: &CancellationToken) -> Mastodon {
+        let mut mastodon = self.clone();
+        mastodon.cancellation = Some(token.clone());
+        mastodon
+    }
+
+    /// Sets the maximum size of response bodies in bytes, or `None` to read
+    /// responses of any size, the default. Routes whose response is larger
+    /// fail with `Error::TooLarge` instead of reading it into memory, which
+    /// protects bots with little memory against misbehaving instances.
+    ///
+    /// Downloads of media are limited separately, see
+    /// `download_attachment`.
+    pub fn set_max_response_size(&mut self, max_size: Option<u64>) {
+        self.max_response_size = max_size;
+    }
+
+    /// The maximum size of response bodies, see `set_max_response_size`.
+    pub fn max_response_size(&self) -> Option<u64> {
+        self.max_response_size
+    }
+
+    /// Enables or disables lenient mode. While enabled, fields of entities
+    /// which fail to deserialise are replaced with a default value, and
+    /// entities in a list which can't be repaired are dropped, logging a
+    /// warning instead of failing the request. Useful with instances running
+    /// a fork sending slightly different JSON.
+    pub fn set_lenient(&mut self, lenient: bool) {
+        self.lenient = lenient;
+    }
+
+    /// Whether the client is in lenient mode.
+    pub fn is_lenient(&self) -> bool {
+        self.lenient
+    }
+
+    /// Sets whether `fetch_avatar` and `fetch_header` fetch the animated
+    /// version of images, or the static one. Enabled by default.
+    pub fn set_animate_images(&mut self, animate_images: bool) {
+        self.animate_images = animate_images;
+    }
+
+    /// Whether `fetch_avatar` and `fetch_header` fetch animated images.
+    pub fn is_animating_images(&self) -> bool {
+        self.animate_images
+    }
+
+    /// The rate limit reported by the most recent response, if the instance
+    /// sent one.
+    pub fn rate_limit(&self) -> Option<RateLimit> {
+        *self.rate_limit.lock().unwrap()
+    }
+
+    /// The status, headers and url of the most recent response. The last
+    /// response is shared with clones of the client, use `with_meta` to get
+    /// the response of a specific call.
+    pub fn last_response(&self) -> Option<ResponseMeta> {
+        self.last_response.lock().unwrap().clone()
+    }
+
+    /// Makes a call, returning its result along with the status and headers
+    /// of its response, `None` if no request was sent, for example in
+    /// dry-run mode. For calls sending several requests, such as `all` on a
+    /// page, this is the last response.
+    ///
+    /// Responses of other calls made concurrently through clones of the
+    /// client can be returned instead.
+    ///
+    /// ```no_run
+    /// # extern crate mammut;
+    /// # use mammut::{Data, Mastodon};
+    /// # fn main() {
+    /// #    try().unwrap();
+    /// # }
+    /// # fn try() -> mammut::Result<()> {
+    /// # let mastodon = Mastodon::from_data(Data::from_env()?);
+    /// let (status, meta) = mastodon.with_meta(|mastodon| mastodon.get_status(1))?;
+    /// if let Some(meta) = meta {
+    ///     println!("{} served by {:?}", status.uri, meta.header("Server"));
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_meta<T, F>(&self, call: F) -> Result<(T, Option<ResponseMeta>)>
+        where F: FnOnce(&Self) -> Result<T>
+    {
+        self.last_response.lock().unwrap().take();
+        let result = call(self)?;
+
+        Ok((result, self.last_response()))
+    }
+
+    /// The endpoints used so far which the instance reported as deprecated.
+    pub fn deprecations(&self) -> Vec<Deprecation> {
+        self.deprecations.lock().unwrap().values().cloned().collect()
+    }
+
+    /// Enables or disables collecting the number of calls, errors and
+    /// latencies of each endpoint, available from `stats`. Disabling it
+    /// discards the stats collected so far. The stats are shared with clones
+    /// of the client.
+    pub fn set_collect_stats(&self, collect: bool) {
+        let mut stats = self.stats.lock().unwrap();
+
+        if !collect {
+            *stats = None;
+        } else if stats.is_none() {
+            *stats = Some(Stats::default());
+        }
+    }
+
+    /// The stats collected since enabling `set_collect_stats`, `None` while
+    /// it's disabled.
+    pub fn stats(&self) -> Option<Stats> {
+        self.stats.lock().unwrap().clone()
+    }
+
+    /// Sets the circuit breaker short-circuiting requests after too many
+    /// failures, or disables it with `None`. The breaker is shared with
+    /// clones of the client.
+    ///
+    /// ```no_run
+    /// # extern crate mammut;
+    /// # use mammut::{Data, Mastodon};
+    /// # use mammut::circuit_breaker::CircuitBreaker;
+    /// # use std::time::Duration;
+    /// # fn main() {
+    /// # let mastodon = Mastodon::from_data(Data::from_env().unwrap());
+    /// mastodon.set_circuit_breaker(Some(CircuitBreaker::new(5, Duration::from_secs(60))));
+    /// # }
+    /// ```
+    pub fn set_circuit_breaker(&self, breaker: Option<CircuitBreaker>) {
+        *self.circuit_breaker.lock().unwrap() = breaker;
+    }
+
+    /// The state of the circuit breaker, `None` if it's disabled.
+    pub fn circuit_breaker(&self) -> Option<CircuitBreaker> {
+        self.circuit_breaker.lock().unwrap().clone()
     }
 
     paged_routes! {
+        (get) blocks: "blocks" => Account,
+        (get) domain_blocks: "domain_blocks" => String,
+        (get) follow_requests: "follow_requests" => Account,
+        (get) bookmarks: "bookmarks" => Status,
         (get) favourites: "favourites" => Status,
+        (get) mutes: "mutes" => Account,
+    }
+
+    paged_routes_id! {
+        (get) followers: "accounts/{}/followers" => Account,
+        (get) following: "accounts/{}/following" => Account,
     }
 
-    route! {
-        (delete (domain: String,)) unblock_domain: "domain_blocks" => Empty,
-        (get) blocks: "blocks" => Vec<Account>,
-        (get) domain_blocks: "domain_blocks" => Vec<String>,
-        (get) follow_requests: "follow_requests" => Vec<Account>,
-        (get) get_home_timeline: "timelines/home" => Vec<Status>,
-        (get) instance: "instance" => Instance,
-        (get) get_emojis: "custom_emojis" => Vec<Emoji>,
-        (get) mutes: "mutes" => Vec<Account>,
-        (get) notifications: "notifications" => Vec<Notification>,
-        (get) reports: "reports" => Vec<Report>,
-        (get) verify_credentials: "accounts/verify_credentials" => Account,
-        (post (account_id: u64, status_ids: Vec<u64>, comment: String,)) report: "reports" => Report,
-        (post (domain: String,)) block_domain: "domain_blocks" => Empty,
-        (post (id: u64,)) authorize_follow_request: "accounts/follow_requests/authorize" => Empty,
-        (post (id: u64,)) reject_follow_request: "accounts/follow_requests/reject" => Empty,
-        (post (q: String, resolve: bool,)) search: "search" => SearchResult,
-        (post (uri: Cow<'static, str>,)) follows: "follows" => Account,
-        (post multipart (file: Cow<'static, str>,)) media: "media" => Attachment,
-        (post) clear_notifications: "notifications/clear" => Empty,
-    }
-
-    route_id! {
-        (get) get_account: "accounts/{}" => Account,
-        (get) followers: "accounts/{}/followers" => Vec<Account>,
-        (get) following: "accounts/{}/following" => Vec<Account>,
-        (get) follow: "accounts/{}/follow" => Account,
-        (get) unfollow: "accounts/{}/unfollow" => Account,
-        (get) block: "accounts/{}/block" => Account,
-        (get) unblock: "accounts/{}/unblock" => Account,
-        (get) mute: "accounts/{}/mute" => Account,
-        (get) unmute: "accounts/{}/unmute" => Account,
-        (get) get_notification: "notifications/{}" => Notification,
-        (get) get_status: "statuses/{}" => Status,
-        (get) get_context: "statuses/{}/context" => Context,
-        (get) get_card: "statuses/{}/card" => Card,
-        (get) reblogged_by: "statuses/{}/reblogged_by" => Vec<Account>,
-        (get) favourited_by: "statuses/{}/favourited_by" => Vec<Account>,
-        (post) reblog: "statuses/{}/reblog" => Status,
-        (post) unreblog: "statuses/{}/unreblog" => Status,
-        (post) favourite: "statuses/{}/favourite" => Status,
-        (post) unfavourite: "statuses/{}/unfavourite" => Status,
-        (delete) delete_status: "statuses/{}" => Empty,
+    paged_routes_request! {
+        (get) blocks_with: "blocks" => Account,
+        (get) domain_blocks_with: "domain_blocks" => String,
+        (get) follow_requests_with: "follow_requests" => Account,
+        (get) bookmarks_with: "bookmarks" => Status,
+        (get) favourites_with: "favourites" => Status,
+        (get) mutes_with: "mutes" => Account,
+        (get) get_home_timeline_with: "timelines/home" => Status,
+        (get) notifications_with: "notifications" => Notification,
     }
 
+    paged_routes_request_id! {
+        (get) followers_with: "accounts/{}/followers" => Account,
+        (get) following_with: "accounts/{}/following" => Account,
+    }
+
+    api_routes!(route);
+    api_routes_id!(route_id);
+
+    /// Update the profile of the authenticated account.
     pub fn update_credentials(&self, changes: CredientialsBuilder)
         -> Result<Account>
     {
 
         let url = self.route("/api/v1/accounts/update_credentials");
-        let response = self.client.patch(&url)
-            .headers(self.headers.clone())
-            .multipart(changes.into_form()?)
-            .send()?;
+        let account: Account = self.send(self.client.patch(&url)
+            .multipart(changes.into_form()?))?;
 
-        let status = response.status().clone();
+        if !self.dry_run {
+            *self.current_user.lock().unwrap() = Some(account.clone());
+        }
 
-        if status.is_client_error() {
-            return Err(Error::Client(status));
-        } else if status.is_server_error() {
-            return Err(Error::Server(status));
+        Ok(account)
+    }
+
+    /// Returns the authenticated account. The result of `verify_credentials`
+    /// is cached after the first call, use `refresh_current_user` to fetch it
+    /// again.
+    pub fn current_user(&self) -> Result<Account> {
+        if let Some(ref account) = *self.current_user.lock().unwrap() {
+            return Ok(account.clone());
         }
 
-        deserialise(response)
+        self.refresh_current_user()
+    }
+
+    /// The number of pending follow requests of the authenticated account,
+    /// for showing a badge on locked accounts. Refreshes `current_user`.
+    pub fn follow_requests_count(&self) -> Result<u64> {
+        let account = self.refresh_current_user()?;
+        Ok(account.source.map_or(0, |source| source.follow_requests_count))
+    }
+
+    /// Fetches the authenticated account with `verify_credentials`, replacing
+    /// the account cached by `current_user`.
+    pub fn refresh_current_user(&self) -> Result<Account> {
+        let account = self.verify_credentials()?;
+        *self.current_user.lock().unwrap() = Some(account.clone());
+        Ok(account)
     }
 
     /// Post a new status to the account.
     pub fn new_status(&self, status: StatusBuilder) -> Result<Status> {
 
-        let response = self.client.post(&self.route("/api/v1/statuses"))
-            .headers(self.headers.clone())
-            .json(&status)
-            .send()?;
+        self.send_json(&mut self.client.post(&self.route("/api/v1/statuses")), &status)
+    }
+
+    /// Post a new status with an `Idempotency-Key`, retrying with the same key
+    /// won't post the status twice if the earlier attempt reached the
+    /// instance.
+    pub fn new_status_with_key(&self, status: StatusBuilder, idempotency_key: &str)
+        -> Result<Status>
+    {
+        let mut headers = Headers::new();
+        headers.set_raw("Idempotency-Key", idempotency_key.to_owned());
+
+        self.send_json(self.client.post(&self.route("/api/v1/statuses"))
+            .headers(headers), &status)
+    }
+
+    /// Hide all statuses and notifications from `domain`, and remove the
+    /// authenticated user's followers from it.
+    ///
+    /// # Errors
+    /// If `access_token` is not set.
+    pub fn block_domain(&self, domain: &str) -> Result<Empty> {
+        self.send(self.client.post(&self.route("/api/v1/domain_blocks"))
+            .form(&[("domain", domain)]))
+    }
 
-        deserialise(response)
+    /// Unblock `domain`, see `block_domain`.
+    ///
+    /// # Errors
+    /// If `access_token` is not set.
+    pub fn unblock_domain(&self, domain: &str) -> Result<Empty> {
+        self.send(self.client.delete(&self.route("/api/v1/domain_blocks"))
+            .form(&[("domain", domain)]))
     }
 
     /// Get the federated timeline for the instance.
@@ -447,6 +1292,33 @@ impl Mastodon {
         self.get(url)
     }
 
+    /// Get the federated timeline for the instance, starting at the page of
+    /// `request`.
+    pub fn get_public_timeline_with(&self, local: bool, request: PageRequest)
+        -> Result<Page<'_, Status>>
+    {
+        let url = self.route(&format!("/api/v1/timelines/public{}", request.to_query_string()));
+        self.local_timeline_page(url, local)
+    }
+
+    /// Get timeline filtered by a hashtag(eg. `#coffee`) either locally or
+    /// federated, starting at the page of `request`.
+    pub fn get_tagged_timeline_with(&self, hashtag: &str, local: bool, request: PageRequest)
+        -> Result<Page<'_, Status>>
+    {
+        let url = self.route(&format!("/api/v1/timelines/tag/{}{}", hashtag, request.to_query_string()));
+        self.local_timeline_page(url, local)
+    }
+
+    fn local_timeline_page(&self, mut url: String, local: bool) -> Result<Page<'_, Status>> {
+        if local {
+            url += if url.contains('?') { "&local=1" } else { "?local=1" };
+        }
+
+        let response = self.execute(&mut self.client.get(&url))?;
+        Page::new(self, response)
+    }
+
     /// Get timeline filtered by a hashtag(eg. `#coffee`) either locally or
     /// federated.
     pub fn get_tagged_timeline(&self, hashtag: String, local: bool) -> Result<Vec<Status>> {
@@ -460,47 +1332,203 @@ impl Mastodon {
         self.get(url)
     }
 
-    /// Get statuses of a single account by id. Optionally only with pictures
-    /// and or excluding replies.
-    pub fn statuses(&self, id: u64, only_media: bool, exclude_replies: bool, since_id: Option<u64>, max_id: Option<u64>)
-        -> Result<Vec<Status>>
-        {
-            let mut params = Vec::new();
+    /// Get the accounts which reblogged a status, `limit` accounts per page.
+    pub fn reblogged_by(&self, id: u64, limit: Option<u64>) -> Result<Page<'_, Account>> {
+        self.accounts_page(&format!("/api/v1/statuses/{}/reblogged_by", id), limit)
+    }
 
-            if only_media {
-                params.push(("only_media", "1".to_string()));
-            }
+    /// Get the accounts which favourited a status, `limit` accounts per page.
+    pub fn favourited_by(&self, id: u64, limit: Option<u64>) -> Result<Page<'_, Account>> {
+        self.accounts_page(&format!("/api/v1/statuses/{}/favourited_by", id), limit)
+    }
 
-            if exclude_replies {
-                params.push(("exclude_replies", "1".to_string()));
-            }
+    fn accounts_page(&self, url: &str, limit: Option<u64>) -> Result<Page<'_, Account>> {
+        let mut url = self.route(url);
 
-            if let Some(since_id) = since_id {
-                params.push(("since_id", since_id.to_string()));
-            }
+        if let Some(limit) = limit {
+            url += "?limit=";
+            url += &limit.to_string();
+        }
 
-            if let Some(max_id) = max_id {
-                params.push(("max_id", max_id.to_string()));
-            }
+        let response = self.execute(&mut self.client.get(&url))?;
+        Page::new(self, response)
+    }
+
+    /// Get statuses of a single account by id, filtered by `request`.
+    ///
+    /// ```no_run
+    /// # extern crate mammut;
+    /// # use mammut::{Data, Mastodon};
+    /// # use mammut::requests::StatusesRequest;
+    /// # fn main() {
+    /// #    try().unwrap();
+    /// # }
+    /// # fn try() -> mammut::Result<()> {
+    /// # let mastodon = Mastodon::from_data(Data::from_env()?);
+    /// let pinned = mastodon.statuses(1, StatusesRequest::new().pinned())?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn statuses(&self, id: u64, request: StatusesRequest) -> Result<Vec<Status>> {
+        let url = format!("/api/v1/accounts/{}/statuses{}", id, request.to_query_string());
+
+        self.get(self.route(&url))
+    }
 
-            let url = Url::parse_with_params(&format!("{}/api/v1/accounts/{}/statuses", self.base, id), &params).unwrap();
+    /// Get the saved read markers of the home and notification timelines.
+    pub fn markers(&self) -> Result<Markers> {
+        self.get(self.route("/api/v1/markers?timeline[]=home&timeline[]=notifications"))
+    }
+
+    /// Save the read markers of the home and/or notification timelines, given
+    /// as the ID of the last read status or notification.
+    pub fn update_markers(&self, home: Option<&str>, notifications: Option<&str>)
+        -> Result<Markers>
+    {
+        let mut form_data = json::Map::new();
+
+        if let Some(id) = home {
+            form_data.insert("home".into(), json!({ "last_read_id": id }));
+        }
+
+        if let Some(id) = notifications {
+            form_data.insert("notifications".into(), json!({ "last_read_id": id }));
+        }
+
+        self.send_json(&mut self.client.post(&self.route("/api/v1/markers")), &form_data)
+    }
+
+    /// Get the notifications of the given types. Older instances ignore the
+    /// filter, so the notifications are also filtered locally.
+    pub fn notifications_of_types(&self, types: &[NotificationType])
+        -> Result<Vec<Notification>>
+    {
+        let mut url = self.route("/api/v1/notifications?");
 
-            self.get(url.into_string())
+        for notification_type in types {
+            url += "types[]=";
+            url += notification_type.as_str();
+            url += "&";
         }
+        url.pop();
 
+        let notifications: Vec<Notification> = self.get(url)?;
+
+        Ok(notifications.into_iter()
+            .filter(|notification| notification.is_any_of(types))
+            .collect())
+    }
+
+    /// Dismisses every notification of `notification_type` one by one, as
+    /// the API can only clear all notifications at once. Returns the number
+    /// of notifications dismissed.
+    pub fn clear_notifications_of_type(&self, notification_type: NotificationType) -> Result<usize> {
+        let url = self.route(&format!("/api/v1/notifications?types[]={}", notification_type.as_str()));
+        let response = self.execute(&mut self.client.get(&url))?;
+        let notifications: Vec<Notification> = Page::new(self, response)?.all()?;
+
+        let mut dismissed = 0;
+        for notification in notifications.iter().filter(|notification| notification.notification_type == notification_type) {
+            self.dismiss_notification(notification.id.parse()?)?;
+            dismissed += 1;
+        }
+
+        Ok(dismissed)
+    }
+
+    /// Get the notifications newer than the saved notifications marker, for
+    /// displaying an unread count.
+    pub fn unread_notifications(&self) -> Result<UnreadNotifications> {
+        let marker = self.markers()?.notifications;
+        let mut url = self.route("/api/v1/notifications?limit=40");
+
+        if let Some(ref marker) = marker {
+            url += "&min_id=";
+            url += &marker.last_read_id;
+        }
+
+        let mut page = Page::new(self, self.execute(&mut self.client.get(&url))?)?;
+        let mut notifications = mem::take(&mut page.initial_items);
+
+        if marker.is_some() {
+            while let Some(mut newer) = page.prev_page()? {
+                if newer.is_empty() {
+                    break;
+                }
+
+                newer.extend(notifications);
+                notifications = newer;
+            }
+        }
+
+        Ok(UnreadNotifications {
+            count: notifications.len(),
+            notifications,
+            marker,
+        })
+    }
+
+    /// Enables or disables caching the relationships returned by
+    /// `relationships`, so they're only requested once per account. The
+    /// cached relationship to an account is dropped when following,
+    /// blocking or muting it, and all of them when blocking a domain.
+    /// Disabling it discards the cache. The cache is shared with clones of
+    /// the client.
+    ///
+    /// Relationships changed by the other account, such as it following the
+    /// authenticated one, aren't noticed while they're cached.
+    pub fn set_cache_relationships(&self, cache: bool) {
+        let mut relationships = self.relationships.lock().unwrap();
+
+        if !cache {
+            *relationships = None;
+        } else if relationships.is_none() {
+            *relationships = Some(HashMap::new());
+        }
+    }
 
     /// Returns the client account's relationship to a list of other accounts.
     /// Such as whether they follow them or vice versa.
+    ///
+    /// While `set_cache_relationships` is enabled, only the relationships
+    /// which aren't cached yet are requested.
     pub fn relationships(&self, ids: &[u64]) -> Result<Vec<Relationship>> {
+        let ids: Vec<String> = ids.iter().map(|id| id.to_string()).collect();
+
+        // The cache is locked again below, so it mustn't be held while
+        // fetching.
+        let missing: Option<Vec<&String>> = self.relationships.lock().unwrap().as_ref()
+            .map(|cached| ids.iter().filter(|id| !cached.contains_key(*id)).collect());
+        let missing = match missing {
+            Some(missing) => missing,
+            None => return self.fetch_relationships(&ids.iter().collect::<Vec<_>>()),
+        };
+
+        let fetched = if missing.is_empty() {
+            Vec::new()
+        } else {
+            self.fetch_relationships(&missing)?
+        };
+
+        let mut cache = self.relationships.lock().unwrap();
+        let cached = cache.get_or_insert_with(HashMap::new);
+        for relationship in fetched {
+            cached.insert(relationship.id.clone(), relationship);
+        }
+
+        Ok(ids.iter().filter_map(|id| cached.get(id).cloned()).collect())
+    }
+
+    fn fetch_relationships(&self, ids: &[&String]) -> Result<Vec<Relationship>> {
         let mut url = self.route("/api/v1/accounts/relationships?");
 
         if ids.len() == 1 {
             url += "id=";
-            url += &ids[0].to_string();
+            url += ids[0];
         } else {
             for id in ids {
                 url += "id[]=";
-                url += &id.to_string();
+                url += id;
                 url += "&";
             }
             url.pop();
@@ -509,20 +1537,315 @@ impl Mastodon {
         self.get(url)
     }
 
+    /// Get accounts suggested to follow, along with why they're suggested,
+    /// at most `limit` of them.
+    pub fn suggestions(&self, limit: Option<u64>) -> Result<Vec<Suggestion>> {
+        let mut url = self.route("/api/v2/suggestions");
+
+        if let Some(limit) = limit {
+            url += "?limit=";
+            url += &limit.to_string();
+        }
+
+        self.get(url)
+    }
+
+    /// Stop suggesting the account `id`.
+    pub fn remove_suggestion(&self, id: u64) -> Result<Empty> {
+        self.delete(self.route(&format!("/api/v1/suggestions/{}", id)))
+    }
+
+    /// Get the identity proofs of an account. Mastodon 3.5 removed identity
+    /// proofs, so instances without the endpoint return no proofs.
+    pub fn account_identity_proofs(&self, id: u64) -> Result<Vec<IdentityProof>> {
+        let url = self.route(&format!("/api/v1/accounts/{}/identity_proofs", id));
+        let response = self.execute(&mut self.client.get(&url))?;
+
+        match response.status() {
+            StatusCode::NotFound | StatusCode::Gone => Ok(Vec::new()),
+            _ => self.read(response),
+        }
+    }
+
+    /// Search for accounts, hashtags and statuses matching `q`, fetching
+    /// them from other instances if `resolve` is set and `q` is an address
+    /// or url. See `search_with` for more options.
+    pub fn search(&self, q: String, resolve: bool) -> Result<SearchResult> {
+        self.search_with(SearchRequest::new(q).resolve(resolve))
+    }
+
+    /// Search for accounts, hashtags and statuses with the options of
+    /// `request`.
+    ///
+    /// ```no_run
+    /// # extern crate mammut;
+    /// # use mammut::{Data, Mastodon, SearchRequest};
+    /// # use mammut::requests::SearchType;
+    /// # fn main() {
+    /// #    try().unwrap();
+    /// # }
+    /// # fn try() -> mammut::Result<()> {
+    /// # let mastodon = Mastodon::from_data(Data::from_env()?);
+    /// let request = SearchRequest::new("rust").search_type(SearchType::Statuses);
+    /// let first = mastodon.search_with(request.clone().limit(20))?;
+    /// let second = mastodon.search_with(request.offset(20).limit(20))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn search_with(&self, request: SearchRequest) -> Result<SearchResult> {
+        self.get(self.route(&format!("/api/v2/search{}", request.to_query_string())))
+    }
+
+    /// Looks up the status or profile at `url`, such as one pasted by the
+    /// user, fetching it from its instance if this instance doesn't know it
+    /// yet, so it can be interacted with from this account.
+    ///
+    /// ```no_run
+    /// # extern crate mammut;
+    /// # use mammut::{Data, Mastodon};
+    /// # use mammut::entities::prelude::Resolved;
+    /// # fn main() {
+    /// #    try().unwrap();
+    /// # }
+    /// # fn try() -> mammut::Result<()> {
+    /// # let mastodon = Mastodon::from_data(Data::from_env()?);
+    /// match mastodon.resolve_url("https://example.com/@mammut/103270115826048975")? {
+    ///     Resolved::Status(status) => { mastodon.favourite(status.id.parse()?)?; },
+    ///     Resolved::Account(account) => { mastodon.follow(account.id.parse()?)?; },
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    /// `Error::NotFound` if nothing was found at `url`.
+    pub fn resolve_url(&self, url: &str) -> Result<Resolved> {
+        self.search_with(SearchRequest::new(url).resolve(true))?
+            .resolved(url)
+            .ok_or_else(|| Error::NotFound(url.to_owned()))
+    }
+
     /// Search for accounts by their name.
     /// Will lookup an account remotely if the search term is in the
     /// `username@domain` format and not yet in the database.
-    // TODO: Add a limit fn
     pub fn search_accounts(&self, query: &str) -> Result<Vec<Account>> {
-        self.get(format!("{}/api/v1/accounts/search?q={}", self.base, query))
+        self.search_accounts_with(query, None)
+    }
+
+    /// Search for accounts by their name, returning at most `limit` accounts.
+    /// See `search_accounts`.
+    pub fn search_accounts_with(&self, query: &str, limit: Option<u64>) -> Result<Vec<Account>> {
+        let mut params = form_urlencoded::Serializer::new(String::new());
+        params.append_pair("q", query).append_pair("resolve", "true");
+
+        if let Some(limit) = limit {
+            params.append_pair("limit", &limit.to_string());
+        }
+
+        self.get(self.route(&format!("/api/v1/accounts/search?{}", params.finish())))
     }
 
     methods![get, post, delete,];
 
+    // Send a request built from `self.client` and deserialise the response,
+    // unless the request is a write and the client is in dry-run mode.
+    fn send<T: for<'de> serde::Deserialize<'de>>(&self, builder: &mut RequestBuilder)
+        -> Result<T>
+    {
+        self.send_body(builder, None)
+    }
+
+    // Send a request with the JSON `body`, see `send`. In dry-run mode, the
+    // response is synthesised from the body.
+    fn send_json<B, T>(&self, builder: &mut RequestBuilder, body: &B) -> Result<T>
+        where B: serde::Serialize, T: for<'de> serde::Deserialize<'de>
+    {
+        let body = json::to_value(body)?;
+        self.send_body(builder.json(&body), Some(&body))
+    }
+
+    fn send_body<T: for<'de> serde::Deserialize<'de>>(&self, builder: &mut RequestBuilder, body: Option<&json::Value>)
+        -> Result<T>
+    {
+        let request = builder.headers(self.headers.clone()).build()?;
+        self.check(&request)?;
+
+        if self.dry_run && *request.method() != Method::Get {
+            info!("dry run: {} {}", request.method(), request.url());
+            let me = self.current_user.lock().unwrap().clone();
+            return dry_run::synthesise(request.method(), &api_path(&self.base, request.url()), body, me.as_ref());
+        }
+
+        self.read(self.dispatch(request)?)
+    }
+
+    // Deserialise the body of a response, or the error it contains.
+    fn read<T: for<'de> serde::Deserialize<'de>>(&self, response: Response) -> Result<T> {
+        let status = response.status();
+
+        if status.is_client_error() || status.is_server_error() {
+            return Err(self.error_from(response));
+        }
+
+        deserialise(response, self.lenient, self.max_response_size)
+    }
+
+    // The error contained in the response to a failed request.
+    fn error_from(&self, response: Response) -> Error {
+        let status = response.status();
+
+        match deserialise::<ApiError>(response, false, self.max_response_size) {
+            Ok(error) => Error::Api(ApiError { status: Some(status), ..error }),
+            Err(_) if status.is_client_error() => Error::Client(status),
+            Err(_) => Error::Server(status),
+        }
+    }
+
+    // Send a request built from `self.client`, returning the raw response.
+    fn execute(&self, builder: &mut RequestBuilder) -> Result<Response> {
+        let request = builder.headers(self.headers.clone()).build()?;
+        self.check(&request)?;
+
+        self.dispatch(request)
+    }
+
+    // Reject requests the client isn't permitted to send before sending
+    // them.
+    fn check(&self, request: &Request) -> Result<()> {
+        self.check_cancelled()?;
+
+        if self.read_only && *request.method() != Method::Get {
+            return Err(Error::ReadOnly(format!("{} {}", request.method(), request.url())));
+        }
+
+        if let (true, Some(granted)) = (self.check_scopes, self.scopes) {
+            if let Some(scope) = apps::required_scope(request.method(), &api_path(&self.base, request.url())) {
+                if !granted.grants(scope) {
+                    return Err(Error::InsufficientScope(scope.into()));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Drop the cached relationships a request to `url` may change.
+    fn invalidate_relationships(&self, url: &Url) {
+        let mut cache = self.relationships.lock().unwrap();
+        let cached = match *cache {
+            Some(ref mut cached) => cached,
+            None => return,
+        };
+
+        match api_path(&self.base, url)[..] {
+            ["api", "v1", "accounts", id, action] if [
+                "follow", "unfollow", "block", "unblock", "mute", "unmute",
+            ].contains(&action) => {
+                cached.remove(id);
+            },
+            ["api", "v1", "follow_requests", id, "authorize"] |
+            ["api", "v1", "follow_requests", id, "reject"] => {
+                cached.remove(id);
+            },
+            ["api", "v1", "domain_blocks"] | ["api", "v1", "follows"] => cached.clear(),
+            _ => {},
+        }
+    }
+
+    fn check_cancelled(&self) -> Result<()> {
+        match self.cancellation {
+            Some(ref token) if token.is_cancelled() => Err(Error::Cancelled),
+            _ => Ok(()),
+        }
+    }
+
+    // Send a request, keeping track of the rate limit, deprecations and
+    // stats.
+    fn dispatch(&self, request: Request) -> Result<Response> {
+        if let Some(ref breaker) = *self.circuit_breaker.lock().unwrap() {
+            breaker.check()?;
+        }
+
+        let url = request.url().clone();
+        let method = request.method().clone();
+
+        let collect_stats = self.stats.lock().unwrap().is_some();
+        let endpoint = if collect_stats || cfg!(any(feature = "metrics", feature = "tracing")) {
+            Some(stats::endpoint(request.method(), &api_path(&self.base, request.url())))
+        } else {
+            None
+        };
+
+        #[cfg(feature = "tracing")]
+        let span = instrument::span(request.method(), endpoint.as_deref().unwrap_or_default());
+
+        let start = Instant::now();
+        let result = {
+            #[cfg(feature = "tracing")]
+            let _entered = span.enter();
+
+            self.client.execute(request)
+        };
+
+        if let Some(ref endpoint) = endpoint {
+            let latency = start.elapsed();
+            let status = result.as_ref().ok().map(|response| response.status());
+
+            if let Some(ref mut stats) = *self.stats.lock().unwrap() {
+                let failed = status.map(|status| !status.is_success()).unwrap_or(true);
+                stats.record(endpoint, latency, failed);
+            }
+
+            #[cfg(feature = "metrics")]
+            instrument::record_request(endpoint, status, latency);
+
+            #[cfg(feature = "tracing")]
+            instrument::record_span(&span, status, latency);
+        }
+
+        self.invalidate_relationships(&url);
+
+        if let Some(ref mut breaker) = *self.circuit_breaker.lock().unwrap() {
+            let failed = result.as_ref()
+                .map(|response| response.status().is_server_error())
+                .unwrap_or(true);
+            breaker.record(failed);
+        }
+
+        let response = result?;
+
+        *self.last_response.lock().unwrap() = Some(ResponseMeta {
+            url: response.url().clone(),
+            status: response.status(),
+            headers: response.headers().clone(),
+        });
+
+        if let Some(rate_limit) = RateLimit::from_headers(response.headers()) {
+            #[cfg(feature = "metrics")]
+            instrument::record_rate_limit(rate_limit.remaining);
+
+            *self.rate_limit.lock().unwrap() = Some(rate_limit);
+        }
+
+        let endpoint = endpoint.unwrap_or_else(|| stats::endpoint(&method, &api_path(&self.base, &url)));
+        if let Some(deprecation) = Deprecation::from_headers(&endpoint, response.headers()) {
+            let mut deprecations = self.deprecations.lock().unwrap();
+
+            if !deprecations.contains_key(&endpoint) {
+                warn!("{} is deprecated, to be removed {}", endpoint,
+                      deprecation.sunset.map_or("eventually".to_owned(), |date| format!("on {}", date)));
+            }
+
+            deprecations.insert(endpoint, deprecation);
+        }
+
+        // Discard the response if the request was cancelled while in flight.
+        self.check_cancelled()?;
+        Ok(response)
+    }
+
     fn route(&self, url: &str) -> String {
-        let mut s = (*self.base).to_owned();
-        s += url;
-        s
+        route(&self.base, url)
     }
 }
 
@@ -552,26 +1875,84 @@ from! {
     IoError, Io,
     SerdeError, Serde,
     UrlError, Url,
+    ParseIntError, ParseInt,
+    RegexError, Regex,
+}
+
+#[cfg(feature = "image")]
+impl From<image::ImageError> for Error {
+    fn from(from: image::ImageError) -> Self {
+        Error::Image(from)
+    }
+}
+
+// Join `url`, starting with a `/`, to the base url, which may include a path
+// and trailing slashes.
+fn route(base: &str, url: &str) -> String {
+    let mut s = base.trim_end_matches('/').to_owned();
+    s += url;
+    s
+}
+
+// The path segments of `url` below the path of the base url, so routes are
+// matched the same with instances served from `https://example.com/mastodon`
+// as from the root of their domain.
+fn api_path<'a>(base: &str, url: &'a Url) -> Vec<&'a str> {
+    let mut segments: Vec<&str> = url.path_segments().map(|segments| segments.collect()).unwrap_or_default();
+
+    if let Ok(base) = Url::parse(base) {
+        let prefix: Vec<&str> = base.path_segments()
+            .map(|segments| segments.filter(|segment| !segment.is_empty()).collect())
+            .unwrap_or_default();
+
+        if segments.starts_with(&prefix) {
+            segments.drain(..prefix.len());
+        }
+    }
+
+    segments
+}
+
+// Check `base` is an absolute url with a host, which paths can be appended
+// to.
+fn validate_base(base: &str) -> Result<()> {
+    let url = Url::parse(base)?;
+
+    if url.cannot_be_a_base() {
+        // `mastodon.social:443` parses with `mastodon.social` as the scheme.
+        Err(Error::Url(UrlError::RelativeUrlWithoutBase))
+    } else if url.host().is_none() {
+        Err(Error::Url(UrlError::EmptyHost))
+    } else {
+        Ok(())
+    }
 }
 
 // Convert the HTTP response body from JSON. Pass up deserialization errors
 // transparently.
-fn deserialise<T: for<'de> serde::Deserialize<'de>>(mut response: Response)
+fn deserialise<T: for<'de> serde::Deserialize<'de>>(response: Response, lenient: bool, max_size: Option<u64>)
     -> Result<T>
 {
-    use std::io::Read;
-
-    let mut vec = Vec::new();
-    response.read_to_end(&mut vec)?;
+    // Lenient mode needs the whole body to repair it.
+    if lenient {
+        from_body(&body::read(response, max_size)?, true)
+    } else {
+        body::deserialise(response, max_size)
+    }
+}
 
-    match json::from_slice(&vec) {
+fn from_body<T: for<'de> serde::Deserialize<'de>>(body: &[u8], lenient: bool) -> Result<T> {
+    match json::from_slice(body) {
         Ok(t) => Ok(t),
         // If deserializing into the desired type fails try again to
         // see if this is an error response.
         Err(e) => {
-            if let Ok(error) = json::from_slice(&vec) {
+            if let Ok(error) = json::from_slice(body) {
                 return Err(Error::Api(error));
             }
+            if lenient {
+                return Ok(lenient::from_slice(body)?);
+            }
             Err(e.into())
         },
     }
@@ -0,0 +1,183 @@
+use std::collections::HashSet;
+
+use entities::account::Account;
+use entities::Empty;
+use entities::list::List;
+use entities::status::Status;
+use page::Page;
+use requests::PageRequest;
+use super::{Mastodon, Result};
+
+/// The changes `Mastodon::sync_list` made to a list.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ListChanges {
+    /// The ids of the accounts added to the list.
+    pub added: Vec<String>,
+    /// The ids of the accounts removed from the list.
+    pub removed: Vec<String>,
+}
+
+impl ListChanges {
+    /// Whether the list already had the desired members.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+impl Mastodon {
+    /// Equivalent to `/api/v1/lists`
+    ///
+    /// # Errors
+    /// If `access_token` is not set.
+    pub fn get_lists(&self) -> Result<Vec<List>> {
+        self.get(self.route("/api/v1/lists"))
+    }
+
+    /// Creates a list titled `title`.
+    pub fn create_list(&self, title: &str) -> Result<List> {
+        self.send_json(&mut self.client.post(&self.route("/api/v1/lists")), &json!({ "title": title }))
+    }
+
+    /// Equivalent to `/api/v1/lists/:id`
+    ///
+    /// # Errors
+    /// If `access_token` is not set.
+    pub fn get_list(&self, id: u64) -> Result<List> {
+        self.get(self.route(&format!("/api/v1/lists/{}", id)))
+    }
+
+    /// Renames the list `id` to `title`.
+    pub fn update_list(&self, id: u64, title: &str) -> Result<List> {
+        let url = self.route(&format!("/api/v1/lists/{}", id));
+        self.send_json(&mut self.client.put(&url), &json!({ "title": title }))
+    }
+
+    /// Deletes the list `id`. Its members aren't unfollowed.
+    pub fn delete_list(&self, id: u64) -> Result<Empty> {
+        self.delete(self.route(&format!("/api/v1/lists/{}", id)))
+    }
+
+    /// Equivalent to `/api/v1/timelines/list/:id`, the statuses of the
+    /// members of the list `id`.
+    ///
+    /// # Errors
+    /// If `access_token` is not set.
+    pub fn get_list_timeline(&self, id: u64) -> Result<Page<'_, Status>> {
+        self.get_list_timeline_with(id, PageRequest::default())
+    }
+
+    /// Equivalent to `/api/v1/timelines/list/:id`, starting at the page of
+    /// `request`.
+    ///
+    /// # Errors
+    /// If `access_token` is not set.
+    pub fn get_list_timeline_with(&self, id: u64, request: PageRequest) -> Result<Page<'_, Status>> {
+        let url = self.route(&format!("/api/v1/timelines/list/{}{}", id, request.to_query_string()));
+        let response = self.execute(&mut self.client.get(&url))?;
+
+        Page::new(self, response)
+    }
+
+    /// Equivalent to `/api/v1/lists/:id/accounts`
+    ///
+    /// # Errors
+    /// If `access_token` is not set.
+    pub fn list_accounts(&self, id: u64) -> Result<Page<'_, Account>> {
+        let url = self.route(&format!("/api/v1/lists/{}/accounts", id));
+        let response = self.execute(&mut self.client.get(&url))?;
+
+        Page::new(self, response)
+    }
+
+    /// Equivalent to `/api/v1/lists/:id/accounts`, starting at the page of
+    /// `request`. A limit of 0 returns all accounts on one page.
+    ///
+    /// # Errors
+    /// If `access_token` is not set.
+    pub fn list_accounts_with(&self, id: u64, request: PageRequest) -> Result<Page<'_, Account>> {
+        let url = self.route(&format!("/api/v1/lists/{}/accounts{}", id, request.to_query_string()));
+        let response = self.execute(&mut self.client.get(&url))?;
+
+        Page::new(self, response)
+    }
+
+    /// Adds the accounts of `account_ids` to the list `id`. Only accounts
+    /// the authenticated user follows can be added.
+    pub fn add_to_list(&self, id: u64, account_ids: &[String]) -> Result<Empty> {
+        let url = self.route(&format!("/api/v1/lists/{}/accounts", id));
+        self.send(self.client.post(&url).json(&json!({ "account_ids": account_ids })))
+    }
+
+    /// Removes the accounts of `account_ids` from the list `id`.
+    pub fn remove_from_list(&self, id: u64, account_ids: &[String]) -> Result<Empty> {
+        let url = self.route(&format!("/api/v1/lists/{}/accounts", id));
+        self.send(self.client.delete(&url).json(&json!({ "account_ids": account_ids })))
+    }
+
+    /// Makes the accounts of `account_ids` the members of the list `id`,
+    /// adding and removing only the accounts which differ, and returning
+    /// what was changed.
+    ///
+    /// ```no_run
+    /// # extern crate mammut;
+    /// # use mammut::{Data, Mastodon};
+    /// # fn main() {
+    /// #    try().unwrap();
+    /// # }
+    /// # fn try() -> mammut::Result<()> {
+    /// # let mastodon = Mastodon::from_data(Data::from_env()?);
+    /// let rustaceans = vec!["1".to_owned(), "42".to_owned()];
+    /// let changes = mastodon.sync_list(7, &rustaceans)?;
+    /// println!("Added {:?}, removed {:?}", changes.added, changes.removed);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn sync_list(&self, id: u64, account_ids: &[String]) -> Result<ListChanges> {
+        let current: Vec<String> = self.list_accounts(id)?.all()?
+            .into_iter()
+            .map(|account| account.id)
+            .collect();
+        let changes = diff(&current, account_ids);
+
+        if !changes.removed.is_empty() {
+            self.remove_from_list(id, &changes.removed)?;
+        }
+        if !changes.added.is_empty() {
+            self.add_to_list(id, &changes.added)?;
+        }
+
+        Ok(changes)
+    }
+}
+
+/// The changes turning the members `current` into `desired`, keeping the
+/// order of each.
+///
+/// ```
+/// # extern crate mammut;
+/// # fn main() {
+/// # use mammut::lists::diff;
+/// let current = vec!["1".to_owned(), "2".to_owned()];
+/// let desired = vec!["2".to_owned(), "3".to_owned()];
+///
+/// let changes = diff(&current, &desired);
+/// assert_eq!(changes.added, ["3"]);
+/// assert_eq!(changes.removed, ["1"]);
+/// # }
+/// ```
+pub fn diff(current: &[String], desired: &[String]) -> ListChanges {
+    let current_set: HashSet<&String> = current.iter().collect();
+    let desired_set: HashSet<&String> = desired.iter().collect();
+    let mut added = HashSet::new();
+
+    ListChanges {
+        added: desired.iter()
+            .filter(|id| !current_set.contains(id) && added.insert(*id))
+            .cloned()
+            .collect(),
+        removed: current.iter()
+            .filter(|id| !desired_set.contains(id))
+            .cloned()
+            .collect(),
+    }
+}
@@ -0,0 +1,140 @@
+//! Advancing the notifications read marker as notifications are consumed, so
+//! the unread count shown by the web UI and other clients stays in sync.
+//!
+//! ```no_run
+//! # extern crate mammut;
+//! # use mammut::{Data, Mastodon};
+//! # use mammut::markers::MarkerTracker;
+//! # use std::time::Duration;
+//! # fn main() {
+//! #    try().unwrap();
+//! # }
+//! # fn try() -> mammut::Result<()> {
+//! # let mastodon = Mastodon::from_data(Data::from_env()?);
+//! let mut tracker = MarkerTracker::new(&mastodon);
+//! let mut notifications = mastodon.notifications()?;
+//! notifications.reverse();
+//!
+//! for notification in tracker.track(notifications) {
+//!     println!("{:?} from {}", notification.notification_type, notification.account.acct);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::cmp::Ordering;
+use std::time::{Duration, Instant};
+
+use entities::notification::Notification;
+use super::{Mastodon, Result};
+
+/// Saves the id of the newest notification consumed as the notifications
+/// marker, at most once per `interval`, and when dropped.
+#[derive(Debug)]
+pub struct MarkerTracker<'a> {
+    mastodon: &'a Mastodon,
+    last_read_id: Option<String>,
+    saved_id: Option<String>,
+    saved_at: Option<Instant>,
+    /// The time to wait between saving the marker, ten seconds by default.
+    /// Notifications read in the meantime are saved together.
+    pub interval: Duration,
+}
+
+impl<'a> MarkerTracker<'a> {
+    /// Creates a tracker saving the marker of the account of `mastodon`.
+    pub fn new(mastodon: &'a Mastodon) -> Self {
+        MarkerTracker {
+            mastodon,
+            last_read_id: None,
+            saved_id: None,
+            saved_at: None,
+            interval: Duration::from_secs(10),
+        }
+    }
+
+    /// The id of the newest notification read, saved or not.
+    pub fn last_read_id(&self) -> Option<&str> {
+        self.last_read_id.as_deref()
+    }
+
+    /// Marks `notification` as read, saving the marker unless it was saved
+    /// less than `interval` ago. Notifications older than the newest one
+    /// read don't move the marker back.
+    pub fn read(&mut self, notification: &Notification) -> Result<()> {
+        let newer = self.last_read_id.as_ref()
+            .is_none_or(|last| cmp_ids(&notification.id, last) == Ordering::Greater);
+
+        if newer {
+            self.last_read_id = Some(notification.id.clone());
+        }
+
+        if self.saved_at.is_none_or(|saved_at| saved_at.elapsed() >= self.interval) {
+            self.flush()?;
+        }
+
+        Ok(())
+    }
+
+    /// Saves the marker now, if notifications were read since it was last
+    /// saved.
+    pub fn flush(&mut self) -> Result<()> {
+        if self.last_read_id.is_none() || self.last_read_id == self.saved_id {
+            return Ok(());
+        }
+
+        self.mastodon.update_markers(None, self.last_read_id.as_deref())?;
+        self.saved_id = self.last_read_id.clone();
+        self.saved_at = Some(Instant::now());
+
+        Ok(())
+    }
+
+    /// Marks each notification of `notifications` as read once it's
+    /// consumed from the returned iterator, logging failures to save the
+    /// marker. Works with any source of notifications, such as polling or a
+    /// stream.
+    pub fn track<I>(&mut self, notifications: I) -> Tracked<'_, 'a, I::IntoIter>
+        where I: IntoIterator<Item = Notification>
+    {
+        Tracked {
+            tracker: self,
+            notifications: notifications.into_iter(),
+        }
+    }
+}
+
+impl<'a> Drop for MarkerTracker<'a> {
+    fn drop(&mut self) {
+        if let Err(e) = self.flush() {
+            warn!("Failed to save the notifications marker: {}", e);
+        }
+    }
+}
+
+/// An iterator marking notifications as read as they're consumed, returned
+/// by `MarkerTracker::track`.
+#[derive(Debug)]
+pub struct Tracked<'t, 'a: 't, I> {
+    tracker: &'t mut MarkerTracker<'a>,
+    notifications: I,
+}
+
+impl<'t, 'a, I: Iterator<Item = Notification>> Iterator for Tracked<'t, 'a, I> {
+    type Item = Notification;
+
+    fn next(&mut self) -> Option<Notification> {
+        let notification = self.notifications.next()?;
+
+        if let Err(e) = self.tracker.read(&notification) {
+            warn!("Failed to save the notifications marker: {}", e);
+        }
+
+        Some(notification)
+    }
+}
+
+// Ids are numeric, so longer ids are newer.
+pub(crate) fn cmp_ids(a: &str, b: &str) -> Ordering {
+    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+}
@@ -0,0 +1,334 @@
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+#[cfg(feature = "image")]
+use image::{self, DynamicImage, FilterType, GenericImageView, ImageOutputFormat};
+#[cfg(feature = "image")]
+use reqwest::mime;
+#[cfg(feature = "image")]
+use reqwest::multipart::{Form, Part};
+use reqwest::header::ContentLength;
+
+use entities::account::Account;
+use entities::attachment::Attachment;
+use entities::instance::MediaAttachmentsConfiguration;
+use super::{Error, Mastodon, Result};
+
+impl Mastodon {
+    /// Downloads the file of an attachment into `writer`, returning the number
+    /// of bytes written. Fails with `Error::TooLarge` once the file exceeds
+    /// `max_size` bytes, in which case part of it may have been written.
+    ///
+    /// Media is often served from another host than the instance, so the
+    /// request is made without the access token.
+    ///
+    /// ```no_run
+    /// # extern crate mammut;
+    /// # use mammut::{Data, Mastodon};
+    /// # use std::fs::File;
+    /// # fn main() {
+    /// #    try().unwrap();
+    /// # }
+    /// # fn try() -> mammut::Result<()> {
+    /// # let mastodon = Mastodon::from_data(Data::from_env()?);
+    /// let status = mastodon.get_status(1)?;
+    ///
+    /// for attachment in &status.media_attachments {
+    ///     let mut file = File::create(&attachment.id)?;
+    ///     mastodon.download_attachment(attachment, &mut file, Some(40 << 20))?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn download_attachment<W: Write>(&self, attachment: &Attachment, writer: &mut W, max_size: Option<u64>)
+        -> Result<u64>
+    {
+        self.download(&attachment.url, writer, max_size)
+    }
+
+    /// Fetches the avatar of an account, animated or not depending on
+    /// `set_animate_images`. Images are cached by url, so an avatar is only
+    /// downloaded once per client, including its clones.
+    pub fn fetch_avatar(&self, account: &Account) -> Result<Vec<u8>> {
+        let url = if self.animate_images { &account.avatar } else { &account.avatar_static };
+        self.fetch_image(url)
+    }
+
+    /// Fetches the header image of an account, like `fetch_avatar`.
+    pub fn fetch_header(&self, account: &Account) -> Result<Vec<u8>> {
+        let url = if self.animate_images { &account.header } else { &account.header_static };
+        self.fetch_image(url)
+    }
+
+    /// Empties the cache of `fetch_avatar` and `fetch_header`.
+    pub fn clear_image_cache(&self) {
+        self.images.lock().unwrap().clear();
+    }
+
+    /// Uploads a file like `media`, after checking the instance supports its
+    /// type. The type is detected from the contents of the file, see
+    /// `detect_media_type`.
+    ///
+    /// # Errors
+    /// `Error::UnsupportedMediaType` with the detected type, or `None` if it
+    /// wasn't recognised, if the instance doesn't list it as supported.
+    ///
+    /// ```no_run
+    /// # extern crate mammut;
+    /// # use mammut::{Data, Mastodon};
+    /// # fn main() {
+    /// #    try().unwrap();
+    /// # }
+    /// # fn try() -> mammut::Result<()> {
+    /// # let mastodon = Mastodon::from_data(Data::from_env()?);
+    /// let configuration = mastodon.instance()?.configuration.unwrap_or_default();
+    /// let attachment = mastodon.media_checked("clip.webm", &configuration.media_attachments)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn media_checked<P: AsRef<Path>>(&self, path: P, limits: &MediaAttachmentsConfiguration)
+        -> Result<Attachment>
+    {
+        let path = path.as_ref();
+        let mut start = Vec::with_capacity(16);
+        File::open(path)?.take(16).read_to_end(&mut start)?;
+
+        if !limits.supported_mime_types.is_empty() {
+            match detect_media_type(&start) {
+                Some(mime_type) if limits.supports_media_type(mime_type) => {},
+                mime_type => return Err(Error::UnsupportedMediaType(mime_type.map(String::from))),
+            }
+        }
+
+        self.media(path.to_string_lossy().into_owned().into())
+    }
+
+    /// Uploads an image like `media`, first downscaling and reencoding it as
+    /// JPEG if it exceeds the `image_size_limit` or `image_matrix_limit` of
+    /// the instance. Images within the limits are uploaded unchanged.
+    ///
+    /// Requires the `image` feature.
+    ///
+    /// # Errors
+    /// `Error::Image` if the file isn't an image which can be decoded, and
+    /// `Error::TooLarge` if the image can't be made small enough.
+    ///
+    /// ```no_run
+    /// # extern crate mammut;
+    /// # use mammut::{Data, Mastodon};
+    /// # fn main() {
+    /// #    try().unwrap();
+    /// # }
+    /// # fn try() -> mammut::Result<()> {
+    /// # let mastodon = Mastodon::from_data(Data::from_env()?);
+    /// let configuration = mastodon.instance()?.configuration.unwrap_or_default();
+    /// let attachment = mastodon.media_downscaled("photo.png", &configuration.media_attachments)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "image")]
+    pub fn media_downscaled<P: AsRef<Path>>(&self, path: P, limits: &MediaAttachmentsConfiguration)
+        -> Result<Attachment>
+    {
+        let path = path.as_ref();
+        let mut file = Vec::new();
+        File::open(path)?.read_to_end(&mut file)?;
+
+        let original = image::load_from_memory(&file)?;
+        let (width, height) = original.dimensions();
+        let pixels = u64::from(width) * u64::from(height);
+        let too_large = |size: usize| limits.image_size_limit.map(|limit| size as u64 > limit).unwrap_or(false);
+        let too_many_pixels = limits.image_matrix_limit.map(|limit| pixels > limit).unwrap_or(false);
+
+        if !too_large(file.len()) && !too_many_pixels {
+            return self.media(path.to_string_lossy().into_owned().into());
+        }
+
+        let mut scale = limits.image_matrix_limit
+            .map(|limit| (limit as f64 / pixels as f64).sqrt().min(1.0))
+            .unwrap_or(1.0);
+
+        // Shrink the image further until the JPEG fits the size limit.
+        while scale * f64::from(width.min(height)) >= 1.0 {
+            let resized = original.resize((f64::from(width) * scale) as u32,
+                                          (f64::from(height) * scale) as u32,
+                                          FilterType::Lanczos3);
+            let mut jpeg = Vec::new();
+            DynamicImage::ImageRgb8(resized.to_rgb()).write_to(&mut jpeg, ImageOutputFormat::JPEG(85))?;
+
+            if !too_large(jpeg.len()) {
+                return self.upload_jpeg(jpeg, path);
+            }
+
+            scale *= 0.75;
+        }
+
+        Err(Error::TooLarge(limits.image_size_limit.unwrap_or(0)))
+    }
+
+    /// Uploads a file like `media`, removing the EXIF and XMP metadata of
+    /// JPEGs first, which may include the location the photo was taken at.
+    /// Other files are uploaded unchanged.
+    ///
+    /// Requires the `image` feature.
+    #[cfg(feature = "image")]
+    pub fn media_without_metadata<P: AsRef<Path>>(&self, path: P) -> Result<Attachment> {
+        let path = path.as_ref();
+        let mut file = Vec::new();
+        File::open(path)?.read_to_end(&mut file)?;
+
+        if file.starts_with(JPEG_START) {
+            self.upload_jpeg(strip_metadata(&file), path)
+        } else {
+            self.media(path.to_string_lossy().into_owned().into())
+        }
+    }
+
+    #[cfg(feature = "image")]
+    fn upload_jpeg(&self, jpeg: Vec<u8>, path: &Path) -> Result<Attachment> {
+        let name = path.file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "image".to_owned());
+        let length = jpeg.len() as u64;
+        let part = Part::reader_with_length(io::Cursor::new(jpeg), length)
+            .mime(mime::IMAGE_JPEG)
+            .file_name(format!("{}.jpg", name));
+
+        self.send(self.client.post(&self.route("/api/v1/media"))
+            .multipart(Form::new().part("file", part)))
+    }
+
+    fn fetch_image(&self, url: &str) -> Result<Vec<u8>> {
+        if let Some(image) = self.images.lock().unwrap().get(url) {
+            return Ok(image.clone());
+        }
+
+        let mut image = Vec::new();
+        self.download(url, &mut image, None)?;
+        self.images.lock().unwrap().insert(url.to_owned(), image.clone());
+
+        Ok(image)
+    }
+
+    fn download<W: Write>(&self, url: &str, writer: &mut W, max_size: Option<u64>) -> Result<u64> {
+        let response = self.client.get(url).send()?;
+        let status = response.status();
+
+        if status.is_client_error() {
+            return Err(Error::Client(status));
+        } else if status.is_server_error() {
+            return Err(Error::Server(status));
+        }
+
+        let max_size = match max_size {
+            Some(max_size) => max_size,
+            None => return Ok(io::copy(&mut { response }, writer)?),
+        };
+
+        if let Some(&ContentLength(length)) = response.headers().get::<ContentLength>() {
+            if length > max_size {
+                return Err(Error::TooLarge(max_size));
+            }
+        }
+
+        // The length header may be missing or wrong, so read one byte more
+        // than allowed to detect larger files.
+        let written = io::copy(&mut response.take(max_size + 1), writer)?;
+
+        if written > max_size {
+            Err(Error::TooLarge(max_size))
+        } else {
+            Ok(written)
+        }
+    }
+}
+
+/// Detects the MIME type of media from the magic number at its start,
+/// returning `None` for types which can't be uploaded to Mastodon.
+///
+/// ```
+/// # use mammut::media::detect_media_type;
+/// assert_eq!(detect_media_type(b"\x89PNG\r\n\x1a\n"), Some("image/png"));
+/// ```
+pub fn detect_media_type(start: &[u8]) -> Option<&'static str> {
+    let at = |offset: usize, magic: &[u8]| start.len() >= offset && start[offset..].starts_with(magic);
+
+    Some(if at(0, &[0xFF, 0xD8, 0xFF]) {
+        "image/jpeg"
+    } else if at(0, b"\x89PNG\r\n\x1a\n") {
+        "image/png"
+    } else if at(0, b"GIF87a") || at(0, b"GIF89a") {
+        "image/gif"
+    } else if at(0, b"RIFF") && at(8, b"WEBP") {
+        "image/webp"
+    } else if at(0, b"RIFF") && at(8, b"WAVE") {
+        "audio/wave"
+    } else if at(4, b"ftypheic") || at(4, b"ftypheix") || at(4, b"ftypmif1") {
+        "image/heic"
+    } else if at(4, b"ftypavif") {
+        "image/avif"
+    } else if at(4, b"ftypqt") {
+        "video/quicktime"
+    } else if at(4, b"ftypM4A") {
+        "audio/mp4"
+    } else if at(4, b"ftyp") {
+        "video/mp4"
+    } else if at(0, &[0x1A, 0x45, 0xDF, 0xA3]) {
+        "video/webm"
+    } else if at(0, b"OggS") {
+        "audio/ogg"
+    } else if at(0, b"fLaC") {
+        "audio/flac"
+    } else if at(0, b"ID3") || at(0, &[0xFF, 0xFB]) || at(0, &[0xFF, 0xF3]) || at(0, &[0xFF, 0xF2]) {
+        "audio/mpeg"
+    } else {
+        return None;
+    })
+}
+
+#[cfg(feature = "image")]
+const JPEG_START: &[u8] = &[0xFF, 0xD8];
+
+/// Removes the EXIF and XMP segments from a JPEG, returning other data
+/// unchanged. The orientation stored in the EXIF data is removed as well, so
+/// rotated photos may need to be rotated before uploading.
+///
+/// Requires the `image` feature.
+#[cfg(feature = "image")]
+pub fn strip_metadata(jpeg: &[u8]) -> Vec<u8> {
+    if !jpeg.starts_with(JPEG_START) {
+        return jpeg.to_vec();
+    }
+
+    let mut stripped = JPEG_START.to_vec();
+    let mut i = JPEG_START.len();
+
+    // Copy the segments before the image data, skipping the APP1 segments
+    // holding the metadata.
+    while i + 4 <= jpeg.len() && jpeg[i] == 0xFF {
+        let marker = jpeg[i + 1];
+
+        if marker == 0xFF {
+            i += 1;
+            continue;
+        } else if marker == 0xDA {
+            break;
+        }
+
+        let length = (usize::from(jpeg[i + 2]) << 8 | usize::from(jpeg[i + 3])) + 2;
+        let end = (i + length).min(jpeg.len());
+        let payload = &jpeg[(i + 4).min(end)..end];
+        let metadata = marker == 0xE1 &&
+            (payload.starts_with(b"Exif\0") || payload.starts_with(b"http://ns.adobe.com/xap/"));
+
+        if !metadata {
+            stripped.extend_from_slice(&jpeg[i..end]);
+        }
+
+        i = end;
+    }
+
+    stripped.extend_from_slice(&jpeg[i..]);
+    stripped
+}
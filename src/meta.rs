@@ -0,0 +1,40 @@
+use reqwest::StatusCode;
+use reqwest::header::Headers;
+use url::Url;
+
+use page::Links;
+use rate_limit::RateLimit;
+use super::Result;
+
+/// The status, headers and url of a response, which are otherwise discarded
+/// once its body was deserialised. See `Mastodon::last_response` and
+/// `Mastodon::with_meta`.
+#[derive(Debug, Clone)]
+pub struct ResponseMeta {
+    /// The url of the response, after following redirects.
+    pub url: Url,
+    /// The status code of the response.
+    pub status: StatusCode,
+    /// The headers of the response.
+    pub headers: Headers,
+}
+
+impl ResponseMeta {
+    /// The value of the header `name`, `None` if it's missing or not valid
+    /// UTF-8.
+    pub fn header(&self, name: &str) -> Option<String> {
+        self.headers.get_raw(name)
+            .and_then(|raw| raw.one())
+            .and_then(|value| String::from_utf8(value.to_vec()).ok())
+    }
+
+    /// The rate limit reported by the response.
+    pub fn rate_limit(&self) -> Option<RateLimit> {
+        RateLimit::from_headers(&self.headers)
+    }
+
+    /// The neighbouring pages linked by the response.
+    pub fn links(&self) -> Result<Links> {
+        Links::from_headers(&self.headers)
+    }
+}
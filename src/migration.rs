@@ -0,0 +1,192 @@
+//! Moving the follows, lists and bookmarks of an account to another account,
+//! usually on another instance.
+//!
+//! Planning the migration only reads from both accounts, so it doubles as a
+//! dry run:
+//!
+//! ```no_run
+//! # extern crate mammut;
+//! # use mammut::{Data, Mastodon};
+//! # use mammut::migration;
+//! # fn main() {
+//! #    try().unwrap();
+//! # }
+//! # fn try() -> mammut::Result<()> {
+//! # let old = Mastodon::from_data(Data::from_env()?);
+//! # let new = Mastodon::from_data(Data::from_env()?);
+//! let plan = migration::plan(&old, &new)?;
+//! println!("Would follow {} accounts, fill {} lists and copy {} bookmarks",
+//!          plan.follows.len(), plan.lists.len(), plan.bookmarks.len());
+//!
+//! let report = plan.apply(&new);
+//! for (item, e) in &report.failed {
+//!     println!("Couldn't migrate {}: {}", item, e);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashSet;
+
+use entities::account::Account;
+use entities::status::Status;
+use export::{address, domain};
+use requests::{SearchRequest, SearchType};
+use super::{Error, Mastodon, Result};
+
+/// A list to re-create on the new account.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListPlan {
+    /// The title of the list, the new account's list with the same title is
+    /// filled if it has one.
+    pub title: String,
+    /// The `user@domain` addresses of the members of the list.
+    pub members: Vec<String>,
+}
+
+/// What a migration would change on the new account, returned by `plan`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MigrationPlan {
+    /// The `user@domain` addresses of the accounts to follow, leaving out
+    /// those the new account already follows.
+    pub follows: Vec<String>,
+    /// The lists to create or fill.
+    pub lists: Vec<ListPlan>,
+    /// The uris of the statuses to bookmark, leaving out those the new
+    /// account already bookmarked.
+    pub bookmarks: Vec<String>,
+}
+
+/// What a migration changed, returned by `MigrationPlan::apply`.
+#[derive(Debug, Default)]
+pub struct MigrationReport {
+    /// The addresses of the accounts followed.
+    pub followed: Vec<String>,
+    /// The titles of the lists created or filled.
+    pub lists: Vec<String>,
+    /// The uris of the statuses bookmarked.
+    pub bookmarked: Vec<String>,
+    /// The address, list title or uri of each item which couldn't be
+    /// migrated, along with the reason.
+    pub failed: Vec<(String, Error)>,
+}
+
+/// Compares the accounts of `old` and `new`, returning what `apply` would
+/// change on `new`.
+pub fn plan(old: &Mastodon, new: &Mastodon) -> Result<MigrationPlan> {
+    let old_domain = domain(old)?;
+    let new_domain = domain(new)?;
+    let addresses = |accounts: Vec<Account>, domain: &str| -> Vec<String> {
+        accounts.iter().map(|account| address(account, domain)).collect()
+    };
+
+    let me = old.current_user()?.id.parse()?;
+    let following = addresses(old.following(me)?.all()?, &old_domain);
+
+    let new_me = new.current_user()?.id.parse()?;
+    let new_following: HashSet<String> = addresses(new.following(new_me)?.all()?, &new_domain)
+        .into_iter()
+        .map(|address| address.to_lowercase())
+        .collect();
+
+    let mut lists = Vec::new();
+    for list in old.get_lists()? {
+        lists.push(ListPlan {
+            members: addresses(old.list_accounts(list.id.parse()?)?.all()?, &old_domain),
+            title: list.title,
+        });
+    }
+
+    let new_bookmarks: HashSet<String> = new.bookmarks()?.all()?
+        .into_iter()
+        .map(|status| status.uri)
+        .collect();
+
+    Ok(MigrationPlan {
+        follows: following.into_iter()
+            .filter(|address| !new_following.contains(&address.to_lowercase()))
+            .collect(),
+        lists,
+        bookmarks: old.bookmarks()?.all()?
+            .into_iter()
+            .map(|status| status.uri)
+            .filter(|uri| !new_bookmarks.contains(uri))
+            .collect(),
+    })
+}
+
+impl MigrationPlan {
+    /// Follows the accounts, fills the lists and bookmarks the statuses of
+    /// the plan from `new`, carrying on past items which fail.
+    ///
+    /// Accounts have to be followed to be added to a list, so list members
+    /// which weren't followed by either account are reported as failed.
+    pub fn apply(&self, new: &Mastodon) -> MigrationReport {
+        let mut report = MigrationReport::default();
+
+        for handle in &self.follows {
+            match new.resolve_handle(handle).and_then(|account| new.follow(account.id.parse()?)) {
+                Ok(_) => report.followed.push(handle.clone()),
+                Err(e) => report.failed.push((handle.clone(), e)),
+            }
+        }
+
+        for list in &self.lists {
+            match fill_list(new, list, &mut report) {
+                Ok(()) => report.lists.push(list.title.clone()),
+                Err(e) => report.failed.push((list.title.clone(), e)),
+            }
+        }
+
+        for uri in &self.bookmarks {
+            match resolve_status(new, uri).and_then(|status| new.bookmark(status.id.parse()?)) {
+                Ok(_) => report.bookmarked.push(uri.clone()),
+                Err(e) => report.failed.push((uri.clone(), e)),
+            }
+        }
+
+        report
+    }
+}
+
+// Adds the members of `list` to the list of `new` with the same title,
+// creating it if it doesn't exist. Members which can't be resolved are
+// reported as failed, without failing the whole list.
+fn fill_list(new: &Mastodon, list: &ListPlan, report: &mut MigrationReport) -> Result<()> {
+    let id: u64 = match new.get_lists()?.into_iter().find(|existing| existing.title == list.title) {
+        Some(existing) => existing.id.parse()?,
+        None => new.create_list(&list.title)?.id.parse()?,
+    };
+
+    let current: HashSet<String> = new.list_accounts(id)?.all()?
+        .into_iter()
+        .map(|account| account.id)
+        .collect();
+
+    let mut members = Vec::new();
+    for handle in &list.members {
+        match new.resolve_handle(handle) {
+            Ok(account) => if !current.contains(&account.id) {
+                members.push(account.id);
+            },
+            Err(e) => report.failed.push((handle.clone(), e)),
+        }
+    }
+
+    if !members.is_empty() {
+        new.add_to_list(id, &members)?;
+    }
+
+    Ok(())
+}
+
+// Looks up the status at `uri`, fetching it from its instance if the
+// instance of `mastodon` doesn't know it yet.
+fn resolve_status(mastodon: &Mastodon, uri: &str) -> Result<Status> {
+    let request = SearchRequest::new(uri).search_type(SearchType::Statuses).resolve(true);
+
+    mastodon.search_with(request)?.statuses
+        .into_iter()
+        .find(|status| status.uri == uri || status.url.as_deref() == Some(uri))
+        .ok_or_else(|| Error::NotFound(uri.to_owned()))
+}
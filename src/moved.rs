@@ -0,0 +1,41 @@
+use std::collections::HashSet;
+
+use entities::account::Account;
+use super::{Mastodon, Result};
+
+impl Mastodon {
+    /// Follows the `moved` chain of an account to the account it currently
+    /// lives at, fetching each target to pick up moves that happened since.
+    /// Returns `account` itself if it hasn't moved, and stops at the last
+    /// unseen account if the chain contains a loop.
+    pub fn resolve_moved(&self, account: &Account) -> Result<Account> {
+        let mut seen = HashSet::new();
+        let mut current = account.clone();
+        seen.insert(current.id.clone());
+
+        while let Some(moved) = current.moved.take() {
+            if !seen.insert(moved.id.clone()) {
+                break;
+            }
+
+            current = self.get_account(moved.id.parse()?)?;
+        }
+
+        Ok(current)
+    }
+
+    /// Follows the account `account` moved to and unfollows `account`,
+    /// returning the newly followed account. Does nothing but return
+    /// `account` if it hasn't moved.
+    pub fn refollow_moved(&self, account: &Account) -> Result<Account> {
+        let target = self.resolve_moved(account)?;
+
+        if target.id == account.id {
+            return Ok(target);
+        }
+
+        self.follow(target.id.parse()?)?;
+        self.unfollow(account.id.parse()?)?;
+        Ok(target)
+    }
+}
@@ -0,0 +1,355 @@
+//! Persists statuses to a file until they have been posted, so they aren't
+//! lost to network errors or the application exiting while offline. The
+//! `ScheduledOutbox` does the same for statuses to be posted at a later time.
+//!
+//! ```no_run
+//! # extern crate mammut;
+//! # use mammut::{Data, Mastodon, StatusBuilder};
+//! # use mammut::outbox::Outbox;
+//! # fn main() {
+//! #    try().unwrap();
+//! # }
+//! # fn try() -> mammut::Result<()> {
+//! # let mastodon = Mastodon::from_data(Data::from_env()?);
+//! let mut outbox = Outbox::open("outbox.json")?;
+//! outbox.push(StatusBuilder::new("Hello from the train!".into()))?;
+//!
+//! // Later, once back online.
+//! for (key, result) in outbox.flush(&mastodon)? {
+//!     println!("{}: {:?}", key, result.map(|status| status.url));
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::cmp;
+use std::fs::File;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use chrono::{DateTime, Duration, Utc};
+use json;
+use reqwest::StatusCode;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use atomic_file;
+use entities::status::Status;
+use status_builder::StatusBuilder;
+use super::{ApiError, Error, Mastodon, Result};
+
+/// A status waiting in the outbox.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Draft {
+    /// The `Idempotency-Key` the status is posted with, which prevents it
+    /// from being posted twice when retried.
+    pub key: String,
+    /// The status to post.
+    pub status: StatusBuilder,
+    /// The number of failed attempts to post the status.
+    pub attempts: u32,
+}
+
+/// A queue of statuses stored in a JSON file.
+#[derive(Debug)]
+pub struct Outbox {
+    path: PathBuf,
+    drafts: Vec<Draft>,
+}
+
+impl Outbox {
+    /// Opens the outbox stored at `path`, which is created once a status is
+    /// pushed if it doesn't exist.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_owned();
+        let drafts = load(&path)?;
+
+        Ok(Outbox { path, drafts })
+    }
+
+    /// The statuses waiting to be posted, oldest first.
+    pub fn drafts(&self) -> &[Draft] {
+        &self.drafts
+    }
+
+    /// Adds a status to the outbox, returning its idempotency key.
+    pub fn push(&mut self, status: StatusBuilder) -> Result<String> {
+        let key = idempotency_key(self.drafts.len());
+
+        self.drafts.push(Draft {
+            key: key.clone(),
+            status,
+            attempts: 0,
+        });
+        self.save()?;
+
+        Ok(key)
+    }
+
+    /// Removes a status from the outbox without posting it, returning it if
+    /// it was found.
+    pub fn remove(&mut self, key: &str) -> Result<Option<Draft>> {
+        let draft = match self.drafts.iter().position(|draft| draft.key == key) {
+            Some(i) => self.drafts.remove(i),
+            None => return Ok(None),
+        };
+        self.save()?;
+
+        Ok(Some(draft))
+    }
+
+    /// Posts the statuses in the outbox in order, returning the result of
+    /// each attempt along with the status' key.
+    ///
+    /// Statuses rejected by the instance with a client error are removed
+    /// from the outbox. Statuses failing for any other reason are kept to be
+    /// retried, such as network and server errors, the rate limit, or a
+    /// client in read-only mode. Flushing stops at the first status kept for
+    /// a reason other than a server error, since the remaining statuses would
+    /// fail as well. With a client in dry-run mode, every status is posted
+    /// and kept as an attempt, since nothing was sent.
+    pub fn flush(&mut self, mastodon: &Mastodon) -> Result<Vec<(String, Result<Status>)>> {
+        let mut results = Vec::new();
+        let mut kept = Vec::new();
+        let mut drafts = self.drafts.drain(..);
+
+        for mut draft in drafts.by_ref() {
+            let result = mastodon.new_status_with_key(draft.status.clone(), &draft.key);
+            let retryable = is_retryable(&result);
+
+            if retryable || mastodon.is_dry_run() {
+                draft.attempts += 1;
+                kept.push(draft.clone());
+            }
+
+            let stop = retryable && !is_server_error(&result);
+            results.push((draft.key, result));
+
+            if stop {
+                break;
+            }
+        }
+
+        kept.extend(drafts);
+        self.drafts = kept;
+        self.save()?;
+
+        Ok(results)
+    }
+
+    fn save(&self) -> Result<()> {
+        save(&self.path, &self.drafts)
+    }
+}
+
+/// A status waiting in a `ScheduledOutbox` to be posted.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ScheduledDraft {
+    /// The status to post.
+    pub draft: Draft,
+    /// When to post the status.
+    pub scheduled_at: DateTime<Utc>,
+    /// When to retry posting the status after a failed attempt.
+    #[serde(default)]
+    pub retry_at: Option<DateTime<Utc>>,
+}
+
+impl ScheduledDraft {
+    /// When the status is due to be posted.
+    pub fn due_at(&self) -> DateTime<Utc> {
+        self.retry_at.unwrap_or(self.scheduled_at)
+    }
+}
+
+/// Statuses stored in a JSON file to be posted at a later time, for
+/// instances older than Mastodon 2.7 which can't schedule statuses
+/// themselves.
+///
+/// ```no_run
+/// # extern crate chrono;
+/// # extern crate mammut;
+/// # use chrono::{Duration, Utc};
+/// # use mammut::{Data, Mastodon, StatusBuilder};
+/// # use mammut::outbox::ScheduledOutbox;
+/// # fn main() {
+/// #    try().unwrap();
+/// # }
+/// # fn try() -> mammut::Result<()> {
+/// # let mastodon = Mastodon::from_data(Data::from_env()?);
+/// let mut outbox = ScheduledOutbox::open("scheduled.json")?;
+/// outbox.schedule(StatusBuilder::new("Good morning!".into()), Utc::now() + Duration::hours(8))?;
+///
+/// outbox.run(&mastodon, |key, result| println!("{}: {:?}", key, result.map(|status| status.url)))?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct ScheduledOutbox {
+    path: PathBuf,
+    scheduled: Vec<ScheduledDraft>,
+}
+
+impl ScheduledOutbox {
+    /// Opens the scheduled statuses stored at `path`, which is created once a
+    /// status is scheduled if it doesn't exist.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_owned();
+        let scheduled = load(&path)?;
+
+        Ok(ScheduledOutbox { path, scheduled })
+    }
+
+    /// The statuses waiting to be posted, in the order they were scheduled.
+    pub fn scheduled(&self) -> &[ScheduledDraft] {
+        &self.scheduled
+    }
+
+    /// Schedules a status to be posted at `scheduled_at`, returning its
+    /// idempotency key.
+    pub fn schedule(&mut self, status: StatusBuilder, scheduled_at: DateTime<Utc>) -> Result<String> {
+        let key = idempotency_key(self.scheduled.len());
+
+        self.scheduled.push(ScheduledDraft {
+            draft: Draft {
+                key: key.clone(),
+                status,
+                attempts: 0,
+            },
+            scheduled_at,
+            retry_at: None,
+        });
+        self.save()?;
+
+        Ok(key)
+    }
+
+    /// Removes a status without posting it, returning it if it was found.
+    pub fn remove(&mut self, key: &str) -> Result<Option<ScheduledDraft>> {
+        let scheduled = match self.scheduled.iter().position(|scheduled| scheduled.draft.key == key) {
+            Some(i) => self.scheduled.remove(i),
+            None => return Ok(None),
+        };
+        self.save()?;
+
+        Ok(Some(scheduled))
+    }
+
+    /// When the next status is due, `None` if there are no statuses left.
+    pub fn next_due(&self) -> Option<DateTime<Utc>> {
+        self.scheduled.iter().map(ScheduledDraft::due_at).min()
+    }
+
+    /// Posts the statuses which are due, returning the result of each attempt
+    /// along with the status' key.
+    ///
+    /// Like `Outbox::flush`, only statuses rejected by the instance with a
+    /// client error are removed, and posting stops at the first status kept
+    /// for a reason other than a server error, and statuses posted in dry-run
+    /// mode are kept. Retries are delayed by a minute per failed attempt, up
+    /// to an hour.
+    pub fn post_due(&mut self, mastodon: &Mastodon) -> Result<Vec<(String, Result<Status>)>> {
+        let now = Utc::now();
+        let mut results = Vec::new();
+        let mut kept = Vec::new();
+        let mut scheduled = self.scheduled.drain(..);
+
+        for mut next in scheduled.by_ref() {
+            if next.due_at() > now {
+                kept.push(next);
+                continue;
+            }
+
+            let result = mastodon.new_status_with_key(next.draft.status.clone(), &next.draft.key);
+            let retryable = is_retryable(&result);
+
+            if retryable || mastodon.is_dry_run() {
+                next.draft.attempts += 1;
+                next.retry_at = Some(now + retry_delay(next.draft.attempts));
+                kept.push(next.clone());
+            }
+
+            let stop = retryable && !is_server_error(&result);
+            results.push((next.draft.key, result));
+
+            if stop {
+                break;
+            }
+        }
+
+        kept.extend(scheduled);
+        self.scheduled = kept;
+        self.save()?;
+
+        Ok(results)
+    }
+
+    /// Posts every status when it is due, calling `posted` with the result of
+    /// each attempt, until none are left.
+    pub fn run<F>(&mut self, mastodon: &Mastodon, mut posted: F) -> Result<()>
+        where F: FnMut(&str, Result<Status>)
+    {
+        while let Some(due_at) = self.next_due() {
+            if let Ok(wait) = (due_at - Utc::now()).to_std() {
+                thread::sleep(wait);
+            }
+
+            for (key, result) in self.post_due(mastodon)? {
+                posted(&key, result);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn save(&self) -> Result<()> {
+        save(&self.path, &self.scheduled)
+    }
+}
+
+fn retry_delay(attempts: u32) -> Duration {
+    Duration::minutes(i64::from(cmp::min(attempts, 60)))
+}
+
+// Whether posting a status failed for a reason which may go away, anything
+// but the instance rejecting it with a client error other than the rate
+// limit. Errors read from a successful response are retried too.
+fn is_retryable<T>(result: &Result<T>) -> bool {
+    let status = match *result {
+        Ok(_) => return false,
+        Err(Error::Client(status)) => status,
+        Err(Error::Api(ApiError { status: Some(status), .. })) => status,
+        Err(_) => return true,
+    };
+
+    !status.is_client_error() || status == StatusCode::TooManyRequests
+}
+
+fn is_server_error<T>(result: &Result<T>) -> bool {
+    match *result {
+        Err(Error::Server(_)) => true,
+        Err(Error::Api(ApiError { status: Some(status), .. })) => status.is_server_error(),
+        _ => false,
+    }
+}
+
+fn load<T: DeserializeOwned>(path: &Path) -> Result<Vec<T>> {
+    match File::open(path) {
+        Ok(file) => Ok(json::from_reader(file)?),
+        Err(ref e) if e.kind() == ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+// Write to a temporary file first, so a crash can't leave the file half
+// written.
+fn save<T: Serialize>(path: &Path, items: &[T]) -> Result<()> {
+    atomic_file::save_json(path, items)
+}
+
+pub(crate) fn idempotency_key(n: usize) -> String {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    format!("mammut-{}-{}-{}-{}", process::id(), now.as_secs(), now.subsec_nanos(), n)
+}
@@ -1,9 +1,17 @@
+use std::vec;
+
 use super::{Mastodon, Result, deserialise};
 use reqwest::Response;
-use reqwest::header::{Link, RelationType};
+use reqwest::header::Headers;
 use serde::Deserialize;
 use url::Url;
 
+/// A page of entities from a paginated route, along with the links to the
+/// neighbouring pages.
+///
+/// The next page holds older entities, the previous page newer ones. Since
+/// Mastodon 2.6, instances link the previous page with `min_id`, so it holds
+/// the entities directly newer than this page rather than the newest ones.
 pub struct Page<'a, T: for<'de> Deserialize<'de>> {
     mastodon: &'a Mastodon,
     next: Option<Url>,
@@ -16,61 +24,321 @@ macro_rules! pages {
     ($($direction:ident: $fun:ident),*) => {
 
         $(
+            /// Fetch the items of the page in this direction, returns `None`
+            /// if there is no such page.
             pub fn $fun(&mut self) -> Result<Option<Vec<T>>> {
                 let url = match self.$direction.take() {
                     Some(s) => s,
                     None => return Ok(None),
                 };
 
-                let response = self.mastodon.client.get(url)
-                    .headers(self.mastodon.headers.clone())
-                    .send()?;
+                let response = self.mastodon.execute(&mut self.mastodon.client.get(url))?;
 
                 let (prev, next) = get_links(&response)?;
                 self.next = next;
                 self.prev = prev;
 
-                deserialise(response)
+                deserialise(response, self.mastodon.lenient, self.mastodon.max_response_size)
             }
          )*
     }
 }
 
 impl<'a, T: for<'de> Deserialize<'de>> Page<'a, T> {
+    /// Create a page from the response of a paginated route.
     pub fn new(mastodon: &'a Mastodon, response: Response) -> Result<Self> {
         let (prev, next) = get_links(&response)?;
         Ok(Page {
-            initial_items: deserialise(response)?,
+            initial_items: deserialise(response, mastodon.lenient, mastodon.max_response_size)?,
             next,
             prev,
             mastodon
         })
     }
 
+    /// The url of the next page, for example to resume fetching later, `None`
+    /// if this is the last page.
+    pub fn next_url(&self) -> Option<&Url> {
+        self.next.as_ref()
+    }
+
     pages! {
         next: next_page,
         prev: prev_page
     }
+
+    /// An iterator over the items of this page and every following one,
+    /// fetching the next page when the items of the previous one are used
+    /// up.
+    ///
+    /// ```no_run
+    /// # extern crate mammut;
+    /// # use mammut::{Data, Mastodon};
+    /// # fn main() {
+    /// #    try().unwrap();
+    /// # }
+    /// # fn try() -> mammut::Result<()> {
+    /// # let mastodon = Mastodon::from_data(Data::from_env()?);
+    /// for account in mastodon.followers(1)?.items_iter().take(500) {
+    ///     println!("{}", account?.acct);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn items_iter(self) -> Items<Self, T> {
+        Items::new(self)
+    }
+
+    /// Fetch every following page, returning the items of all pages
+    /// including this one.
+    pub fn all(mut self) -> Result<Vec<T>> {
+        let mut items = ::std::mem::take(&mut self.initial_items);
+
+        while let Some(next) = self.next_page()? {
+            if next.is_empty() {
+                break;
+            }
+
+            items.extend(next);
+        }
+
+        Ok(items)
+    }
 }
 
 
 fn get_links(response: &Response) -> Result<(Option<Url>, Option<Url>)> {
-    let mut prev = None;
-    let mut next = None;
-
-    if let Some(link_header) = response.headers().get::<Link>() {
-        for value in link_header.values() {
-            if let Some(relations) = value.rel() {
-                if relations.contains(&RelationType::Next) {
-                    next = Some(Url::parse(value.link())?);
+    let links = Links::from_headers(response.headers())?;
+    Ok((links.prev, links.next))
+}
+
+/// The neighbouring pages linked in the `Link` header of a response.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Links {
+    /// The page of older entities.
+    pub next: Option<Url>,
+    /// The page of newer entities.
+    pub prev: Option<Url>,
+}
+
+impl Links {
+    /// Reads the `Link` headers of a response, which may each hold several
+    /// links.
+    pub fn from_headers(headers: &Headers) -> Result<Self> {
+        let mut links = Links::default();
+
+        if let Some(raw) = headers.get_raw("Link") {
+            for line in raw.iter() {
+                let parsed = Links::parse(&String::from_utf8_lossy(line))?;
+                links.next = links.next.or(parsed.next);
+                links.prev = links.prev.or(parsed.prev);
+            }
+        }
+
+        Ok(links)
+    }
+
+    /// Parses the value of a `Link` header, such as
+    /// `<https://example.com/?max_id=1>; rel="next", <https://example.com/?min_id=2>; rel="prev"`.
+    /// Commas and semicolons inside urls or quoted parameters are handled,
+    /// as are links with several relations, such as `rel="next last"`.
+    ///
+    /// ```
+    /// # extern crate mammut;
+    /// # fn main() {
+    /// # use mammut::page::Links;
+    /// let links = Links::parse(r#"<https://example.com/a?x=1,2>; title="a, b"; rel="next last""#).unwrap();
+    /// assert_eq!(links.next.unwrap().as_str(), "https://example.com/a?x=1,2");
+    /// assert_eq!(links.prev, None);
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    /// `Error::Url` if a `next` or `prev` link isn't a valid url.
+    pub fn parse(value: &str) -> Result<Self> {
+        let mut links = Links::default();
+
+        for link in split_unquoted(value, ',') {
+            let mut parts = split_unquoted(link, ';').into_iter();
+            let target = match parts.next().map(str::trim) {
+                Some(target) if target.starts_with('<') && target.ends_with('>') => &target[1..target.len() - 1],
+                _ => continue,
+            };
+
+            for param in parts {
+                let mut param = param.splitn(2, '=');
+                let name = param.next().unwrap_or("").trim();
+                let value = param.next().unwrap_or("").trim().trim_matches('"');
+
+                if !name.eq_ignore_ascii_case("rel") {
+                    continue;
                 }
 
-                if relations.contains(&RelationType::Prev) {
-                    prev = Some(Url::parse(value.link())?);
+                for relation in value.split_whitespace() {
+                    if relation.eq_ignore_ascii_case("next") && links.next.is_none() {
+                        links.next = Some(Url::parse(target)?);
+                    } else if relation.eq_ignore_ascii_case("prev") && links.prev.is_none() {
+                        links.prev = Some(Url::parse(target)?);
+                    }
                 }
             }
         }
+
+        Ok(links)
+    }
+}
+
+// Splits `value` at each `separator` outside of `<...>` and quotes.
+fn split_unquoted(value: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut in_url = false;
+    let mut in_quotes = false;
+    let mut escaped = false;
+
+    for (i, c) in value.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_quotes => escaped = true,
+            '"' if !in_url => in_quotes = !in_quotes,
+            '<' if !in_quotes => in_url = true,
+            '>' if !in_quotes => in_url = false,
+            c if c == separator && !in_url && !in_quotes => {
+                parts.push(&value[start..i]);
+                start = i + c.len_utf8();
+            },
+            _ => {},
+        }
     }
 
-    Ok((prev, next))
+    parts.push(&value[start..]);
+    parts
+}
+
+/// Routes returning their entities in pages, which can be fetched one after
+/// the other. Implemented by `Page` and by `Vec` for routes returning a single
+/// page, so code fetching entities can be written once for both.
+///
+/// ```no_run
+/// # extern crate mammut;
+/// # use mammut::{Data, Mastodon};
+/// # use mammut::page::Paginate;
+/// # fn main() {
+/// #    try().unwrap();
+/// # }
+/// # fn try() -> mammut::Result<()> {
+/// # let mastodon = Mastodon::from_data(Data::from_env()?);
+/// let marker = "103270";
+/// let unread = mastodon.notifications()?.fetch_until(|notification| notification.id == marker)?;
+/// # Ok(())
+/// # }
+/// ```
+pub trait Paginate<T> {
+    /// Takes the entities of the current page, leaving it empty.
+    fn take_items(&mut self) -> Vec<T>;
+
+    /// Fetches the entities of the following page, returns `None` if there
+    /// is no such page.
+    fn fetch_next(&mut self) -> Result<Option<Vec<T>>>;
+
+    /// Fetches pages until an entity matches `stop`, returning the entities
+    /// before it, including those of the current page. Returns every entity
+    /// if none matches.
+    fn fetch_until<F>(mut self, mut stop: F) -> Result<Vec<T>>
+        where Self: Sized, F: FnMut(&T) -> bool
+    {
+        let mut items = Vec::new();
+        let mut page = self.take_items();
+
+        loop {
+            for item in page {
+                if stop(&item) {
+                    return Ok(items);
+                }
+
+                items.push(item);
+            }
+
+            page = match self.fetch_next()? {
+                Some(next) if !next.is_empty() => next,
+                _ => return Ok(items),
+            };
+        }
+    }
+
+    /// Fetches every following page, returning the entities of all pages
+    /// including the current one.
+    fn fetch_all(self) -> Result<Vec<T>> where Self: Sized {
+        self.fetch_until(|_| false)
+    }
+}
+
+impl<'a, T: for<'de> Deserialize<'de>> Paginate<T> for Page<'a, T> {
+    fn take_items(&mut self) -> Vec<T> {
+        ::std::mem::take(&mut self.initial_items)
+    }
+
+    fn fetch_next(&mut self) -> Result<Option<Vec<T>>> {
+        self.next_page()
+    }
+}
+
+/// The items of pages, fetched as they're needed, see `Page::items_iter`.
+///
+/// The iterator ends at the last page, or after returning the error of a
+/// page which couldn't be fetched.
+#[derive(Debug)]
+pub struct Items<P, T> {
+    pages: P,
+    items: vec::IntoIter<T>,
+    done: bool,
+}
+
+impl<P: Paginate<T>, T> Items<P, T> {
+    /// Iterates over the items of `pages`, starting with those of the
+    /// current page.
+    pub fn new(mut pages: P) -> Self {
+        let items = pages.take_items().into_iter();
+
+        Items {
+            pages,
+            items,
+            done: false,
+        }
+    }
+}
+
+impl<P: Paginate<T>, T> Iterator for Items<P, T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.items.next() {
+                return Some(Ok(item));
+            }
+
+            if self.done {
+                return None;
+            }
+
+            match self.pages.fetch_next() {
+                Ok(Some(next)) if !next.is_empty() => self.items = next.into_iter(),
+                Ok(_) => self.done = true,
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                },
+            }
+        }
+    }
+}
+
+impl<T> Paginate<T> for Vec<T> {
+    fn take_items(&mut self) -> Vec<T> {
+        ::std::mem::take(self)
+    }
+
+    fn fetch_next(&mut self) -> Result<Option<Vec<T>>> {
+        Ok(None)
+    }
 }
@@ -0,0 +1,44 @@
+use std::time::Duration;
+
+use chrono::prelude::*;
+use reqwest::header::Headers;
+
+/// The rate limit of the instance, as reported by the headers of the most
+/// recent response.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    /// The number of requests allowed in the current period.
+    pub limit: u64,
+    /// The number of requests left in the current period.
+    pub remaining: u64,
+    /// When the current period ends and `remaining` is reset to `limit`.
+    pub reset: DateTime<Utc>,
+}
+
+impl RateLimit {
+    /// Reads the `X-RateLimit-*` headers of a response, returns `None` if
+    /// they're missing or malformed.
+    pub fn from_headers(headers: &Headers) -> Option<Self> {
+        fn header(headers: &Headers, name: &str) -> Option<String> {
+            headers.get_raw(name)
+                .and_then(|raw| raw.one())
+                .and_then(|value| String::from_utf8(value.to_vec()).ok())
+        }
+
+        Some(RateLimit {
+            limit: header(headers, "X-RateLimit-Limit")?.parse().ok()?,
+            remaining: header(headers, "X-RateLimit-Remaining")?.parse().ok()?,
+            reset: header(headers, "X-RateLimit-Reset")?.parse().ok()?,
+        })
+    }
+
+    /// How long to wait before sending another request, `None` if there are
+    /// requests remaining in the current period.
+    pub fn wait(&self) -> Option<Duration> {
+        if self.remaining > 0 {
+            return None;
+        }
+
+        Some((self.reset - Utc::now()).to_std().unwrap_or_default())
+    }
+}
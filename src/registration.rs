@@ -1,7 +1,10 @@
+use rand::{self, Rng};
 use reqwest::Client;
+use url::form_urlencoded;
 
-use super::{Error, Mastodon, Result};
+use super::{validate_base, Data, Error, Mastodon, Result};
 use apps::{AppBuilder, Scopes};
+use entities::status::Application;
 
 /// Handles registering your mastodon app to your instance. It is recommended
 /// you cache your data struct to avoid registering on every run.
@@ -11,7 +14,9 @@ pub struct Registration {
     client_id: Option<String>,
     client_secret: Option<String>,
     redirect: Option<String>,
+    redirect_uris: Vec<String>,
     scopes: Scopes,
+    state: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -19,30 +24,83 @@ struct OAuth {
     client_id: String,
     client_secret: String,
     redirect_uri: String,
+    #[serde(flatten)]
+    application: Application,
 }
 
 #[derive(Deserialize)]
 struct AccessToken {
     access_token: String,
+    scope: Option<String>,
 }
 
 impl Registration {
     /// Construct a new registration process to the instance of the `base` url.
+    /// The url may include a path, for instances not served from the root of
+    /// their domain.
     /// ```
+    /// # use mammut::Registration;
     /// let registration = Registration::new("https://mastodon.social");
     /// ```
     pub fn new<I: Into<String>>(base: I) -> Self {
         Registration {
-            base: base.into(),
+            base: base.into().trim_end_matches('/').to_owned(),
             client: Client::new(),
             client_id: None,
             client_secret: None,
             redirect: None,
+            redirect_uris: Vec::new(),
             scopes: Scopes::Read,
+            state: None,
         }
     }
 
-    /// Register the application with the server from the `base` url.
+    /// Construct a registration process for an app which was registered
+    /// before, skipping `register` and continuing with `authorise`. Scopes
+    /// default to `Scopes::Read`, see `set_scopes`.
+    pub fn from_app_credentials<I>(base: I, client_id: I, client_secret: I, redirect: I) -> Self
+        where I: Into<String>
+    {
+        let redirect = redirect.into();
+        Registration {
+            base: base.into().trim_end_matches('/').to_owned(),
+            client: Client::new(),
+            client_id: Some(client_id.into()),
+            client_secret: Some(client_secret.into()),
+            redirect: Some(redirect.clone()),
+            redirect_uris: vec![redirect],
+            scopes: Scopes::Read,
+            state: None,
+        }
+    }
+
+    /// Construct a registration process reusing the app credentials in
+    /// `data`, for getting a new access token with different `scopes` without
+    /// registering the app again. Continue with `authorise` and
+    /// `create_access_token`.
+    ///
+    /// The instance only grants scopes the app was registered with, so an
+    /// app registered with `Scopes::Read` can't be authorised for
+    /// `Scopes::ReadWrite` this way.
+    pub fn reauthorize_with_scopes(data: &Data, scopes: Scopes) -> Self {
+        let mut registration = Registration::from_app_credentials(
+            &*data.base,
+            &*data.client_id,
+            &*data.client_secret,
+            &*data.redirect,
+        );
+        registration.scopes = scopes;
+        registration
+    }
+
+    /// Sets the scopes requested by the authorisation url. `register` sets
+    /// these to the scopes of the app.
+    pub fn set_scopes(&mut self, scopes: Scopes) {
+        self.scopes = scopes;
+    }
+
+    /// Register the application with the server from the `base` url,
+    /// returning the name and website it was registered with.
     ///
     /// ```no_run
     /// # extern crate mammut;
@@ -72,34 +130,123 @@ impl Registration {
     /// # Ok(())
     /// # }
     /// ```
-    pub fn register(&mut self, app_builder: AppBuilder) -> Result<()> {
+    pub fn register(&mut self, app_builder: AppBuilder) -> Result<Application> {
+        app_builder.validate()?;
+        validate_base(&self.base)?;
         let url = format!("{}/api/v1/apps", self.base);
         self.scopes = app_builder.scopes;
         let app: OAuth = self.client.post(&url).form(&app_builder).send()?.json()?;
 
         self.client_id = Some(app.client_id);
         self.client_secret = Some(app.client_secret);
-        self.redirect = Some(app.redirect_uri);
+        // Fall back to the redirect uris the app was registered with if the
+        // instance doesn't return them.
+        let redirect_uris = if app.redirect_uri.trim().is_empty() {
+            app_builder.redirect_uris
+        } else {
+            &app.redirect_uri
+        };
+        self.redirect_uris = redirect_uris.lines()
+            .map(str::trim)
+            .filter(|uri| !uri.is_empty())
+            .map(String::from)
+            .collect();
+        self.redirect = self.redirect_uris.first().cloned();
+
+        Ok(app.application)
+    }
 
+    /// The redirect uris the app was registered with.
+    pub fn redirect_uris(&self) -> &[String] {
+        &self.redirect_uris
+    }
+
+    /// Selects which of the app's redirect uris `authorise` and
+    /// `create_access_token` use, for apps registered with several. Defaults
+    /// to the first one.
+    ///
+    /// # Errors
+    /// `Error::RedirectNotRegistered` if the app wasn't registered with
+    /// `redirect`.
+    pub fn set_redirect<I: Into<String>>(&mut self, redirect: I) -> Result<()> {
+        let redirect = redirect.into();
+        if !self.redirect_uris.contains(&redirect) {
+            return Err(Error::RedirectNotRegistered(redirect));
+        }
+        self.redirect = Some(redirect);
         Ok(())
     }
 
     /// Returns the full url needed for authorisation. This needs to be opened
     /// in a browser.
+    ///
+    /// # Errors
+    /// `Error::RedirectRequired` if the app was registered without a redirect
+    /// uri.
     pub fn authorise(&mut self) -> Result<String> {
         self.is_registered()?;
 
-        let url = format!(
+        let mut url = format!(
             "{}/oauth/authorize?client_id={}&redirect_uri={}&scope={}&response_type=code",
             self.base,
             self.client_id.clone().unwrap(),
-            self.redirect.clone().unwrap(),
+            encode(self.redirect()?),
             self.scopes,
         );
 
+        if let Some(ref state) = self.state {
+            url += "&state=";
+            url += &encode(state);
+        }
+
         Ok(url)
     }
 
+    /// Sets the `state` parameter of the authorisation url, which the
+    /// instance passes back to the redirect url. Check it with
+    /// `verify_state` to protect web apps against CSRF.
+    pub fn set_state<I: Into<String>>(&mut self, state: I) {
+        self.state = Some(state.into());
+    }
+
+    /// Sets the `state` parameter of the authorisation url to a random value,
+    /// and returns it.
+    ///
+    /// ```no_run
+    /// # extern crate mammut;
+    /// # use mammut::Registration;
+    /// # fn main() {
+    /// #    try().unwrap();
+    /// # }
+    /// # fn try() -> mammut::Result<()> {
+    /// # let mut registration = Registration::new("https://mastodon.social");
+    /// let state = registration.generate_state();
+    /// let url = registration.authorise()?;
+    /// // Store `state` in the user's session, then when the redirect url is
+    /// // requested with the `code` and `state` parameters:
+    /// # let returned_state = state.clone();
+    /// registration.verify_state(&returned_state)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn generate_state(&mut self) -> String {
+        let state: String = rand::thread_rng().gen_ascii_chars().take(32).collect();
+        self.state = Some(state.clone());
+        state
+    }
+
+    /// Checks the `state` parameter passed to the redirect url matches the
+    /// one in the authorisation url.
+    ///
+    /// # Errors
+    /// `Error::StateMismatch` if no state was set or it doesn't match.
+    pub fn verify_state(&self, state: &str) -> Result<()> {
+        match self.state {
+            Some(ref expected) if expected == state => Ok(()),
+            _ => Err(Error::StateMismatch),
+        }
+    }
+
     fn is_registered(&self) -> Result<()> {
         if self.client_id.is_none() {
             Err(Error::ClientIdRequired)
@@ -110,28 +257,42 @@ impl Registration {
         }
     }
 
+    fn redirect(&self) -> Result<&str> {
+        self.redirect.as_deref().ok_or(Error::RedirectRequired)
+    }
+
     /// Create an access token from the client id, client secret, and code
     /// provided by the authorisation url.
+    ///
+    /// # Errors
+    /// `Error::RedirectRequired` if the app was registered without a redirect
+    /// uri.
     pub fn create_access_token(self, code: String) -> Result<Mastodon> {
         self.is_registered()?;
+        let redirect = self.redirect()?.to_owned();
         let url = format!(
             "{}/oauth/token?client_id={}&client_secret={}&code={}&grant_type=authorization_code&redirect_uri={}",
             self.base,
             self.client_id.clone().unwrap(),
             self.client_secret.clone().unwrap(),
             code,
-            self.redirect.clone().unwrap()
+            encode(&redirect)
         );
 
         let token: AccessToken = self.client.post(&url).send()?.json()?;
 
-        Ok(Mastodon::from_registration(self.base,
-                                       self.client_id.unwrap(),
-                                       self.client_secret.unwrap(),
-                                       self.redirect.unwrap(),
-                                       token.access_token,
-                                       self.client))
+        let mut mastodon = Mastodon::from_registration(self.base,
+                                                       self.client_id.unwrap(),
+                                                       self.client_secret.unwrap(),
+                                                       redirect,
+                                                       token.access_token,
+                                                       self.client);
+        mastodon.scopes = token.scope.as_deref().and_then(Scopes::from_granted);
+
+        Ok(mastodon)
     }
 }
 
-
+fn encode(value: &str) -> String {
+    form_urlencoded::byte_serialize(value.as_bytes()).collect()
+}
@@ -0,0 +1,361 @@
+use reqwest::header::Headers;
+use url::form_urlencoded;
+
+/// Options for `Mastodon::statuses`, listing the statuses of an account.
+///
+/// ```
+/// # use mammut::requests::StatusesRequest;
+/// let request = StatusesRequest::new()
+///     .exclude_reblogs()
+///     .tagged("rust")
+///     .limit(40);
+/// assert_eq!(request.to_query_string(), "?exclude_reblogs=1&tagged=rust&limit=40");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StatusesRequest {
+    only_media: bool,
+    exclude_replies: bool,
+    exclude_reblogs: bool,
+    pinned: bool,
+    tagged: Option<String>,
+    since_id: Option<u64>,
+    max_id: Option<u64>,
+    min_id: Option<u64>,
+    limit: Option<u64>,
+}
+
+impl StatusesRequest {
+    /// Request all statuses of the account, newest first.
+    pub fn new() -> Self {
+        StatusesRequest::default()
+    }
+
+    /// Only statuses with media attachments.
+    pub fn only_media(mut self) -> Self {
+        self.only_media = true;
+        self
+    }
+
+    /// Skip statuses replying to other statuses.
+    pub fn exclude_replies(mut self) -> Self {
+        self.exclude_replies = true;
+        self
+    }
+
+    /// Skip reblogs of other statuses.
+    pub fn exclude_reblogs(mut self) -> Self {
+        self.exclude_reblogs = true;
+        self
+    }
+
+    /// Only the statuses pinned to the account's profile.
+    pub fn pinned(mut self) -> Self {
+        self.pinned = true;
+        self
+    }
+
+    /// Only statuses with the hashtag `tag`, given without the leading `#`.
+    pub fn tagged<I: Into<String>>(mut self, tag: I) -> Self {
+        self.tagged = Some(tag.into());
+        self
+    }
+
+    /// Only statuses newer than the status `id`.
+    pub fn since_id(mut self, id: u64) -> Self {
+        self.since_id = Some(id);
+        self
+    }
+
+    /// Only statuses older than the status `id`.
+    pub fn max_id(mut self, id: u64) -> Self {
+        self.max_id = Some(id);
+        self
+    }
+
+    /// Only statuses newer than the status `id`, starting with the ones
+    /// directly after it rather than the newest, unlike `since_id`.
+    pub fn min_id(mut self, id: u64) -> Self {
+        self.min_id = Some(id);
+        self
+    }
+
+    /// The maximum number of statuses to return.
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// The query string for the request, including the leading `?`, or an
+    /// empty string if no options are set.
+    pub fn to_query_string(&self) -> String {
+        let mut query = form_urlencoded::Serializer::new(String::new());
+
+        for &(name, set) in &[
+            ("only_media", self.only_media),
+            ("exclude_replies", self.exclude_replies),
+            ("exclude_reblogs", self.exclude_reblogs),
+            ("pinned", self.pinned),
+        ] {
+            if set {
+                query.append_pair(name, "1");
+            }
+        }
+
+        if let Some(ref tag) = self.tagged {
+            query.append_pair("tagged", tag);
+        }
+
+        for &(name, value) in &[
+            ("since_id", self.since_id),
+            ("max_id", self.max_id),
+            ("min_id", self.min_id),
+            ("limit", self.limit),
+        ] {
+            if let Some(value) = value {
+                query.append_pair(name, &value.to_string());
+            }
+        }
+
+        let query = query.finish();
+
+        if query.is_empty() {
+            query
+        } else {
+            format!("?{}", query)
+        }
+    }
+}
+
+/// The position of a page of a paginated route, for the `*_with` variants of
+/// routes such as `Mastodon::get_home_timeline_with`.
+///
+/// ```
+/// # use mammut::requests::PageRequest;
+/// let request = PageRequest::new().min_id("103270").limit(40);
+/// assert_eq!(request.to_query_string(), "?min_id=103270&limit=40");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PageRequest {
+    since_id: Option<String>,
+    max_id: Option<String>,
+    min_id: Option<String>,
+    limit: Option<u64>,
+}
+
+impl PageRequest {
+    /// Request the newest page.
+    pub fn new() -> Self {
+        PageRequest::default()
+    }
+
+    /// Only items newer than the item `id`, the newest of them first.
+    pub fn since_id<I: Into<String>>(mut self, id: I) -> Self {
+        self.since_id = Some(id.into());
+        self
+    }
+
+    /// Only items older than the item `id`.
+    pub fn max_id<I: Into<String>>(mut self, id: I) -> Self {
+        self.max_id = Some(id.into());
+        self
+    }
+
+    /// Only items newer than the item `id`, starting with the ones directly
+    /// after it rather than the newest, unlike `since_id`. Supported since
+    /// Mastodon 2.6.
+    pub fn min_id<I: Into<String>>(mut self, id: I) -> Self {
+        self.min_id = Some(id.into());
+        self
+    }
+
+    /// The maximum number of items per page. Instances cap it, usually at 40
+    /// items, or 80 for accounts.
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// The query string for the request, including the leading `?`, or an
+    /// empty string if no options are set.
+    pub fn to_query_string(&self) -> String {
+        let mut query = form_urlencoded::Serializer::new(String::new());
+
+        for &(name, value) in &[
+            ("since_id", &self.since_id),
+            ("max_id", &self.max_id),
+            ("min_id", &self.min_id),
+        ] {
+            if let Some(ref value) = *value {
+                query.append_pair(name, value);
+            }
+        }
+
+        if let Some(limit) = self.limit {
+            query.append_pair("limit", &limit.to_string());
+        }
+
+        let query = query.finish();
+
+        if query.is_empty() {
+            query
+        } else {
+            format!("?{}", query)
+        }
+    }
+}
+
+/// The type of results a search is restricted to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchType {
+    /// Only accounts.
+    Accounts,
+    /// Only hashtags.
+    Hashtags,
+    /// Only statuses.
+    Statuses,
+}
+
+impl SearchType {
+    /// The name of the type used by the API.
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            SearchType::Accounts => "accounts",
+            SearchType::Hashtags => "hashtags",
+            SearchType::Statuses => "statuses",
+        }
+    }
+}
+
+/// Options for `Mastodon::search_with`.
+///
+/// ```
+/// # use mammut::requests::{SearchRequest, SearchType};
+/// let request = SearchRequest::new("rust")
+///     .search_type(SearchType::Hashtags)
+///     .offset(20)
+///     .limit(20);
+/// assert_eq!(request.to_query_string(), "?q=rust&type=hashtags&offset=20&limit=20");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchRequest {
+    query: String,
+    search_type: Option<SearchType>,
+    resolve: bool,
+    following: bool,
+    account_id: Option<String>,
+    offset: Option<u64>,
+    limit: Option<u64>,
+}
+
+impl SearchRequest {
+    /// Search accounts, hashtags and statuses for `query`.
+    pub fn new<I: Into<String>>(query: I) -> Self {
+        SearchRequest {
+            query: query.into(),
+            search_type: None,
+            resolve: false,
+            following: false,
+            account_id: None,
+            offset: None,
+            limit: None,
+        }
+    }
+
+    /// Only return results of `search_type`.
+    pub fn search_type(mut self, search_type: SearchType) -> Self {
+        self.search_type = Some(search_type);
+        self
+    }
+
+    /// Fetch accounts and statuses from other instances if `query` is their
+    /// address or url.
+    pub fn resolve(mut self, resolve: bool) -> Self {
+        self.resolve = resolve;
+        self
+    }
+
+    /// Only accounts the user follows.
+    pub fn following(mut self) -> Self {
+        self.following = true;
+        self
+    }
+
+    /// Only statuses posted by the account `id`.
+    pub fn account_id<I: Into<String>>(mut self, id: I) -> Self {
+        self.account_id = Some(id.into());
+        self
+    }
+
+    /// Skip the first `offset` results, for fetching the following pages of
+    /// results. Only supported with a `search_type`.
+    pub fn offset(mut self, offset: u64) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// The maximum number of results of each type.
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// The query string for the request, including the leading `?`.
+    pub fn to_query_string(&self) -> String {
+        let mut query = form_urlencoded::Serializer::new(String::new());
+        query.append_pair("q", &self.query);
+
+        if let Some(search_type) = self.search_type {
+            query.append_pair("type", search_type.as_str());
+        }
+
+        for &(name, set) in &[("resolve", self.resolve), ("following", self.following)] {
+            if set {
+                query.append_pair(name, "true");
+            }
+        }
+
+        if let Some(ref id) = self.account_id {
+            query.append_pair("account_id", id);
+        }
+
+        for &(name, value) in &[("offset", self.offset), ("limit", self.limit)] {
+            if let Some(value) = value {
+                query.append_pair(name, &value.to_string());
+            }
+        }
+
+        format!("?{}", query.finish())
+    }
+}
+
+/// One-off settings for the requests of a client, see
+/// `Mastodon::with_options`.
+///
+/// ```
+/// # use mammut::requests::RequestOptions;
+/// let options = RequestOptions::new()
+///     .header("X-Request-Id", "1234");
+/// ```
+///
+/// There's no timeout option, as the version of reqwest mammut uses only
+/// sets timeouts for a whole HTTP client: pass a client built with the
+/// timeout to `Mastodon::from_data_with_client` instead. There's no retry
+/// option either, since requests are never retried.
+#[derive(Debug, Clone, Default)]
+pub struct RequestOptions {
+    pub(crate) headers: Headers,
+}
+
+impl RequestOptions {
+    /// Options changing nothing.
+    pub fn new() -> Self {
+        RequestOptions::default()
+    }
+
+    /// Send the header `name` with each request, replacing the value the
+    /// client would otherwise send.
+    pub fn header<V: Into<String>>(mut self, name: &'static str, value: V) -> Self {
+        self.headers.set_raw(name, value.into());
+        self
+    }
+}
@@ -0,0 +1,175 @@
+//! Queues requests by priority and runs them without exceeding the rate
+//! limit of the instance.
+//!
+//! ```no_run
+//! # extern crate mammut;
+//! # use mammut::{Data, Mastodon};
+//! # use mammut::scheduler::{Priority, Scheduler};
+//! # fn main() {
+//! # let mastodon = Mastodon::from_data(Data::from_env().unwrap());
+//! let scheduler = Scheduler::new(mastodon);
+//! let me = scheduler.submit(Priority::High, |mastodon| mastodon.verify_credentials());
+//! let timeline = scheduler.submit(Priority::Low, |mastodon| mastodon.get_home_timeline());
+//!
+//! println!("{} requests queued, done in {:?}", scheduler.len(), scheduler.eta());
+//! scheduler.run();
+//!
+//! println!("{:?}", me.recv().unwrap());
+//! println!("{:?}", timeline.recv().unwrap());
+//! # }
+//! ```
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::sync::Mutex;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+
+use chrono::prelude::*;
+
+use super::{Mastodon, Result};
+
+/// The length of a rate limit period on Mastodon instances.
+const PERIOD: i64 = 5 * 60;
+
+/// The priority of a queued request, higher priorities run first.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    /// Run after all other requests, such as background crawling.
+    Low,
+    /// The default priority.
+    #[default]
+    Normal,
+    /// Run before all other requests, such as user initiated actions.
+    High,
+}
+
+type Task = Box<dyn FnOnce(&Mastodon) + Send>;
+
+struct Job {
+    priority: Priority,
+    // Orders jobs of the same priority by submission.
+    sequence: u64,
+    task: Task,
+}
+
+impl Ord for Job {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.priority.cmp(&other.priority)
+            .then_with(|| other.sequence.cmp(&self.sequence))
+    }
+}
+
+impl PartialOrd for Job {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Job {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Job {}
+
+/// A priority queue of requests which waits for the rate limit to reset
+/// whenever it's exhausted. Each queued closure is assumed to make a single
+/// request.
+pub struct Scheduler {
+    mastodon: Mastodon,
+    queue: Mutex<(BinaryHeap<Job>, u64)>,
+    /// Number of requests of the rate limit to leave unused, for requests
+    /// made outside of the scheduler.
+    pub reserve: u64,
+}
+
+impl Scheduler {
+    /// Creates an empty scheduler making requests with `mastodon`.
+    pub fn new(mastodon: Mastodon) -> Self {
+        Scheduler {
+            mastodon,
+            queue: Mutex::new((BinaryHeap::new(), 0)),
+            reserve: 0,
+        }
+    }
+
+    /// Queues a request, returning a receiver for its result once it has run.
+    pub fn submit<F, T>(&self, priority: Priority, request: F) -> Receiver<Result<T>>
+        where F: FnOnce(&Mastodon) -> Result<T> + Send + 'static,
+              T: Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel();
+        let task: Task = Box::new(move |mastodon: &Mastodon| {
+            // The receiver not waiting for the result isn't an error.
+            let _ = sender.send(request(mastodon));
+        });
+
+        let mut queue = self.queue.lock().unwrap();
+        let sequence = queue.1;
+        queue.1 += 1;
+        queue.0.push(Job { priority, sequence, task });
+
+        receiver
+    }
+
+    /// The number of queued requests.
+    pub fn len(&self) -> usize {
+        self.queue.lock().unwrap().0.len()
+    }
+
+    /// Whether there are no queued requests.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Estimates how long it will take to run the queued requests, based on
+    /// the last known rate limit. Time spent on the requests themselves isn't
+    /// taken into account.
+    pub fn eta(&self) -> Duration {
+        let queued = self.len() as i64;
+        let rate_limit = match self.mastodon.rate_limit() {
+            Some(rate_limit) => rate_limit,
+            None => return Duration::from_secs(0),
+        };
+
+        let remaining = rate_limit.remaining.saturating_sub(self.reserve) as i64;
+        if queued <= remaining {
+            return Duration::from_secs(0);
+        }
+
+        let per_period = rate_limit.limit.saturating_sub(self.reserve).max(1) as i64;
+        let periods = (queued - remaining - 1) / per_period;
+        let until_reset = (rate_limit.reset - Utc::now()).num_seconds().max(0);
+
+        Duration::from_secs((until_reset + periods * PERIOD) as u64)
+    }
+
+    /// Runs the highest priority queued request, waiting for the rate limit
+    /// to reset first if needed. Returns `false` if the queue was empty.
+    pub fn run_next(&self) -> bool {
+        let job = match self.queue.lock().unwrap().0.pop() {
+            Some(job) => job,
+            None => return false,
+        };
+
+        if let Some(rate_limit) = self.mastodon.rate_limit() {
+            if rate_limit.remaining <= self.reserve {
+                if let Ok(wait) = (rate_limit.reset - Utc::now()).to_std() {
+                    thread::sleep(wait);
+                }
+            }
+        }
+
+        (job.task)(&self.mastodon);
+        true
+    }
+
+    /// Runs queued requests until the queue is empty, including requests
+    /// submitted while running.
+    pub fn run(&self) {
+        while self.run_next() {}
+    }
+}
@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use reqwest::Method;
+
+/// Calls made by a client, collected once enabled with
+/// `Mastodon::set_collect_stats`.
+#[derive(Debug, Clone, Default)]
+pub struct Stats {
+    /// The calls of each endpoint, keyed by the method and path of the
+    /// endpoint, with ids replaced by `:id`, e.g. `GET /api/v1/statuses/:id`.
+    pub endpoints: HashMap<String, EndpointStats>,
+}
+
+impl Stats {
+    /// The number of calls to all endpoints.
+    pub fn calls(&self) -> u64 {
+        self.endpoints.values().map(|endpoint| endpoint.calls).sum()
+    }
+
+    /// The number of failed calls to all endpoints.
+    pub fn errors(&self) -> u64 {
+        self.endpoints.values().map(|endpoint| endpoint.errors).sum()
+    }
+
+    /// Records a call of `endpoint` which took `latency`.
+    pub fn record(&mut self, endpoint: &str, latency: Duration, failed: bool) {
+        let stats = self.endpoints.entry(endpoint.to_owned()).or_default();
+
+        stats.calls += 1;
+        stats.total_latency += latency;
+        stats.max_latency = stats.max_latency.max(latency);
+
+        if failed {
+            stats.errors += 1;
+        }
+    }
+}
+
+/// Calls made to one endpoint.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EndpointStats {
+    /// The number of calls.
+    pub calls: u64,
+    /// The number of calls which failed to connect, or returned an error
+    /// status.
+    pub errors: u64,
+    /// The time spent waiting for responses of all calls.
+    pub total_latency: Duration,
+    /// The time spent waiting for the slowest response.
+    pub max_latency: Duration,
+}
+
+impl EndpointStats {
+    /// The average time spent waiting for a response.
+    pub fn mean_latency(&self) -> Duration {
+        if self.calls == 0 {
+            Duration::default()
+        } else {
+            self.total_latency / self.calls as u32
+        }
+    }
+
+    /// The fraction of calls which failed, from 0 to 1.
+    pub fn error_rate(&self) -> f64 {
+        if self.calls == 0 {
+            0.0
+        } else {
+            self.errors as f64 / self.calls as f64
+        }
+    }
+}
+
+/// The name an endpoint is recorded under: the method and the path segments
+/// below the base url, with numeric segments replaced by `:id`.
+///
+/// ```
+/// # extern crate mammut;
+/// # extern crate reqwest;
+/// # fn main() {
+/// # use mammut::stats::endpoint;
+/// let path = ["api", "v1", "accounts", "42", "statuses"];
+/// assert_eq!(endpoint(&reqwest::Method::Get, &path), "GET /api/v1/accounts/:id/statuses");
+/// # }
+/// ```
+pub fn endpoint(method: &Method, path: &[&str]) -> String {
+    let path = path.iter()
+        .map(|segment| if !segment.is_empty() && segment.bytes().all(|b| b.is_ascii_digit()) {
+            ":id"
+        } else {
+            segment
+        })
+        .collect::<Vec<_>>()
+        .join("/");
+
+    format!("{} /{}", method, path)
+}
@@ -1,5 +1,11 @@
+use std::cmp;
+
+use entities::instance::InstanceConfiguration;
+use entities::status::Status;
+use super::{Error, Result};
+
 /// A builder pattern struct for constructing a status.
-#[derive(Debug, Default, Clone, Serialize)]
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
 pub struct StatusBuilder {
     /// The text of the status.
     pub status: String,
@@ -18,10 +24,29 @@ pub struct StatusBuilder {
     /// Visibility of the status, defaults to `Public`.
     #[serde(skip_serializing_if="Option::is_none")]
     pub visibility: Option<Visibility>,
+    /// A poll attached to the status, which can't be combined with media.
+    #[serde(default, skip_serializing_if="Option::is_none")]
+    pub poll: Option<PollBuilder>,
+}
+
+/// A poll to attach to a status.
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct PollBuilder {
+    /// The choices of the poll.
+    pub options: Vec<String>,
+    /// How long the poll is open for, in seconds.
+    pub expires_in: u64,
+    /// Whether more than one choice can be voted for.
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub multiple: Option<bool>,
+    /// Whether to hide the vote counts until the poll ends.
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub hide_totals: Option<bool>,
 }
 
-/// The visibility of a status.
-#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+/// The visibility of a status, ordered from the most to the least
+/// restricted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
 pub enum Visibility {
     /// A Direct message to a user
     #[serde(rename = "direct")]
@@ -33,6 +58,7 @@ pub enum Visibility {
     #[serde(rename = "unlisted")]
     Unlisted,
     /// Posted to public timelines
+    #[default]
     #[serde(rename = "public")]
     Public,
 }
@@ -41,18 +67,156 @@ impl StatusBuilder {
 
     /// Create a new status with text.
     /// ```
+    /// # use mammut::StatusBuilder;
     /// let status = StatusBuilder::new("Hello World!".into());
     /// ```
     pub fn new(status: String) -> Self {
         StatusBuilder {
-            status: status,
+            status,
             ..Self::default()
         }
     }
+
+    /// Turns the status into a reply to `status`: it's posted in the same
+    /// thread, mentioning the author, with the content warning of `status`
+    /// unless it has its own. The visibility is narrowed to that of `status`,
+    /// so replies to private statuses stay private.
+    ///
+    /// ```
+    /// # extern crate mammut;
+    /// # use mammut::StatusBuilder;
+    /// # use mammut::entities::status::Status;
+    /// # fn reply(status: &Status) -> mammut::Result<()> {
+    /// let reply = StatusBuilder::new("Thanks!".into()).reply_to(status)?;
+    /// # Ok(())
+    /// # }
+    /// # fn main() {}
+    /// ```
+    pub fn reply_to(mut self, status: &Status) -> Result<Self> {
+        let mention = format!("@{}", status.account.acct);
+        if !self.status.split_whitespace().any(|word| word == mention) {
+            self.status = format!("{} {}", mention, self.status);
+        }
+
+        self.in_reply_to_id = Some(status.id.parse()?);
+        self.visibility = Some(match self.visibility {
+            Some(visibility) => cmp::min(visibility, status.visibility),
+            None => status.visibility,
+        });
+
+        if self.spoiler_text.is_none() && !status.spoiler_text.is_empty() {
+            self.spoiler_text = Some(status.spoiler_text.clone());
+        }
+
+        Ok(self)
+    }
+
+    /// Checks the status against the limits of the instance, so it isn't
+    /// rejected after uploading it. Links count as
+    /// `characters_reserved_per_url` characters and mentions of remote
+    /// accounts only count the username, as on the instance.
+    ///
+    /// The media types of attachments can't be checked here since the status
+    /// only refers to them by id, see
+    /// `InstanceConfiguration::supports_media_type`.
+    ///
+    /// # Errors
+    /// `Error::InvalidStatus` describing the first limit exceeded.
+    ///
+    /// ```no_run
+    /// # extern crate mammut;
+    /// # use mammut::{Data, Mastodon, StatusBuilder};
+    /// # fn main() {
+    /// #    try().unwrap();
+    /// # }
+    /// # fn try() -> mammut::Result<()> {
+    /// # let mastodon = Mastodon::from_data(Data::from_env()?);
+    /// let configuration = mastodon.instance()?.configuration.unwrap_or_default();
+    /// let status = StatusBuilder::new("Hello World!".into());
+    ///
+    /// status.validate_against(&configuration)?;
+    /// mastodon.new_status(status)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn validate_against(&self, configuration: &InstanceConfiguration) -> Result<()> {
+        let statuses = &configuration.statuses;
+        let mut characters = count_characters(&self.status, statuses.characters_reserved_per_url);
+
+        if let Some(ref spoiler_text) = self.spoiler_text {
+            characters += count_characters(spoiler_text, statuses.characters_reserved_per_url);
+        }
+
+        if characters > statuses.max_characters {
+            return Err(Error::InvalidStatus(format!(
+                "the status has {} characters, the instance allows {}",
+                characters, statuses.max_characters
+            )));
+        }
+
+        let attachments = self.media_ids.as_ref().map(Vec::len).unwrap_or(0);
+
+        if attachments > statuses.max_media_attachments {
+            return Err(Error::InvalidStatus(format!(
+                "the status has {} attachments, the instance allows {}",
+                attachments, statuses.max_media_attachments
+            )));
+        }
+
+        if let Some(ref poll) = self.poll {
+            let polls = &configuration.polls;
+
+            if attachments > 0 {
+                return Err(Error::InvalidStatus("a status can't have both a poll and attachments".into()));
+            }
+
+            if poll.options.len() > polls.max_options {
+                return Err(Error::InvalidStatus(format!(
+                    "the poll has {} options, the instance allows {}",
+                    poll.options.len(), polls.max_options
+                )));
+            }
+
+            if let Some(option) = poll.options.iter()
+                .find(|option| option.chars().count() > polls.max_characters_per_option)
+            {
+                return Err(Error::InvalidStatus(format!(
+                    "the poll option \"{}\" is longer than {} characters",
+                    option, polls.max_characters_per_option
+                )));
+            }
+
+            if poll.expires_in < polls.min_expiration || poll.expires_in > polls.max_expiration {
+                return Err(Error::InvalidStatus(format!(
+                    "polls must be open for {} to {} seconds",
+                    polls.min_expiration, polls.max_expiration
+                )));
+            }
+        }
+
+        Ok(())
+    }
 }
 
-impl Default for Visibility {
-    fn default() -> Self {
-        Visibility::Public
+// Counts characters the way the instance does: links count as a fixed number
+// of characters, and `@user@domain` mentions only as `@user`.
+fn count_characters(text: &str, characters_per_url: usize) -> usize {
+    let mut count = 0;
+    let mut words = text.split(|c: char| c.is_whitespace()).peekable();
+
+    while let Some(word) = words.next() {
+        count += if word.starts_with("https://") || word.starts_with("http://") {
+            characters_per_url
+        } else if word.starts_with('@') {
+            word.split('@').nth(1).unwrap_or("").chars().count() + 1
+        } else {
+            word.chars().count()
+        };
+
+        if words.peek().is_some() {
+            count += 1;
+        }
     }
+
+    count
 }
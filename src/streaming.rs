@@ -0,0 +1,459 @@
+//! Receiving statuses, notifications and deletions as they happen from the
+//! streaming API, instead of polling timelines. Deletions contain the id of
+//! the status as a `u64`, like the ids routes take.
+//!
+//! ```no_run
+//! # extern crate mammut;
+//! # use mammut::{Data, Mastodon};
+//! # use mammut::streaming::Event;
+//! # fn main() {
+//! #    try().unwrap();
+//! # }
+//! # fn try() -> mammut::Result<()> {
+//! # let mastodon = Mastodon::from_data(Data::from_env()?);
+//! for event in mastodon.stream_user()? {
+//!     match event? {
+//!         Event::Update(status) => println!("{}: {}", status.account.acct, status.content),
+//!         Event::Notification(notification) => println!("{:?}", notification.notification_type),
+//!         Event::Delete(id) => println!("{} was deleted", id),
+//!         _ => {},
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Streams end when the connection does, `Mastodon::reconnecting_stream`
+//! reconnects instead, and fetches the statuses missed in between.
+
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader};
+use std::thread;
+use std::time::Duration;
+
+use reqwest::Response;
+use url::form_urlencoded;
+
+use dedup::Seen;
+use entities::notification::{Notification, NotificationType};
+use entities::status::Status;
+#[cfg(feature = "metrics")]
+use instrument;
+use markers::cmp_ids;
+use timeline::Cursor;
+use super::{from_body, Mastodon, Result};
+
+/// An event received from a stream.
+#[derive(Debug, Clone)]
+pub enum Event {
+    /// A new status.
+    Update(Box<Status>),
+    /// A status was edited.
+    StatusUpdate(Box<Status>),
+    /// A new notification, only sent to the user stream.
+    Notification(Box<Notification>),
+    /// The status with this id was deleted. Ids which aren't numeric fail
+    /// with `Error::ParseInt`.
+    Delete(u64),
+    /// The user's filters changed, only sent to the user stream.
+    FiltersChanged,
+    /// A status posted while a `Reconnecting` stream was disconnected,
+    /// fetched from the stream's timeline after reconnecting.
+    Backfilled(Box<Status>),
+}
+
+/// The streams of the streaming API.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Stream {
+    /// The events of the authenticated user, see `Mastodon::stream_user`.
+    User,
+    /// The public statuses of the whole known network.
+    Public,
+    /// The public statuses of the instance's own users.
+    Local,
+    /// The public statuses tagged with the hashtag, without the `#`.
+    Hashtag(String),
+}
+
+impl Stream {
+    // The route of the stream below `/api/v1/streaming/`.
+    pub(crate) fn path(&self) -> String {
+        match *self {
+            Stream::User => "user".to_owned(),
+            Stream::Public => "public".to_owned(),
+            Stream::Local => "public/local".to_owned(),
+            Stream::Hashtag(ref hashtag) => {
+                let query = form_urlencoded::Serializer::new(String::new())
+                    .append_pair("tag", hashtag)
+                    .finish();
+                format!("hashtag?{}", query)
+            },
+        }
+    }
+
+    // The name of the stream in metrics, without the tag of hashtag streams
+    // to bound the number of labels.
+    #[cfg(feature = "metrics")]
+    fn label(&self) -> &'static str {
+        match *self {
+            Stream::User => "user",
+            Stream::Public => "public",
+            Stream::Local => "public/local",
+            Stream::Hashtag(_) => "hashtag",
+        }
+    }
+
+    // The timeline receiving the statuses of the stream.
+    fn timeline(&self) -> Cursor {
+        match *self {
+            Stream::User => Cursor::home(),
+            Stream::Public => Cursor::public(),
+            Stream::Local => Cursor::local(),
+            Stream::Hashtag(ref hashtag) => Cursor::tag(hashtag),
+        }
+    }
+}
+
+/// The events of a stream, read as the instance sends them. Events of types
+/// this version doesn't know are skipped, and the iterator ends when the
+/// instance closes the connection.
+#[derive(Debug)]
+pub struct EventStream<R = BufReader<Response>> {
+    reader: R,
+    lenient: bool,
+}
+
+impl<R: BufRead> EventStream<R> {
+    /// Reads events in the server-sent events format from `reader`.
+    pub fn new(reader: R) -> Self {
+        EventStream {
+            reader,
+            lenient: false,
+        }
+    }
+
+    // Reads the next event and its data, `None` at the end of the stream.
+    fn read_event(&mut self) -> Result<Option<(String, String)>> {
+        let mut event = String::new();
+        let mut data = String::new();
+        let mut line = String::new();
+
+        loop {
+            line.clear();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Ok(None);
+            }
+
+            let line = line.trim_end_matches(['\r', '\n']);
+            if line.is_empty() {
+                if !event.is_empty() || !data.is_empty() {
+                    return Ok(Some((event, data)));
+                }
+                continue;
+            }
+
+            let (field, value) = match line.find(':') {
+                // Comments, such as the heartbeats keeping the connection
+                // open.
+                Some(0) => continue,
+                Some(i) => (&line[..i], line[i + 1..].strip_prefix(' ').unwrap_or(&line[i + 1..])),
+                None => (line, ""),
+            };
+
+            match field {
+                "event" => event = value.to_owned(),
+                "data" => {
+                    if !data.is_empty() {
+                        data.push('\n');
+                    }
+                    data.push_str(value);
+                },
+                _ => {},
+            }
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for EventStream<R> {
+    type Item = Result<Event>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (event, data) = match self.read_event() {
+                Ok(Some(event)) => event,
+                Ok(None) => return None,
+                Err(e) => return Some(Err(e)),
+            };
+
+            let event = match &*event {
+                "update" => from_body(data.as_bytes(), self.lenient).map(Event::Update),
+                "status.update" => from_body(data.as_bytes(), self.lenient).map(Event::StatusUpdate),
+                "notification" => from_body(data.as_bytes(), self.lenient).map(Event::Notification),
+                "delete" => data.trim().parse().map(Event::Delete).map_err(Into::into),
+                "filters_changed" => Ok(Event::FiltersChanged),
+                _ => {
+                    debug!("Skipping streaming event {}", event);
+                    continue;
+                },
+            };
+
+            return Some(event);
+        }
+    }
+}
+
+/// The statuses mentioning the authenticated user, read from the user
+/// stream, returned by `Mastodon::stream_mentions`.
+#[derive(Debug)]
+pub struct MentionStream<R = BufReader<Response>> {
+    events: EventStream<R>,
+}
+
+impl<R: BufRead> MentionStream<R> {
+    /// Yields the statuses of the mention notifications of `events`.
+    pub fn new(events: EventStream<R>) -> Self {
+        MentionStream { events }
+    }
+}
+
+impl<R: BufRead> Iterator for MentionStream<R> {
+    type Item = Result<Status>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for event in &mut self.events {
+            match event {
+                Ok(Event::Notification(notification)) => {
+                    if notification.notification_type != NotificationType::Mention {
+                        continue;
+                    }
+                    if let Some(status) = notification.status {
+                        return Some(Ok(status));
+                    }
+                },
+                Ok(_) => {},
+                Err(e) => return Some(Err(e)),
+            }
+        }
+
+        None
+    }
+}
+
+/// The events of a stream which reconnects whenever the connection ends or
+/// fails, returned by `Mastodon::reconnecting_stream`.
+///
+/// After each connect, the statuses newer than the newest one received are
+/// fetched from the stream's timeline and yielded oldest first as
+/// `Event::Backfilled`, so statuses posted while disconnected aren't missed.
+/// Statuses both streamed and backfilled are yielded once. The stream is
+/// reconnected `reconnect_delay` after it ends, and failures to connect or
+/// backfill are yielded and retried after it, so the iterator never ends.
+pub struct Reconnecting<'a> {
+    mastodon: &'a Mastodon,
+    stream: Stream,
+    cursor: Cursor,
+    events: Option<EventStream>,
+    backfilled: VecDeque<Status>,
+    seen: Seen<String>,
+    reconnect: bool,
+    /// The time to wait before reconnecting after the stream ended, or it or
+    /// a backfill failed, five seconds by default.
+    pub reconnect_delay: Duration,
+}
+
+impl<'a> Reconnecting<'a> {
+    /// The id of the newest status received, to save and pass as the
+    /// `since_id` of `Mastodon::reconnecting_stream` when streaming again
+    /// later.
+    pub fn position(&self) -> Option<&str> {
+        self.cursor.position.as_deref()
+    }
+
+    // Connects the stream, then fetches the statuses missed since the
+    // position, so none are posted in between.
+    fn connect(&mut self) -> Result<()> {
+        let events = self.mastodon.stream(&self.stream.path())?;
+
+        if self.cursor.position.is_some() {
+            let statuses = self.mastodon.sync(&mut self.cursor)?;
+            self.backfilled.extend(statuses.into_iter().rev());
+        }
+
+        self.events = Some(events);
+        Ok(())
+    }
+
+    // Moves the position to `status` if it's newer, returning whether it
+    // wasn't received before.
+    fn receive(&mut self, status: &Status) -> bool {
+        if !self.seen.insert(status.id.clone()) {
+            return false;
+        }
+
+        let newer = self.cursor.position.as_ref()
+            .is_none_or(|position| cmp_ids(&status.id, position) == Ordering::Greater);
+        if newer {
+            self.cursor.position = Some(status.id.clone());
+        }
+
+        true
+    }
+}
+
+impl<'a> Iterator for Reconnecting<'a> {
+    type Item = Result<Event>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(status) = self.backfilled.pop_front() {
+                if self.receive(&status) {
+                    return Some(Ok(Event::Backfilled(Box::new(status))));
+                }
+                continue;
+            }
+
+            let event = match self.events {
+                Some(ref mut events) => events.next(),
+                None => {
+                    if self.reconnect {
+                        thread::sleep(self.reconnect_delay);
+
+                        #[cfg(feature = "metrics")]
+                        instrument::record_reconnect(self.stream.label());
+                    }
+                    self.reconnect = true;
+
+                    if let Err(e) = self.connect() {
+                        return Some(Err(e));
+                    }
+                    continue;
+                },
+            };
+
+            match event {
+                Some(Ok(Event::Update(status))) => {
+                    if self.receive(&status) {
+                        return Some(Ok(Event::Update(status)));
+                    }
+                },
+                Some(Ok(event)) => return Some(Ok(event)),
+                Some(Err(e)) => {
+                    self.events = None;
+                    return Some(Err(e));
+                },
+                None => {
+                    debug!("Stream {} ended, reconnecting", self.stream.path());
+                    self.events = None;
+                },
+            }
+        }
+    }
+}
+
+impl Mastodon {
+    /// Streams the events of the authenticated user: statuses on the home
+    /// timeline, notifications and filter changes.
+    ///
+    /// The stream ends when the instance closes the connection, or errors if
+    /// it sends nothing for the client's timeout, which reqwest defaults to 30
+    /// seconds; instances send a heartbeat every few seconds.
+    pub fn stream_user(&self) -> Result<EventStream> {
+        self.stream(&Stream::User.path())
+    }
+
+    /// Streams the statuses mentioning the authenticated user as they're
+    /// received, see `stream_user`. Unlike `poll_mentions`, this doesn't use
+    /// up the rate limit, but mentions received while disconnected are
+    /// missed.
+    ///
+    /// ```no_run
+    /// # extern crate mammut;
+    /// # use mammut::{Data, Mastodon};
+    /// # fn main() {
+    /// #    try().unwrap();
+    /// # }
+    /// # fn try() -> mammut::Result<()> {
+    /// # let mastodon = Mastodon::from_data(Data::from_env()?);
+    /// for status in mastodon.stream_mentions()? {
+    ///     let status = status?;
+    ///     println!("{}: {}", status.account.acct, status.content);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn stream_mentions(&self) -> Result<MentionStream> {
+        self.stream_user().map(MentionStream::new)
+    }
+
+    /// Streams the public statuses of the whole known network.
+    pub fn stream_public(&self) -> Result<EventStream> {
+        self.stream(&Stream::Public.path())
+    }
+
+    /// Streams the public statuses of the instance's own users.
+    pub fn stream_local(&self) -> Result<EventStream> {
+        self.stream(&Stream::Local.path())
+    }
+
+    /// Streams the public statuses tagged with `hashtag`, without the `#`.
+    pub fn stream_hashtag(&self, hashtag: &str) -> Result<EventStream> {
+        self.stream(&Stream::Hashtag(hashtag.to_owned()).path())
+    }
+
+    /// Streams the events of `stream`, reconnecting whenever the connection
+    /// ends, see `Reconnecting`. The statuses newer than `since_id`, saved
+    /// from `Reconnecting::position` when streaming before, are backfilled
+    /// after connecting; with `None` only the statuses missed while
+    /// reconnecting are. The stream connects on the first call to `next`.
+    ///
+    /// The last 1000 statuses are remembered to yield each once.
+    ///
+    /// ```no_run
+    /// # extern crate mammut;
+    /// # use mammut::{Data, Mastodon};
+    /// # use mammut::streaming::{Event, Stream};
+    /// # fn main() {
+    /// #    try().unwrap();
+    /// # }
+    /// # fn try() -> mammut::Result<()> {
+    /// # let mastodon = Mastodon::from_data(Data::from_env()?);
+    /// for event in mastodon.reconnecting_stream(Stream::User, Some("1234".into())) {
+    ///     match event {
+    ///         Ok(Event::Update(status)) | Ok(Event::Backfilled(status)) => println!("{}", status.content),
+    ///         Ok(_) => {},
+    ///         Err(e) => println!("Reconnecting: {}", e),
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn reconnecting_stream(&self, stream: Stream, since_id: Option<String>) -> Reconnecting<'_> {
+        let cursor = Cursor { position: since_id, ..stream.timeline() };
+
+        Reconnecting {
+            mastodon: self,
+            stream,
+            cursor,
+            events: None,
+            backfilled: VecDeque::new(),
+            seen: Seen::new(1000),
+            reconnect: false,
+            reconnect_delay: Duration::from_secs(5),
+        }
+    }
+
+    fn stream(&self, stream: &str) -> Result<EventStream> {
+        let url = self.route(&format!("/api/v1/streaming/{}", stream));
+        let response = self.execute(&mut self.client.get(&url))?;
+
+        if !response.status().is_success() {
+            return Err(self.error_from(response));
+        }
+
+        Ok(EventStream {
+            reader: BufReader::new(response),
+            lenient: self.lenient,
+        })
+    }
+}
@@ -0,0 +1,202 @@
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::ErrorKind;
+use std::mem;
+use std::path::Path;
+
+use json;
+
+use atomic_file;
+use entities::status::Status;
+use page::Page;
+use super::{Mastodon, Result};
+
+/// Statuses newer than a given id, returned by `Mastodon::sync_home`.
+#[derive(Debug, Clone)]
+pub struct TimelineSync {
+    /// The new statuses, newest first.
+    pub statuses: Vec<Status>,
+    /// The id of the newest status seen, to pass to the next sync. The
+    /// `since_id` that was synced from if there were no new statuses.
+    pub high_water_mark: String,
+}
+
+/// The position of an application in a timeline, for resuming where it left
+/// off after a restart.
+///
+/// ```no_run
+/// # extern crate mammut;
+/// # use mammut::{Data, Mastodon};
+/// # use mammut::timeline::Cursor;
+/// # fn main() {
+/// #    try().unwrap();
+/// # }
+/// # fn try() -> mammut::Result<()> {
+/// # let mastodon = Mastodon::from_data(Data::from_env()?);
+/// let mut cursor = Cursor::load("home.json")?.unwrap_or_else(Cursor::home);
+///
+/// for status in mastodon.sync(&mut cursor)?.iter().rev() {
+///     println!("{}", status.content);
+/// }
+///
+/// cursor.save("home.json")?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Cursor {
+    /// The route of the timeline below `/api/v1/`, e.g. `timelines/home`.
+    pub timeline: String,
+    /// The id of the newest status seen, `None` if the timeline hasn't been
+    /// synced yet.
+    pub position: Option<String>,
+}
+
+impl Cursor {
+    /// A cursor at the start of the timeline at `timeline`, a route below
+    /// `/api/v1/`.
+    pub fn new<I: Into<String>>(timeline: I) -> Self {
+        Cursor {
+            timeline: timeline.into(),
+            position: None,
+        }
+    }
+
+    /// A cursor for the home timeline.
+    pub fn home() -> Self {
+        Cursor::new("timelines/home")
+    }
+
+    /// A cursor for the federated timeline.
+    pub fn public() -> Self {
+        Cursor::new("timelines/public")
+    }
+
+    /// A cursor for the public statuses of the instance's own users.
+    pub fn local() -> Self {
+        Cursor::new("timelines/public?local=true")
+    }
+
+    /// A cursor for the timeline of a hashtag.
+    pub fn tag(hashtag: &str) -> Self {
+        Cursor::new(format!("timelines/tag/{}", hashtag))
+    }
+
+    /// A cursor for the timeline of a list.
+    pub fn list(id: u64) -> Self {
+        Cursor::new(format!("timelines/list/{}", id))
+    }
+
+    /// Loads a cursor saved at `path`, returning `None` if there is no such
+    /// file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Option<Self>> {
+        match File::open(path) {
+            Ok(file) => Ok(Some(json::from_reader(file)?)),
+            Err(ref e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Saves the cursor to `path`, replacing the file atomically so a crash
+    /// can't leave a partially written cursor behind.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        atomic_file::save_json(path.as_ref(), self)
+    }
+}
+
+impl Mastodon {
+    /// Fetch every status in the home timeline newer than `since_id`,
+    /// following as many pages as needed.
+    ///
+    /// ```no_run
+    /// # extern crate mammut;
+    /// # use mammut::{Data, Mastodon};
+    /// # fn main() {
+    /// #    try().unwrap();
+    /// # }
+    /// # fn try() -> mammut::Result<()> {
+    /// # let mastodon = Mastodon::from_data(Data::from_env()?);
+    /// # let last_run = String::from("1");
+    /// let sync = mastodon.sync_home(&last_run)?;
+    /// for status in sync.statuses.iter().rev() {
+    ///     println!("{}", status.content);
+    /// }
+    /// // Store `sync.high_water_mark` for the next run.
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn sync_home(&self, since_id: &str) -> Result<TimelineSync> {
+        let statuses = self.statuses_since("timelines/home", Some(since_id))?;
+        let high_water_mark = statuses.first()
+            .map(|status| status.id.clone())
+            .unwrap_or_else(|| since_id.to_owned());
+
+        Ok(TimelineSync { statuses, high_water_mark })
+    }
+
+    /// Fetch every status in the cursor's timeline newer than its position,
+    /// and move the cursor to the newest one. Only the most recent page is
+    /// fetched for a cursor without a position.
+    pub fn sync(&self, cursor: &mut Cursor) -> Result<Vec<Status>> {
+        let statuses = self.statuses_since(&cursor.timeline, cursor.position.as_deref())?;
+
+        if let Some(status) = statuses.first() {
+            cursor.position = Some(status.id.clone());
+        }
+
+        Ok(statuses)
+    }
+
+    fn statuses_since(&self, timeline: &str, since_id: Option<&str>) -> Result<Vec<Status>> {
+        let separator = if timeline.contains('?') { '&' } else { '?' };
+        let mut url = self.route(&format!("/api/v1/{}{}limit=40", timeline, separator));
+
+        if let Some(id) = since_id {
+            url += "&min_id=";
+            url += id;
+        }
+
+        let mut page = Page::new(self, self.execute(&mut self.client.get(&url))?)?;
+        let mut statuses: Vec<Status> = mem::take(&mut page.initial_items);
+
+        // With `min_id` the instance returns the page right after it, so the
+        // newer statuses are on the previous pages.
+        while since_id.is_some() {
+            match page.prev_page()? {
+                Some(ref newer) if newer.is_empty() => break,
+                Some(mut newer) => {
+                    newer.extend(statuses);
+                    statuses = newer;
+                },
+                None => break,
+            }
+        }
+
+        Ok(statuses)
+    }
+}
+
+/// Merges pages of statuses from several sources into one timeline, removing
+/// duplicates and sorting it newest first.
+pub fn merge<I>(pages: I) -> Vec<Status>
+    where I: IntoIterator<Item = Vec<Status>>
+{
+    let mut statuses: Vec<Status> = pages.into_iter().flatten().collect();
+
+    dedup_by_uri(&mut statuses);
+    sort_by_created_at(&mut statuses);
+    statuses
+}
+
+/// Removes all but the first occurrence of each status. Statuses are
+/// compared by URI, since the same remote status has a different id on each
+/// instance.
+pub fn dedup_by_uri(statuses: &mut Vec<Status>) {
+    let mut seen = HashSet::new();
+    statuses.retain(|status| seen.insert(status.uri.clone()));
+}
+
+/// Sorts statuses by the time they were created, newest first.
+pub fn sort_by_created_at(statuses: &mut [Status]) {
+    statuses.sort_by(|a, b| b.cmp(a));
+}
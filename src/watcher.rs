@@ -0,0 +1,124 @@
+//! Watching hashtags for new statuses.
+//!
+//! ```no_run
+//! # extern crate mammut;
+//! # use mammut::{Data, Mastodon};
+//! # use mammut::watcher::HashtagWatcher;
+//! # use std::time::Duration;
+//! # fn main() {
+//! #    try().unwrap();
+//! # }
+//! # fn try() -> mammut::Result<()> {
+//! # let mastodon = Mastodon::from_data(Data::from_env()?);
+//! let mut watcher = HashtagWatcher::open("hashtags.json", &["rust", "rustlang"])?;
+//!
+//! watcher.run(&mastodon, Duration::from_secs(60), |status| {
+//!     println!("{}", status.uri);
+//! });
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fs::File;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+use json;
+
+use atomic_file;
+use dedup::Seen;
+use entities::status::Status;
+use timeline::{self, Cursor};
+use super::{Mastodon, Result};
+
+/// Polls the timelines of several hashtags, emitting each new status once and
+/// saving its position in every timeline to a file.
+///
+/// The watcher only polls, so new statuses arrive up to a polling interval
+/// late and every poll uses up a request per hashtag of the rate limit. To
+/// receive the statuses of a hashtag as they're posted, stream it with
+/// `Mastodon::reconnecting_stream` and `Stream::Hashtag` instead, which
+/// fetches the statuses missed while disconnected the same way.
+#[derive(Debug)]
+pub struct HashtagWatcher {
+    path: PathBuf,
+    cursors: Vec<Cursor>,
+    seen: Seen<String>,
+}
+
+impl HashtagWatcher {
+    /// Opens the watcher saved at `path`, watching `hashtags` (without the
+    /// leading `#`). Hashtags which weren't watched before start at their
+    /// most recent statuses.
+    pub fn open<P: AsRef<Path>>(path: P, hashtags: &[&str]) -> Result<Self> {
+        let path = path.as_ref().to_owned();
+        let saved: Vec<Cursor> = match File::open(&path) {
+            Ok(file) => json::from_reader(file)?,
+            Err(ref e) if e.kind() == ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+
+        let cursors = hashtags.iter()
+            .map(|hashtag| {
+                let cursor = Cursor::tag(&hashtag.trim_start_matches('#').to_lowercase());
+                saved.iter()
+                    .find(|saved| saved.timeline == cursor.timeline)
+                    .cloned()
+                    .unwrap_or(cursor)
+            })
+            .collect();
+
+        Ok(HashtagWatcher {
+            path,
+            cursors,
+            seen: Seen::new(1000),
+        })
+    }
+
+    /// The positions in the timeline of each hashtag.
+    pub fn cursors(&self) -> &[Cursor] {
+        &self.cursors
+    }
+
+    /// Fetches the statuses posted since the last poll, oldest first. A
+    /// status with several of the hashtags is only returned once.
+    ///
+    /// The position of each hashtag is saved once all of them were fetched,
+    /// so if any fails, the statuses are fetched again on the next poll.
+    pub fn poll(&mut self, mastodon: &Mastodon) -> Result<Vec<Status>> {
+        let mut cursors = self.cursors.clone();
+        let mut pages = Vec::with_capacity(cursors.len());
+
+        for cursor in &mut cursors {
+            pages.push(mastodon.sync(cursor)?);
+        }
+
+        let mut statuses = timeline::merge(pages);
+        statuses.retain(|status| self.seen.insert(status.uri.clone()));
+        statuses.reverse();
+
+        self.cursors = cursors;
+        self.save()?;
+
+        Ok(statuses)
+    }
+
+    /// Polls every `interval` forever, calling `new_status` for each new
+    /// status and logging errors.
+    pub fn run<F: FnMut(Status)>(&mut self, mastodon: &Mastodon, interval: Duration, mut new_status: F) {
+        loop {
+            match self.poll(mastodon) {
+                Ok(statuses) => statuses.into_iter().for_each(&mut new_status),
+                Err(e) => warn!("Failed to fetch hashtag timelines: {}", e),
+            }
+
+            thread::sleep(interval);
+        }
+    }
+
+    fn save(&self) -> Result<()> {
+        atomic_file::save_json(&self.path, &self.cursors)
+    }
+}
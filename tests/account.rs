@@ -0,0 +1,45 @@
+extern crate mammut;
+#[macro_use] extern crate serde_json;
+
+mod common;
+
+use mammut::entities::account::Account;
+use mammut::entities::identity_proof::IdentityProof;
+use mammut::status_builder::Visibility;
+
+#[test]
+fn verify_credentials_source() {
+    let mut account = common::account("1");
+    account["note"] = json!("<p>Hi <a href=\"https://example.com\">example.com</a></p>");
+    account["source"] = json!({
+        "privacy": "unlisted",
+        "sensitive": true,
+        "language": "de",
+        "note": "Hi example.com",
+        "fields": [{ "name": "Web", "value": "example.com", "verified_at": null }],
+        "follow_requests_count": 3
+    });
+    let account: Account = serde_json::from_value(account).unwrap();
+
+    let source = account.source.unwrap();
+    assert_eq!(source.privacy, Visibility::Unlisted);
+    assert!(source.sensitive);
+    assert_eq!(source.language, Some("de".to_owned()));
+    assert_eq!(source.note, "Hi example.com");
+    assert_eq!(source.fields[0].value, "example.com");
+    assert_eq!(source.follow_requests_count, 3);
+}
+
+#[test]
+fn identity_proof() {
+    let proof: IdentityProof = serde_json::from_value(json!({
+        "provider": "Keybase",
+        "provider_username": "gargron",
+        "updated_at": "2019-07-21T20:14:39.596Z",
+        "proof_url": "https://keybase.io/gargron/sigs/5cxRhpkh",
+        "profile_url": "https://keybase.io/gargron",
+    })).unwrap();
+
+    assert_eq!(proof.username, "gargron");
+    assert_eq!(proof.verified_at.timestamp(), 1563740079);
+}
@@ -0,0 +1,33 @@
+extern crate mammut;
+#[macro_use] extern crate serde_json;
+
+mod common;
+
+use mammut::alert::Rule;
+use mammut::entities::status::Status;
+
+fn status(content: &str, spoiler_text: &str, language: &str) -> Status {
+    let mut status = common::status("1");
+    status["content"] = json!(content);
+    status["spoiler_text"] = json!(spoiler_text);
+    status["language"] = json!(language);
+
+    serde_json::from_value(status).unwrap()
+}
+
+#[test]
+fn keyword_rules() {
+    let rule = Rule::keyword("mammut", "Mammut");
+
+    assert!(rule.is_match(&status("<p>Trying <b>mammut</b> today</p>", "", "en")));
+    assert!(!rule.is_match(&status("<p>mammuthus</p>", "", "en")));
+    assert!(rule.is_match(&status("<p>...</p>", "mammut rant", "en")));
+    assert!(!rule.clone().match_spoiler_text(false).is_match(&status("<p>...</p>", "mammut rant", "en")));
+    assert!(!rule.clone().match_hidden(false).is_match(&status("<p>mammut</p>", "rant", "en")));
+    assert!(!rule.languages(&["de"]).is_match(&status("<p>mammut</p>", "", "en")));
+}
+
+#[test]
+fn invalid_regex() {
+    assert!(Rule::regex("broken", "(").is_err());
+}
@@ -0,0 +1,20 @@
+extern crate mammut;
+
+use mammut::{ApiError, ApiErrorKind};
+
+fn error(error: &str) -> ApiError {
+    ApiError {
+        error: error.to_owned(),
+        error_description: None,
+        status: None,
+    }
+}
+
+#[test]
+fn kinds() {
+    assert_eq!(error("The access token is invalid").kind(), ApiErrorKind::InvalidToken);
+    assert_eq!(error("Record not found").kind(), ApiErrorKind::RecordNotFound);
+    assert_eq!(error("This action is not allowed").kind(), ApiErrorKind::NotAllowed);
+    assert_eq!(error("Validation failed: Text can't be blank").kind(), ApiErrorKind::ValidationFailed);
+    assert_eq!(error("Something else").kind(), ApiErrorKind::Unknown);
+}
@@ -0,0 +1,131 @@
+extern crate mammut;
+#[macro_use] extern crate serde_json;
+
+mod common;
+
+use mammut::{Error, Registration};
+use mammut::apps::{AppBuilder, Scope, Scopes, REDIRECT_OOB};
+use mammut::entities::status::Application;
+
+#[test]
+fn validate() {
+    let app = AppBuilder {
+        client_name: "mammut_test",
+        redirect_uris: REDIRECT_OOB,
+        ..AppBuilder::default()
+    };
+    assert!(app.validate().is_ok());
+
+    let unnamed = AppBuilder { client_name: " ", ..AppBuilder::default() };
+    match unnamed.validate() {
+        Err(Error::ClientNameRequired) => {},
+        other => panic!("expected ClientNameRequired, got {:?}", other),
+    }
+
+    let bad_redirect = AppBuilder { redirect_uris: "localhost/callback", ..app };
+    match bad_redirect.validate() {
+        Err(Error::Url(_)) => {},
+        other => panic!("expected Url, got {:?}", other),
+    }
+}
+
+#[test]
+fn multiple_redirect_uris() {
+    let app = AppBuilder {
+        client_name: "mammut_test",
+        redirect_uris: "urn:ietf:wg:oauth:2.0:oob\nhttps://example.com/callback",
+        ..AppBuilder::default()
+    };
+    assert!(app.validate().is_ok());
+}
+
+#[test]
+fn set_redirect() {
+    let mut registration = Registration::from_app_credentials(
+        "https://example.com", "id", "secret", REDIRECT_OOB);
+    assert!(registration.set_redirect(REDIRECT_OOB).is_ok());
+
+    match registration.set_redirect("https://example.com/callback") {
+        Err(Error::RedirectNotRegistered(ref uri)) if uri == "https://example.com/callback" => {},
+        other => panic!("expected RedirectNotRegistered, got {:?}", other),
+    }
+}
+
+#[test]
+fn registered_redirect_is_the_fallback() {
+    let app = json!({
+        "client_id": "id",
+        "client_secret": "secret",
+        "redirect_uri": "",
+        "name": "mammut_test",
+        "website": null,
+    });
+    let (base, _) = common::serve(vec![(200, app.clone()), (200, app)]);
+
+    let mut registration = Registration::new(base.clone());
+    registration.register(AppBuilder {
+        client_name: "mammut_test",
+        redirect_uris: REDIRECT_OOB,
+        ..AppBuilder::default()
+    }).unwrap();
+    assert_eq!(registration.redirect_uris(), [REDIRECT_OOB]);
+    assert!(registration.authorise().unwrap().contains("&redirect_uri=urn%3Aietf%3Awg%3Aoauth%3A2.0%3Aoob&"));
+
+    let mut registration = Registration::new(base);
+    registration.register(AppBuilder { client_name: "mammut_test", ..AppBuilder::default() }).unwrap();
+    match registration.authorise() {
+        Err(Error::RedirectRequired) => {},
+        other => panic!("expected RedirectRequired, got {:?}", other),
+    }
+    match registration.create_access_token("code".into()) {
+        Err(Error::RedirectRequired) => {},
+        Err(e) => panic!("expected RedirectRequired, got {:?}", e),
+        Ok(_) => panic!("expected RedirectRequired"),
+    }
+}
+
+#[test]
+fn application() {
+    let app: Application = serde_json::from_str(r#"{
+        "name": "mammut_test",
+        "website": null,
+        "vapid_key": "BCk-QqERU0q-CfYZjcuB6lnyyOYfJ2AifKqfeGIm7Z-HiTU5T9eTG5GxVA0_OH5mMlI4UkkDTpaZwozy0TzdZ2M="
+    }"#).unwrap();
+
+    assert_eq!(app.name, "mammut_test");
+    assert_eq!(app.website, None);
+}
+
+#[test]
+fn granted_scopes() {
+    assert_eq!(Scopes::from_granted("read write follow push"), Some(Scopes::All | Scope::Push));
+    assert_eq!(Scopes::from_granted("write read"), Some(Scopes::ReadWrite));
+    assert_eq!(Scopes::from_granted("push read:statuses"), Some(Scope::Push | Scope::ReadStatuses));
+    assert_eq!(Scopes::from_granted("read:unknown"), None);
+
+    assert!(Scopes::ReadFollow.includes(Scopes::Follow));
+    assert_eq!(Scopes::ReadWrite.intersection(Scopes::WriteFollow), Some(Scopes::Write));
+    assert_eq!(Scopes::Read.intersection(Scopes::Write), None);
+    assert_eq!(Scopes::Read.intersection(Scope::ReadStatuses | Scope::WriteMedia), Some(Scope::ReadStatuses.into()));
+    assert!(Scopes::Follow.grants(Scope::WriteBlocks));
+    assert!(Scopes::from(Scope::AdminRead).grants(Scope::AdminReadReports));
+    assert!(!Scopes::All.grants(Scope::AdminReadReports));
+}
+
+#[test]
+fn granular_scopes() {
+    for &scope in Scope::ALL {
+        assert_eq!(Scope::parse(scope.as_str()), Some(scope));
+    }
+    assert_eq!(Scope::AdminReadReports.parent(), Some(Scope::AdminRead));
+    assert_eq!(Scope::Push.parent(), None);
+
+    let app = AppBuilder {
+        client_name: "mammut_test",
+        redirect_uris: REDIRECT_OOB,
+        scopes: Scopes::Read | Scope::WriteMedia | Scope::Push,
+        website: None,
+    };
+    assert_eq!(serde_json::to_value(&app).unwrap()["scopes"], "read push write:media");
+    assert_eq!(app.scopes.to_string(), "read%20push%20write:media");
+}
@@ -0,0 +1,63 @@
+extern crate mammut;
+#[macro_use] extern crate serde_json;
+
+mod common;
+
+use mammut::Error;
+use mammut::archive::{Archive, Collection};
+
+#[test]
+fn open_creates_the_layout() {
+    let path = std::env::temp_dir().join(format!("mammut-archive-{}", std::process::id()));
+
+    let archive = Archive::open(&path).unwrap();
+    for collection in &Collection::ALL {
+        assert!(path.join(collection.name()).is_dir());
+        assert!(!archive.progress().is_done(*collection));
+    }
+    assert!(path.join("media").is_dir());
+
+    std::fs::write(path.join("progress.json"), r#"{"done":["statuses"]}"#).unwrap();
+    let reopened = Archive::open(&path).unwrap();
+    assert!(reopened.progress().is_done(Collection::Statuses));
+    assert!(!reopened.progress().is_done(Collection::Bookmarks));
+
+    std::fs::remove_dir_all(&path).unwrap();
+}
+
+#[test]
+fn rejects_ids_which_arent_file_names() {
+    let path = std::env::temp_dir().join(format!("mammut-archive-ids-{}", std::process::id()));
+    let escaping = common::status("../../escaped");
+    let mut attachment = common::status("2");
+    attachment["media_attachments"] = json!([{
+        "id": "../../escaped",
+        "type": "image",
+        "url": "https://example.com/escaped.png",
+        "remote_url": null,
+        "preview_url": "https://example.com/escaped_small.png",
+        "text_url": null,
+        "meta": {},
+        "description": null
+    }]);
+
+    let (base, _) = common::serve(vec![
+        (200, common::account("1")),
+        (200, json!([escaping])),
+        (200, json!([attachment])),
+    ]);
+    let mastodon = common::mastodon_at(&base);
+
+    match Archive::open(path.join("a")).unwrap().run(&mastodon) {
+        Err(Error::Io(_)) => {},
+        other => panic!("expected the status to be rejected, got {:?}", other),
+    }
+    match Archive::open(path.join("b")).unwrap().run(&mastodon) {
+        Err(Error::Io(_)) => {},
+        other => panic!("expected the attachment to be rejected, got {:?}", other),
+    }
+
+    // Nothing was written next to the archives.
+    assert_eq!(std::fs::read_dir(&path).unwrap().count(), 2);
+    std::fs::remove_dir_all(&path).unwrap();
+}
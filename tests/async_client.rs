@@ -0,0 +1,137 @@
+#![cfg(feature = "async")]
+
+extern crate futures;
+extern crate mammut;
+#[macro_use] extern crate serde_json;
+extern crate tokio_core;
+
+mod common;
+
+use futures::Stream;
+use mammut::Data;
+use mammut::entities::attachment::Attachment;
+use mammut::async_client::Mastodon;
+use mammut::streaming::{self, Event};
+use tokio_core::reactor::Core;
+
+fn mastodon(base: &str, core: &Core) -> Mastodon {
+    Mastodon::from_data(Data {
+        base: base.to_owned().into(),
+        client_id: "id".into(),
+        client_secret: "secret".into(),
+        redirect: "urn:ietf:wg:oauth:2.0:oob".into(),
+        token: "token".into(),
+    }, &core.handle())
+}
+
+#[test]
+fn pages_stream_the_items_of_every_page() {
+    let next = "Link: <{base}/api/v1/accounts/1/followers?max_id=2>; rel=\"next\"\r\n".to_owned();
+    let (base, requests) = common::serve_with_headers(vec![
+        (200, next, json!([common::account("3"), common::account("2")]).to_string()),
+        (200, String::new(), json!([common::account("1")]).to_string()),
+    ]);
+    let mut core = Core::new().unwrap();
+    let mastodon = mastodon(&base, &core);
+
+    let ids = core.run(mastodon.followers(1).map(|account| account.id).collect()).unwrap();
+
+    assert_eq!(ids, ["3", "2", "1"]);
+    let requests: Vec<String> = requests.iter().collect();
+    assert_eq!(requests, [
+        "GET /api/v1/accounts/1/followers",
+        "GET /api/v1/accounts/1/followers?max_id=2",
+    ]);
+}
+
+#[test]
+fn pages_end_with_an_error() {
+    let (base, _) = common::serve(vec![(500, json!({"error": "down"}))]);
+    let mut core = Core::new().unwrap();
+    let mastodon = mastodon(&base, &core);
+
+    match core.run(mastodon.bookmarks().collect()) {
+        Err(mammut::Error::Api(ref e)) => assert_eq!(e.error, "down"),
+        other => panic!("expected an API error, got {:?}", other.map(|statuses| statuses.len())),
+    }
+}
+
+#[test]
+fn events_stream_the_events_of_a_stream() {
+    let body = format!("event: update\ndata: {}\n\n:thump\r\n\r\nevent: delete\ndata: 2\n\n", common::status("2"));
+    let (base, requests) = common::serve_text(vec![(200, body)]);
+    let mut core = Core::new().unwrap();
+    let mastodon = mastodon(&base, &core);
+
+    let events = core.run(mastodon.stream(&streaming::Stream::Public).collect()).unwrap();
+
+    assert_eq!(events.len(), 2);
+    match events[0] {
+        Event::Update(ref status) => assert_eq!(status.id, "2"),
+        ref event => panic!("expected an update, got {:?}", event),
+    }
+    match events[1] {
+        Event::Delete(id) => assert_eq!(id, 2),
+        ref event => panic!("expected a deletion, got {:?}", event),
+    }
+    assert_eq!(requests.recv().unwrap(), "GET /api/v1/streaming/public");
+}
+
+#[test]
+fn download_attachment() {
+    let (base, requests) = common::serve_text(vec![
+        (200, "0123456789".to_owned()),
+        (200, "0123456789".to_owned()),
+    ]);
+    let attachment: Attachment = serde_json::from_value(json!({
+        "id": "1",
+        "type": "image",
+        "url": format!("{}/media/1.png", base),
+        "remote_url": null,
+        "preview_url": format!("{}/media/1-small.png", base),
+        "text_url": null,
+        "meta": null,
+        "description": null,
+    })).unwrap();
+    let mut core = Core::new().unwrap();
+    let mastodon = mastodon(&base, &core);
+
+    let (file, written) = core.run(mastodon.download_attachment(&attachment, Vec::new(), None)).unwrap();
+    assert_eq!(written, 10);
+    assert_eq!(file, b"0123456789");
+
+    match core.run(mastodon.download_attachment(&attachment, Vec::new(), Some(4))) {
+        Err(mammut::Error::TooLarge(4)) => {},
+        other => panic!("expected TooLarge, got {:?}", other.map(|(_, written)| written)),
+    }
+
+    let requests: Vec<String> = requests.iter().collect();
+    assert_eq!(requests, ["GET /media/1.png", "GET /media/1.png"]);
+}
+
+#[test]
+fn errors_are_read_like_the_blocking_client() {
+    let (base, _) = common::serve_text(vec![
+        (422, json!({"error": "Validation failed"}).to_string()),
+        (404, "Not found".to_owned()),
+        (503, "Unavailable".to_owned()),
+    ]);
+    let mut core = Core::new().unwrap();
+    let mastodon = mastodon(&base, &core);
+
+    match core.run(mastodon.get_status(1)) {
+        Err(mammut::Error::Api(ref e)) => {
+            assert_eq!(e.error, "Validation failed");
+            assert_eq!(e.status.map(u16::from), Some(422));
+        },
+        other => panic!("expected an API error, got {:?}", other.map(|status| status.id)),
+    }
+    match core.run(mastodon.get_status(1)) {
+        Err(mammut::Error::Client(status)) => assert_eq!(u16::from(status), 404),
+        other => panic!("expected a client error, got {:?}", other.map(|status| status.id)),
+    }
+    match core.run(mastodon.get_status(1)) {
+        Err(mammut::Error::Server(status)) => assert_eq!(u16::from(status), 503),
+        other => panic!("expected a server error, got {:?}", other.map(|status| status.id)),
+    }
+}
@@ -0,0 +1,79 @@
+extern crate mammut;
+#[macro_use] extern crate serde_json;
+
+mod common;
+
+use mammut::{Data, Error, Mastodon, Registration, StatusBuilder};
+use mammut::apps::Scope;
+
+#[test]
+fn base_with_path() {
+    let mut mastodon = common::mastodon_at("https://example.invalid/mastodon/");
+    mastodon.set_read_only(true);
+
+    match mastodon.new_status(StatusBuilder::new("hello".to_owned())) {
+        Err(Error::ReadOnly(request)) => {
+            assert_eq!(request, "POST https://example.invalid/mastodon/api/v1/statuses")
+        },
+        other => panic!("expected a read-only error, got {:?}", other),
+    }
+}
+
+#[test]
+fn registration_base_with_path() {
+    let mut registration = Registration::from_app_credentials(
+        "https://example.invalid/mastodon//", "id", "secret", "urn:ietf:wg:oauth:2.0:oob");
+
+    assert!(registration.authorise().unwrap()
+        .starts_with("https://example.invalid/mastodon/oauth/authorize?client_id=id&"));
+}
+
+#[test]
+fn invalid_base() {
+    for base in &["mastodon.social", "mastodon.social:443", "https://", "file:///mastodon"] {
+        let data = Data {
+            base: base.to_string().into(),
+            client_id: "id".into(),
+            client_secret: "secret".into(),
+            redirect: "urn:ietf:wg:oauth:2.0:oob".into(),
+            token: "token".into(),
+        };
+
+        match Mastodon::try_from_data(data) {
+            Err(Error::Url(_)) => {},
+            other => panic!("expected {} to be invalid, got {:?}", base, other),
+        }
+    }
+}
+
+#[test]
+fn base_path_is_stripped_from_routes() {
+    let mut mastodon = common::mastodon_at("https://example.invalid/mastodon");
+    mastodon.set_scopes(Some(Scope::WriteFollows.into()));
+    mastodon.set_check_scopes(true);
+    mastodon.set_dry_run(true);
+
+    if let Err(Error::InsufficientScope(scopes)) = mastodon.follow(1) {
+        panic!("expected the request to be permitted, needed {:?}", scopes);
+    }
+
+    let (base, requests) = common::serve(vec![
+        (200, json!([common::relationship("1", false)])),
+        (200, common::relationship("1", true)),
+        (200, json!([common::relationship("1", true)])),
+    ]);
+    let mastodon = common::mastodon_at(&format!("{}/mastodon/", base));
+    mastodon.set_cache_relationships(true);
+    mastodon.set_collect_stats(true);
+
+    assert!(!mastodon.relationships(&[1]).unwrap()[0].following);
+    assert!(mastodon.follow(1).unwrap().following);
+    assert!(mastodon.relationships(&[1]).unwrap()[0].following);
+
+    assert_eq!(requests.iter().take(3).collect::<Vec<_>>(), [
+        "GET /mastodon/api/v1/accounts/relationships?id=1",
+        "POST /mastodon/api/v1/accounts/1/follow",
+        "GET /mastodon/api/v1/accounts/relationships?id=1",
+    ]);
+    assert_eq!(mastodon.stats().unwrap().endpoints["POST /api/v1/accounts/:id/follow"].calls, 1);
+}
@@ -0,0 +1,38 @@
+extern crate mammut;
+#[macro_use] extern crate serde_json;
+
+mod common;
+
+use mammut::Error;
+use mammut::apps::Scopes;
+
+#[test]
+fn get_accounts_returns_every_id() {
+    let mastodon = common::mastodon().restricted_to(Scopes::Write);
+
+    let ids: Vec<u64> = (1..10).collect();
+    let results = mastodon.get_accounts(&ids);
+
+    assert_eq!(results.len(), ids.len());
+    for result in results {
+        match result {
+            Err(Error::InsufficientScope(_)) => {},
+            other => panic!("expected the request to be rejected, got {:?}", other),
+        }
+    }
+
+    assert!(mastodon.get_accounts(&[]).is_empty());
+}
+
+#[test]
+fn resolve_handle_fetches_remote_accounts() {
+    let mut account = common::account("2");
+    account["acct"] = json!("a+b@example.com");
+    let (base, requests) = common::serve(vec![(200, json!([account]))]);
+
+    let found = common::mastodon_at(&base).resolve_handle("@a+b@example.com").unwrap();
+
+    assert_eq!(found.id, "2");
+    assert_eq!(requests.recv().unwrap(),
+               "GET /api/v1/accounts/search?q=a%2Bb%40example.com&resolve=true&limit=1");
+}
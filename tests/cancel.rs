@@ -0,0 +1,32 @@
+extern crate mammut;
+#[macro_use] extern crate serde_json;
+
+mod common;
+
+use mammut::Error;
+use mammut::cancel::CancellationToken;
+
+#[test]
+fn guard() {
+    let token = CancellationToken::new();
+
+    token.guard().disarm();
+    assert!(!token.is_cancelled());
+
+    drop(token.guard());
+    assert!(token.is_cancelled());
+}
+
+#[test]
+fn cancelled_requests_fail() {
+    let mastodon = common::mastodon();
+    let token = CancellationToken::new();
+    let view = mastodon.with_cancellation(&token);
+
+    token.cancel();
+
+    match view.verify_credentials() {
+        Err(Error::Cancelled) => {},
+        other => panic!("expected the request to be cancelled, got {:?}", other),
+    }
+}
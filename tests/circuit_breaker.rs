@@ -0,0 +1,35 @@
+extern crate mammut;
+
+use std::time::Duration;
+
+use mammut::Error;
+use mammut::circuit_breaker::CircuitBreaker;
+
+#[test]
+fn opens_after_consecutive_failures() {
+    let mut breaker = CircuitBreaker::new(3, Duration::from_secs(60));
+    breaker.record(true);
+    breaker.record(true);
+    breaker.record(false);
+    breaker.record(true);
+    breaker.record(true);
+    assert!(breaker.check().is_ok());
+
+    breaker.record(true);
+    assert!(breaker.is_open());
+    match breaker.check() {
+        Err(Error::CircuitOpen(left)) => assert!(left <= Duration::from_secs(60)),
+        other => panic!("expected the breaker to be open, got {:?}", other),
+    }
+}
+
+#[test]
+fn closes_after_success_once_cooled_down() {
+    let mut breaker = CircuitBreaker::new(1, Duration::from_secs(0));
+    breaker.record(true);
+    assert!(breaker.check().is_ok());
+
+    breaker.record(false);
+    assert_eq!(breaker.failures(), 0);
+    assert!(!breaker.is_open());
+}
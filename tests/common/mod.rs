@@ -0,0 +1,144 @@
+// Fixtures shared by the tests. Not every test uses all of them.
+#![allow(dead_code)]
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+use mammut::{Data, Mastodon};
+use serde_json::Value;
+
+/// A client of an instance which doesn't exist, for tests which don't send
+/// any requests.
+pub fn mastodon() -> Mastodon {
+    mastodon_at("https://mastodon.invalid")
+}
+
+/// A client of the instance at `base`.
+pub fn mastodon_at(base: &str) -> Mastodon {
+    Mastodon::from_data(Data {
+        base: base.to_owned().into(),
+        client_id: "id".into(),
+        client_secret: "secret".into(),
+        redirect: "urn:ietf:wg:oauth:2.0:oob".into(),
+        token: "token".into(),
+    })
+}
+
+/// The JSON of the account `id`, named `user<id>`.
+pub fn account(id: &str) -> Value {
+    json!({
+        "acct": format!("user{}", id),
+        "avatar": "",
+        "avatar_static": "",
+        "created_at": "2017-04-01T00:00:00Z",
+        "display_name": "",
+        "followers_count": 0,
+        "following_count": 0,
+        "header": "",
+        "header_static": "",
+        "id": id,
+        "locked": false,
+        "note": "",
+        "statuses_count": 0,
+        "url": format!("https://example.com/@user{}", id),
+        "username": format!("user{}", id)
+    })
+}
+
+/// The JSON of the public status `id` by account 1, without any content.
+pub fn status(id: &str) -> Value {
+    json!({
+        "id": id,
+        "uri": format!("https://example.com/statuses/{}", id),
+        "url": null,
+        "account": account("1"),
+        "in_reply_to_id": null,
+        "in_reply_to_account_id": null,
+        "reblog": null,
+        "content": "",
+        "created_at": "2018-01-01T00:00:00Z",
+        "emojis": [],
+        "reblogs_count": 0,
+        "favourites_count": 0,
+        "reblogged": null,
+        "favourited": null,
+        "sensitive": false,
+        "spoiler_text": "",
+        "visibility": "public",
+        "media_attachments": [],
+        "mentions": [],
+        "tags": [],
+        "application": null,
+        "language": null,
+        "pinned": null
+    })
+}
+
+/// The JSON of the relationship to the account `id`.
+pub fn relationship(id: &str, following: bool) -> Value {
+    json!({
+        "id": id,
+        "following": following,
+        "followed_by": false,
+        "blocking": false,
+        "muting": false,
+        "requested": false,
+        "muting_notifications": false,
+        "domain_blocking": false
+    })
+}
+
+/// Answers one request after another with `responses`, given as their
+/// status code and JSON body, on a local port. Returns the url of the
+/// server, and the method and path of each request received.
+pub fn serve(responses: Vec<(u16, Value)>) -> (String, Receiver<String>) {
+    serve_text(responses.into_iter().map(|(status, body)| (status, body.to_string())).collect())
+}
+
+/// Like `serve`, with bodies which aren't JSON, such as streams.
+pub fn serve_text(responses: Vec<(u16, String)>) -> (String, Receiver<String>) {
+    serve_with_headers(responses.into_iter().map(|(status, body)| (status, String::new(), body)).collect())
+}
+
+/// Like `serve_text`, sending the headers given as `Name: value\r\n` lines
+/// with each response, with `{base}` replaced by the url of the server.
+pub fn serve_with_headers(responses: Vec<(u16, String, String)>) -> (String, Receiver<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    let (requests, received) = mpsc::channel();
+
+    let server = base.clone();
+    thread::spawn(move || {
+        for (status, headers, body) in responses {
+            let headers = headers.replace("{base}", &server);
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+            let mut request = String::new();
+            reader.read_line(&mut request).unwrap();
+            let mut length = 0;
+            loop {
+                let mut header = String::new();
+                reader.read_line(&mut header).unwrap();
+                if header.trim().is_empty() {
+                    break;
+                }
+                if let Some(value) = header.to_lowercase().strip_prefix("content-length:") {
+                    length = value.trim().parse().unwrap();
+                }
+            }
+            reader.by_ref().take(length).read_to_end(&mut Vec::new()).unwrap();
+
+            write!(stream, "HTTP/1.1 {} Status\r\nContent-Type: application/json\r\n{}\
+                            Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                   status, headers, body.len(), body).unwrap();
+
+            let request: Vec<&str> = request.split_whitespace().take(2).collect();
+            let _ = requests.send(request.join(" "));
+        }
+    });
+
+    (base, received)
+}
@@ -0,0 +1,50 @@
+#[macro_use] extern crate log;
+extern crate mammut;
+#[macro_use] extern crate serde_json;
+
+mod common;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use log::{Level, Log, Metadata, Record};
+
+static WARNINGS: AtomicUsize = AtomicUsize::new(0);
+
+struct CountWarnings;
+
+impl Log for CountWarnings {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= Level::Warn
+    }
+
+    fn log(&self, record: &Record) {
+        if record.level() == Level::Warn && record.target().starts_with("mammut") {
+            WARNINGS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+#[test]
+fn deprecations_are_tracked_per_endpoint() {
+    log::set_logger(&CountWarnings).unwrap();
+    log::set_max_level(log::LevelFilter::Warn);
+    warn!("not counted, logged outside of mammut");
+
+    let headers = "Deprecation: true\r\n".to_owned();
+    let (base, _) = common::serve_with_headers(vec![
+        (200, headers.clone(), common::account("1").to_string()),
+        (200, headers, common::account("2").to_string()),
+    ]);
+    let mastodon = common::mastodon_at(&base);
+
+    mastodon.get_account(1).unwrap();
+    mastodon.get_account(2).unwrap();
+
+    let deprecations = mastodon.deprecations();
+    assert_eq!(deprecations.len(), 1);
+    assert_eq!(deprecations[0].endpoint, "GET /api/v1/accounts/:id");
+    assert_eq!(deprecations[0].deprecation.as_deref(), Some("true"));
+    assert_eq!(WARNINGS.load(Ordering::SeqCst), 1);
+}
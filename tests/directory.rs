@@ -0,0 +1,31 @@
+extern crate mammut;
+#[macro_use] extern crate serde_json;
+
+use mammut::directory::Server;
+
+#[test]
+fn joinmastodon_server() {
+    let servers: Vec<Server> = serde_json::from_value(json!([{
+        "domain": "mastodon.social",
+        "version": "4.2.0",
+        "description": "The original server operated by the Mastodon gGmbH non-profit",
+        "languages": ["en"],
+        "region": "",
+        "categories": [],
+        "proxied_thumbnail": "https://proxy.joinmastodon.org/thumbnail.png",
+        "blurhash": "UeKUpFxuo~R%0nW;WCnhF6RjaJt757oJodS$",
+        "total_users": 1800000,
+        "last_week_users": 250000,
+        "approval_required": false,
+        "language": "en",
+        "category": "general"
+    }, {
+        "domain": "example.com"
+    }])).unwrap();
+
+    assert_eq!(servers[0].last_week_users, 250000);
+    assert_eq!(servers[0].category, "general");
+    assert_eq!(servers[1].base(), "https://example.com");
+    assert!(servers[1].languages.is_empty());
+    assert!(servers[1].proxied_thumbnail.is_none());
+}
@@ -0,0 +1,21 @@
+extern crate mammut;
+#[macro_use] extern crate serde_json;
+
+mod common;
+
+use mammut::StatusBuilder;
+
+#[test]
+fn writes_are_synthesised_instead_of_sent() {
+    let (base, requests) = common::serve(vec![(200, common::status("1"))]);
+    let mut mastodon = common::mastodon_at(&base);
+    mastodon.set_dry_run(true);
+
+    assert!(mastodon.clear_notifications().is_ok());
+    assert!(mastodon.delete_status(1).is_ok());
+    assert!(mastodon.follow(1).unwrap().following);
+    assert_eq!(mastodon.new_status(StatusBuilder::new("hi".into())).unwrap().content, "hi");
+    assert_eq!(mastodon.get_status(1).unwrap().id, "1");
+
+    assert_eq!(requests.iter().collect::<Vec<_>>(), ["GET /api/v1/statuses/1"]);
+}
@@ -0,0 +1,29 @@
+extern crate mammut;
+#[macro_use] extern crate serde_json;
+
+use mammut::entities::status::Emoji;
+
+fn emoji(shortcode: &str, category: Option<&str>) -> Emoji {
+    serde_json::from_value(json!({
+        "shortcode": shortcode,
+        "static_url": "",
+        "url": "",
+        "category": category,
+    })).unwrap()
+}
+
+#[test]
+fn group_by_category() {
+    let groups = Emoji::group_by_category(vec![
+        emoji("ferris", Some("Rust")),
+        emoji("blobcat", None),
+        emoji("corro", Some("Rust")),
+    ]);
+
+    let rust: Vec<_> = groups[&Some("Rust".to_owned())].iter()
+        .map(|emoji| &*emoji.shortcode)
+        .collect();
+    assert_eq!(rust, ["ferris", "corro"]);
+    assert_eq!(groups[&None].len(), 1);
+    assert!(groups[&None][0].visible_in_picker);
+}
@@ -0,0 +1,26 @@
+extern crate mammut;
+#[macro_use] extern crate serde_json;
+
+mod common;
+
+#[test]
+fn following_exports_boosts_and_notifications() {
+    let mut quiet = common::relationship("3", true);
+    quiet["showing_reblogs"] = json!(false);
+    quiet["notifying"] = json!(true);
+    let (base, requests) = common::serve(vec![
+        (200, common::account("1")),
+        (200, json!([common::account("2"), common::account("3")])),
+        (200, json!([common::relationship("2", true), quiet])),
+    ]);
+    let mastodon = common::mastodon_at(&base);
+
+    let mut csv = Vec::new();
+    mammut::export::following(&mastodon, &mut csv).unwrap();
+
+    assert_eq!(String::from_utf8(csv).unwrap(), "Account address,Show boosts,Notify on new posts,Languages\n\
+                                                 user2@127.0.0.1,true,false,\n\
+                                                 user3@127.0.0.1,false,true,\n");
+    let requests: Vec<String> = requests.iter().collect();
+    assert_eq!(requests[1], "GET /api/v1/accounts/1/following");
+}
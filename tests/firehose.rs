@@ -0,0 +1,74 @@
+extern crate mammut;
+#[macro_use] extern crate serde_json;
+
+mod common;
+
+use std::sync::mpsc;
+
+use mammut::entities::status::Status;
+use mammut::firehose::{Callback, Firehose};
+use mammut::jsonl::JsonLines;
+
+fn status(id: &str, uri: &str) -> Status {
+    let mut status = common::status(id);
+    status["uri"] = json!(uri);
+    status["content"] = json!("<p>Hello</p>");
+    status["media_attachments"] = json!([{
+        "id": "5",
+        "type": "image",
+        "url": "https://example.com/5.png",
+        "remote_url": null,
+        "preview_url": "https://example.com/5_small.png",
+        "text_url": null,
+        "meta": {},
+        "description": null
+    }]);
+
+    serde_json::from_value(status).unwrap()
+}
+
+#[test]
+fn passes_each_status_once() {
+    let mastodon = common::mastodon();
+    let (sender, receiver) = mpsc::channel();
+    let mut lines = Vec::new();
+    let mut count = 0;
+
+    {
+        let mut firehose = Firehose::new(&mastodon);
+        firehose
+            .add_sink(sender)
+            .add_sink(JsonLines::new(&mut lines))
+            .add_sink(Callback(|_: &Status| {
+                count += 1;
+                Ok(())
+            }));
+
+        assert!(firehose.push(&status("1", "https://example.com/1")).unwrap());
+        assert!(firehose.push(&status("2", "https://example.org/2")).unwrap());
+        assert!(!firehose.push(&status("3", "https://example.com/1")).unwrap());
+    }
+
+    assert_eq!(count, 2);
+    assert_eq!(receiver.try_iter().map(|status| status.id).collect::<Vec<_>>(), ["1", "2"]);
+
+    let written: Vec<Status> = String::from_utf8(lines).unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(written.len(), 2);
+    assert_eq!(written[1].uri, "https://example.org/2");
+    assert!(written[1].media_attachments[0].meta.is_none());
+}
+
+#[test]
+fn stops_once_the_receiver_is_dropped() {
+    let mastodon = common::mastodon();
+    let (sender, receiver) = mpsc::channel();
+    drop(receiver);
+
+    let mut firehose = Firehose::new(&mastodon);
+    firehose.add_sink(sender);
+
+    assert!(firehose.push(&status("1", "https://example.com/1")).is_err());
+}
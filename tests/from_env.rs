@@ -0,0 +1,27 @@
+extern crate mammut;
+
+use std::env;
+
+use mammut::{Data, Error};
+
+#[test]
+fn from_env() {
+    env::set_var("MASTODON_BASE", "https://mastodon.social");
+    env::set_var("MASTODON_CLIENT_ID", "id");
+    env::set_var("MASTODON_CLIENT_SECRET", "secret");
+    env::remove_var("MASTODON_ACCESS_TOKEN");
+
+    match Data::from_env() {
+        Err(Error::EnvVar(name)) => assert_eq!(name, "MASTODON_ACCESS_TOKEN"),
+        other => panic!("expected missing token, got {:?}", other),
+    }
+
+    env::set_var("MASTODON_ACCESS_TOKEN", "token");
+    let data = Data::from_env().unwrap();
+
+    assert_eq!(data.base, "https://mastodon.social");
+    assert_eq!(data.client_id, "id");
+    assert_eq!(data.client_secret, "secret");
+    assert_eq!(data.redirect, "urn:ietf:wg:oauth:2.0:oob");
+    assert_eq!(data.token, "token");
+}
@@ -0,0 +1,26 @@
+extern crate mammut;
+#[macro_use] extern crate serde_json;
+
+use mammut::entities::instance::Instance;
+
+#[test]
+fn instance_stats_and_contact() {
+    let instance: Instance = serde_json::from_value(json!({
+        "uri": "mastodon.social",
+        "title": "Mastodon",
+        "description": "",
+        "email": "staff@mastodon.social",
+        "version": "3.5.3",
+        "urls": [],
+        "stats": { "user_count": 812303, "status_count": 38151616, "domain_count": 25255 },
+        "thumbnail": "https://files.mastodon.social/site_uploads/files/000/000/001/original/banner.png",
+        "languages": ["en"],
+        "registrations": true,
+        "contact_account": null,
+    })).unwrap();
+
+    assert_eq!(instance.stats.user_count, 812303);
+    assert_eq!(instance.languages, ["en"]);
+    assert!(instance.registrations);
+    assert!(instance.contact_account.is_none());
+}
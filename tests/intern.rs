@@ -0,0 +1,42 @@
+extern crate mammut;
+#[macro_use] extern crate serde_json;
+
+mod common;
+
+use std::sync::Arc;
+
+use mammut::entities::status::Status;
+use mammut::intern::Interner;
+
+fn status(id: &str, account_id: &str, reblog: Option<serde_json::Value>) -> serde_json::Value {
+    let mut status = common::status(id);
+    status["account"] = common::account(account_id);
+    status["reblog"] = json!(reblog);
+    status
+}
+
+#[test]
+fn shares_accounts() {
+    let statuses: Vec<Status> = serde_json::from_value(json!([
+        status("1", "1", None),
+        status("2", "2", None),
+        status("3", "1", Some(status("4", "2", None))),
+    ])).unwrap();
+
+    let mut interner = Interner::new();
+    let statuses = interner.intern_statuses(statuses);
+
+    assert_eq!(interner.len(), 2);
+    assert!(Arc::ptr_eq(statuses[0].account(), statuses[2].account()));
+    assert!(Arc::ptr_eq(statuses[1].account(), statuses[2].reblog_account().unwrap()));
+    assert!(!Arc::ptr_eq(statuses[0].account(), statuses[1].account()));
+    assert_eq!(statuses[2].status().account.id, "1");
+    assert_eq!(statuses[2].status().account.acct, "");
+
+    let status = statuses[2].clone().into_status();
+    assert_eq!(status.account.acct, common::account("1")["acct"]);
+    assert_eq!(status.reblog.unwrap().account.acct, common::account("2")["acct"]);
+
+    interner.clear();
+    assert!(interner.is_empty());
+}
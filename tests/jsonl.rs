@@ -0,0 +1,41 @@
+extern crate mammut;
+#[macro_use] extern crate serde_json;
+
+use std::fs;
+
+use mammut::jsonl::{JsonLines, JsonLinesFile, Rotation};
+
+#[test]
+fn writes_lines() {
+    let mut lines = JsonLines::new(Vec::new());
+    lines.write(&json!({ "id": "1" })).unwrap();
+    lines.write(&json!({ "id": "2" })).unwrap();
+
+    assert_eq!(String::from_utf8(lines.into_inner()).unwrap(), "{\"id\":\"1\"}\n{\"id\":\"2\"}\n");
+}
+
+#[test]
+fn rotates_by_size() {
+    let dir = std::env::temp_dir().join(format!("mammut-jsonl-{}", std::process::id()));
+    let rotation = Rotation { max_size: Some(20), max_age: None };
+    let mut file = JsonLinesFile::create(&dir, "statuses", rotation).unwrap();
+    assert!(file.path().is_none());
+
+    for id in 0..4 {
+        file.write(&json!({ "id": id.to_string() })).unwrap();
+    }
+    let last = file.path().unwrap().to_owned();
+    drop(file);
+
+    let mut names: Vec<String> = fs::read_dir(&dir).unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    names.sort();
+
+    // Each line is 11 bytes, so every file holds two lines.
+    assert_eq!(names.len(), 2);
+    assert!(names.iter().all(|name| name.starts_with("statuses-") && name.ends_with(".jsonl")));
+    assert_eq!(fs::read_to_string(&last).unwrap(), "{\"id\":\"2\"}\n{\"id\":\"3\"}\n");
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,29 @@
+extern crate mammut;
+#[macro_use] extern crate serde_json;
+
+mod common;
+
+use std::sync::mpsc;
+use std::time::Duration;
+
+use mammut::Error;
+use mammut::apps::{Scope, Scopes};
+
+#[test]
+fn reports_failures() {
+    // Restricting the client makes `verify_credentials` fail without a
+    // request being sent.
+    let mastodon = common::mastodon().restricted_to(Scopes::Write);
+
+    let (failures, failed) = mpsc::channel();
+    let keepalive = mastodon.keepalive(Duration::from_millis(10), move |e| {
+        failures.send(e).unwrap();
+    });
+
+    match failed.recv_timeout(Duration::from_secs(5)) {
+        Ok(Error::InsufficientScope(scopes)) => assert_eq!(scopes, Scope::ReadAccounts.into()),
+        other => panic!("expected the ping to fail, got {:?}", other),
+    }
+
+    keepalive.stop();
+}
@@ -0,0 +1,27 @@
+extern crate mammut;
+#[macro_use] extern crate serde_json;
+
+mod common;
+
+use mammut::Error;
+
+#[test]
+fn list_routes() {
+    let mut mastodon = common::mastodon();
+    mastodon.set_read_only(true);
+
+    match mastodon.update_list(7, "Rustaceans") {
+        Err(Error::ReadOnly(request)) => {
+            assert_eq!(request, "PUT https://mastodon.invalid/api/v1/lists/7")
+        },
+        other => panic!("expected a read-only error, got {:?}", other),
+    }
+
+    match mastodon.delete_list(7) {
+        Err(Error::ReadOnly(request)) => {
+            assert_eq!(request, "DELETE https://mastodon.invalid/api/v1/lists/7")
+        },
+        Err(e) => panic!("expected a read-only error, got {:?}", e),
+        Ok(_) => panic!("expected a read-only error"),
+    }
+}
@@ -0,0 +1,36 @@
+extern crate mammut;
+#[macro_use] extern crate serde_json;
+
+mod common;
+
+use mammut::entities::notification::Notification;
+use mammut::markers::MarkerTracker;
+
+fn notification(id: &str) -> Notification {
+    serde_json::from_value(json!({
+        "id": id,
+        "type": "follow",
+        "created_at": "2019-01-01T00:00:00Z",
+        "account": common::account("1")
+    })).unwrap()
+}
+
+#[test]
+fn advances_to_newest() {
+    let mut mastodon = common::mastodon();
+    mastodon.set_dry_run(true);
+
+    let mut tracker = MarkerTracker::new(&mastodon);
+    assert!(tracker.last_read_id().is_none());
+
+    let consumed: Vec<_> = tracker.track(vec![notification("9"), notification("10"), notification("8")])
+        .map(|notification| notification.id)
+        .collect();
+
+    assert_eq!(consumed, vec!["9", "10", "8"]);
+    assert_eq!(tracker.last_read_id(), Some("10"));
+
+    tracker.read(&notification("100")).unwrap();
+    tracker.flush().unwrap();
+    assert_eq!(tracker.last_read_id(), Some("100"));
+}
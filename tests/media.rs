@@ -0,0 +1,38 @@
+extern crate mammut;
+
+use mammut::media::detect_media_type;
+use mammut::entities::instance::MediaAttachmentsConfiguration;
+
+#[test]
+fn detect_and_check_media_type() {
+    let mp4 = b"\x00\x00\x00\x18ftypmp42\x00\x00\x00\x00";
+    assert_eq!(detect_media_type(mp4), Some("video/mp4"));
+    assert_eq!(detect_media_type(b"\xFF\xD8\xFF\xE0"), Some("image/jpeg"));
+    assert_eq!(detect_media_type(b"%PDF-1.4"), None);
+    assert_eq!(detect_media_type(b""), None);
+
+    let limits = MediaAttachmentsConfiguration {
+        supported_mime_types: vec!["image/jpeg".into(), "video/mp4".into()],
+        ..MediaAttachmentsConfiguration::default()
+    };
+    assert!(limits.supports_media_type("video/mp4"));
+    assert!(!limits.supports_media_type("image/heic"));
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn strip_metadata_removes_exif() {
+    let mut jpeg = vec![0xFF, 0xD8];
+    // APP0 JFIF segment, kept.
+    jpeg.extend_from_slice(&[0xFF, 0xE0, 0x00, 0x07, b'J', b'F', b'I', b'F', 0x00]);
+    // APP1 EXIF segment, removed.
+    jpeg.extend_from_slice(&[0xFF, 0xE1, 0x00, 0x08, b'E', b'x', b'i', b'f', 0x00, 0x00]);
+    // Start of scan and image data, kept.
+    jpeg.extend_from_slice(&[0xFF, 0xDA, 0x00, 0x02, 0x12, 0x34, 0xFF, 0xD9]);
+
+    let stripped = mammut::media::strip_metadata(&jpeg);
+
+    assert_eq!(&stripped[..11], &jpeg[..11]);
+    assert_eq!(&stripped[11..], &jpeg[21..]);
+    assert_eq!(mammut::media::strip_metadata(b"GIF89a"), b"GIF89a");
+}
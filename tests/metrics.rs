@@ -0,0 +1,62 @@
+#![cfg(feature = "metrics")]
+
+extern crate mammut;
+extern crate metrics;
+#[macro_use] extern crate serde_json;
+
+mod common;
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use mammut::streaming::Stream;
+use metrics::{Counter, Gauge, Histogram, Key, KeyName, Metadata, Recorder, SharedString, Unit};
+
+// Counts the reconnects recorded, ignoring every other metric.
+#[derive(Default)]
+struct Reconnects(Arc<AtomicU64>);
+
+impl Recorder for Reconnects {
+    fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+    fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+    fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn register_counter(&self, key: &Key, _: &Metadata) -> Counter {
+        if key.name() == "mammut_stream_reconnects_total" {
+            Counter::from_arc(self.0.clone())
+        } else {
+            Counter::noop()
+        }
+    }
+
+    fn register_gauge(&self, _: &Key, _: &Metadata) -> Gauge {
+        Gauge::noop()
+    }
+
+    fn register_histogram(&self, _: &Key, _: &Metadata) -> Histogram {
+        Histogram::noop()
+    }
+}
+
+#[test]
+fn reconnecting_streams_record_reconnects() {
+    let update = |id: &str| format!("event: update\ndata: {}\n\n", common::status(id));
+    let (base, _) = common::serve_text(vec![
+        (200, update("1")),
+        (200, update("2")),
+        (200, json!([]).to_string()),
+    ]);
+    let mastodon = common::mastodon_at(&base);
+    let recorder = Reconnects::default();
+
+    metrics::with_local_recorder(&recorder, || {
+        let mut events = mastodon.reconnecting_stream(Stream::Public, None);
+        events.reconnect_delay = Duration::from_millis(0);
+
+        assert!(events.next().unwrap().is_ok());
+        assert_eq!(recorder.0.load(Ordering::SeqCst), 0);
+        assert!(events.next().unwrap().is_ok());
+        assert_eq!(recorder.0.load(Ordering::SeqCst), 1);
+    });
+}
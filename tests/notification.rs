@@ -0,0 +1,62 @@
+extern crate mammut;
+#[macro_use] extern crate serde_json;
+
+mod common;
+
+use mammut::entities::notification::{Notification, NotificationType};
+
+#[test]
+fn as_str_matches_api_names() {
+    let types = [
+        NotificationType::Mention,
+        NotificationType::Reblog,
+        NotificationType::Favourite,
+        NotificationType::Follow,
+        NotificationType::SeveredRelationships,
+        NotificationType::AdminSignUp,
+        NotificationType::AdminReport,
+        NotificationType::Unknown,
+    ];
+
+    for notification_type in &types {
+        let name = format!("\"{}\"", notification_type.as_str());
+        let parsed: NotificationType = serde_json::from_str(&name).unwrap();
+        assert_eq!(parsed, *notification_type);
+    }
+}
+
+#[test]
+fn unknown_types() {
+    let notification: Notification = serde_json::from_value(json!({
+        "id": "1",
+        "type": "quoted_update",
+        "created_at": "2019-01-01T00:00:00Z",
+        "account": common::account("1"),
+    })).unwrap();
+
+    assert_eq!(notification.notification_type, NotificationType::Unknown);
+}
+
+#[test]
+fn admin_report() {
+    let notification: Notification = serde_json::from_value(json!({
+        "id": "34975861",
+        "type": "admin.report",
+        "created_at": "2022-11-28T13:34:02.000Z",
+        "account": common::account("1"),
+        "report": {
+            "id": "48914",
+            "action_taken": false,
+            "comment": "Spam",
+            "category": "spam",
+            "created_at": "2022-11-28T13:34:02.000Z",
+            "status_ids": ["109259402177932903"],
+            "target_account": common::account("2"),
+        },
+    })).unwrap();
+
+    assert_eq!(notification.notification_type, NotificationType::AdminReport);
+    let report = notification.report.unwrap();
+    assert!(!report.action_taken);
+    assert_eq!(report.target_account.unwrap().id, "2");
+}
@@ -0,0 +1,109 @@
+extern crate chrono;
+extern crate mammut;
+#[macro_use] extern crate serde_json;
+
+mod common;
+
+use chrono::{DateTime, Duration, Utc};
+use mammut::{Error, StatusBuilder};
+use mammut::outbox::{Outbox, ScheduledOutbox};
+
+fn outbox(name: &str, statuses: &[&str]) -> Outbox {
+    let path = std::env::temp_dir().join(format!("mammut-outbox-{}-{}.json", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let mut outbox = Outbox::open(&path).unwrap();
+    for status in statuses {
+        outbox.push(StatusBuilder::new(status.to_string())).unwrap();
+    }
+
+    outbox
+}
+
+#[test]
+fn flush_keeps_statuses_in_dry_run() {
+    let mut mastodon = common::mastodon();
+    mastodon.set_dry_run(true);
+    let mut outbox = outbox("dry-run", &["first", "second"]);
+
+    let results = outbox.flush(&mastodon).unwrap();
+
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].1.as_ref().unwrap().content, "first");
+    assert_eq!(results[1].1.as_ref().unwrap().content, "second");
+    assert_eq!(outbox.drafts().len(), 2);
+    assert_eq!(outbox.drafts()[0].attempts, 1);
+    assert_eq!(outbox.drafts()[1].attempts, 1);
+}
+
+#[test]
+fn flush_keeps_statuses_failing_on_the_server() {
+    let (base, _) = common::serve(vec![
+        (500, json!({ "error": "Something went wrong" })),
+        (429, json!({ "error": "Too many requests" })),
+    ]);
+    let mastodon = common::mastodon_at(&base);
+    let mut outbox = outbox("server-error", &["first", "second", "third"]);
+
+    let results = outbox.flush(&mastodon).unwrap();
+
+    assert_eq!(results.len(), 2);
+    match results[0].1 {
+        Err(Error::Api(ref error)) => assert_eq!(error.status.map(u16::from), Some(500)),
+        ref other => panic!("expected a server error, got {:?}", other),
+    }
+    assert_eq!(outbox.drafts().len(), 3);
+}
+
+#[test]
+fn flush_drops_rejected_statuses() {
+    let (base, _) = common::serve(vec![
+        (422, json!({ "error": "Validation failed: Text can't be blank" })),
+        (200, common::status("1")),
+    ]);
+    let mastodon = common::mastodon_at(&base);
+    let mut outbox = outbox("rejected", &["", "second"]);
+
+    let results = outbox.flush(&mastodon).unwrap();
+
+    assert!(results[0].1.is_err());
+    assert_eq!(results[1].1.as_ref().unwrap().id, "1");
+    assert!(outbox.drafts().is_empty());
+}
+
+#[test]
+fn scheduled_outbox_persists_statuses() {
+    let path = std::env::temp_dir().join(format!("mammut-scheduled-{}.json", std::process::id()));
+    let later: DateTime<Utc> = "2030-01-01T08:00:00Z".parse().unwrap();
+
+    let mut outbox = ScheduledOutbox::open(&path).unwrap();
+    assert_eq!(outbox.next_due(), None);
+
+    let first = outbox.schedule(StatusBuilder::new("later".into()), later).unwrap();
+    outbox.schedule(StatusBuilder::new("sooner".into()), later - Duration::hours(1)).unwrap();
+
+    let mut reopened = ScheduledOutbox::open(&path).unwrap();
+    assert_eq!(reopened.scheduled().len(), 2);
+    assert_eq!(reopened.next_due(), Some(later - Duration::hours(1)));
+
+    assert!(reopened.remove(&first).unwrap().is_some());
+    assert_eq!(ScheduledOutbox::open(&path).unwrap().scheduled().len(), 1);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn post_due_keeps_statuses_in_dry_run() {
+    let path = std::env::temp_dir().join(format!("mammut-scheduled-dry-run-{}.json", std::process::id()));
+    let mut mastodon = common::mastodon();
+    mastodon.set_dry_run(true);
+
+    let mut outbox = ScheduledOutbox::open(&path).unwrap();
+    outbox.schedule(StatusBuilder::new("now".into()), Utc::now() - Duration::minutes(1)).unwrap();
+
+    let results = outbox.post_due(&mastodon).unwrap();
+
+    assert_eq!(results.len(), 1);
+    assert_eq!(outbox.scheduled().len(), 1);
+    assert!(outbox.next_due().unwrap() > Utc::now());
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,67 @@
+extern crate mammut;
+
+use mammut::Error;
+use mammut::page::{Items, Links, Paginate};
+
+#[test]
+fn parse_links() {
+    let links = Links::parse(concat!(
+        r#"<https://mastodon.social/api/v1/timelines/home?max_id=101>; rel="next", "#,
+        r#"<https://mastodon.social/api/v1/timelines/home?min_id=120>; rel="prev""#,
+    )).unwrap();
+
+    assert_eq!(links.next.unwrap().query(), Some("max_id=101"));
+    assert_eq!(links.prev.unwrap().query(), Some("min_id=120"));
+}
+
+#[test]
+fn parse_links_with_quoted_params_and_several_rels() {
+    let links = Links::parse(r#"<https://example.com/a>; title="older; page, 2"; rel="next last", <https://example.com/b>; rel=prev"#).unwrap();
+
+    assert_eq!(links.next.unwrap().as_str(), "https://example.com/a");
+    assert_eq!(links.prev.unwrap().as_str(), "https://example.com/b");
+    assert_eq!(Links::parse("").unwrap(), Links::default());
+}
+
+#[test]
+fn fetch_until_stops_before_the_match() {
+    let statuses = vec![5, 4, 3, 2, 1];
+
+    assert_eq!(statuses.clone().fetch_until(|&id| id == 3).unwrap(), [5, 4]);
+    assert_eq!(statuses.fetch_all().unwrap().len(), 5);
+}
+
+struct Pages(Vec<mammut::Result<Vec<u32>>>);
+
+impl Paginate<u32> for Pages {
+    fn take_items(&mut self) -> Vec<u32> {
+        vec![1, 2]
+    }
+
+    fn fetch_next(&mut self) -> mammut::Result<Option<Vec<u32>>> {
+        if self.0.is_empty() {
+            return Ok(None);
+        }
+        self.0.remove(0).map(Some)
+    }
+}
+
+#[test]
+fn items_fetches_following_pages() {
+    let items: Vec<u32> = Items::new(Pages(vec![Ok(vec![3]), Ok(vec![4, 5])]))
+        .collect::<mammut::Result<_>>()
+        .unwrap();
+    assert_eq!(items, [1, 2, 3, 4, 5]);
+
+    let taken: Vec<u32> = Items::new(Pages(vec![Ok(vec![3]), Ok(vec![]), Ok(vec![4])]))
+        .take(10)
+        .collect::<mammut::Result<_>>()
+        .unwrap();
+    assert_eq!(taken, [1, 2, 3]);
+
+    let mut failing = Items::new(Pages(vec![Err(Error::Cancelled), Ok(vec![3])]));
+    assert_eq!(failing.next().unwrap().unwrap(), 1);
+    assert_eq!(failing.next().unwrap().unwrap(), 2);
+    assert!(failing.next().unwrap().is_err());
+    assert!(failing.next().is_none());
+}
@@ -0,0 +1,86 @@
+extern crate mammut;
+#[macro_use] extern crate serde_json;
+
+mod common;
+
+use mammut::{Error, StatusBuilder};
+use mammut::apps::{Scope, Scopes};
+
+#[test]
+fn rejects_writes() {
+    let mut mastodon = common::mastodon();
+    mastodon.set_read_only(true);
+    mastodon.set_dry_run(true);
+
+    match mastodon.new_status(StatusBuilder::new("hello".to_owned())) {
+        Err(Error::ReadOnly(request)) => {
+            assert_eq!(request, "POST https://mastodon.invalid/api/v1/statuses")
+        },
+        other => panic!("expected a read-only error, got {:?}", other),
+    }
+
+    match mastodon.unblock_domain("example.com") {
+        Err(Error::ReadOnly(_)) => {},
+        Err(e) => panic!("expected a read-only error, got {:?}", e),
+        Ok(_) => panic!("expected a read-only error"),
+    }
+}
+
+#[test]
+fn insufficient_scope() {
+    let mut mastodon = common::mastodon();
+    mastodon.set_scopes(Some(Scopes::ReadWrite));
+    let follow = mastodon.restricted_to(Scopes::ReadFollow);
+
+    assert_eq!(follow.scopes(), Some(Scopes::Read));
+
+    match follow.follow(1) {
+        Err(Error::InsufficientScope(scopes)) => assert_eq!(scopes, Scope::WriteFollows.into()),
+        other => panic!("expected an insufficient scope error, got {:?}", other),
+    }
+
+    match mastodon.restricted_to(Scopes::Follow).get_home_timeline() {
+        Err(Error::InsufficientScope(scopes)) => assert_eq!(scopes, Scope::ReadStatuses.into()),
+        Err(e) => panic!("expected an insufficient scope error, got {:?}", e),
+        Ok(_) => panic!("expected an insufficient scope error"),
+    }
+}
+
+#[test]
+fn restricted_to_scopes_not_granted() {
+    let mut mastodon = common::mastodon();
+    mastodon.set_scopes(Some(Scopes::Read));
+    let writer = mastodon.restricted_to(Scopes::Write);
+
+    assert_eq!(writer.scopes(), Some(Scopes::empty()));
+
+    match writer.new_status(StatusBuilder::new("hello".to_owned())) {
+        Err(Error::InsufficientScope(scopes)) => assert_eq!(scopes, Scope::WriteStatuses.into()),
+        other => panic!("expected an insufficient scope error, got {:?}", other),
+    }
+}
+
+#[test]
+fn granular_scopes() {
+    let mut mastodon = common::mastodon();
+    mastodon.set_scopes(Some(Scope::ReadStatuses | Scope::WriteStatuses));
+    mastodon.set_check_scopes(true);
+    mastodon.set_dry_run(true);
+
+    match mastodon.notifications() {
+        Err(Error::InsufficientScope(scopes)) => assert_eq!(scopes, Scope::ReadNotifications.into()),
+        Err(e) => panic!("expected an insufficient scope error, got {:?}", e),
+        Ok(_) => panic!("expected an insufficient scope error"),
+    }
+
+    // Dry runs can't synthesise a status, but the request was permitted.
+    if let Err(Error::InsufficientScope(scopes)) = mastodon.new_status(StatusBuilder::new("hello".to_owned())) {
+        panic!("expected the request to be permitted, needed {:?}", scopes);
+    }
+
+    // `follow` still grants following and blocking.
+    mastodon.set_scopes(Some(Scopes::Follow));
+    if let Err(e) = mastodon.unblock_domain("example.com") {
+        panic!("expected the request to be permitted, got {:?}", e);
+    }
+}
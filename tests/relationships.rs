@@ -0,0 +1,40 @@
+extern crate mammut;
+#[macro_use] extern crate serde_json;
+
+mod common;
+
+#[test]
+fn uncached_relationships_are_fetched_every_time() {
+    let (base, requests) = common::serve(vec![
+        (200, json!([common::relationship("1", true)])),
+        (200, json!([common::relationship("1", false)])),
+    ]);
+    let mastodon = common::mastodon_at(&base);
+
+    assert!(mastodon.relationships(&[1]).unwrap()[0].following);
+    assert!(!mastodon.relationships(&[1]).unwrap()[0].following);
+    assert_eq!(requests.iter().collect::<Vec<_>>(), [
+        "GET /api/v1/accounts/relationships?id=1",
+        "GET /api/v1/accounts/relationships?id=1",
+    ]);
+}
+
+#[test]
+fn cached_relationships_are_fetched_once() {
+    let (base, requests) = common::serve(vec![
+        (200, json!([common::relationship("1", true)])),
+        (200, json!([common::relationship("2", false)])),
+    ]);
+    let mastodon = common::mastodon_at(&base);
+    mastodon.set_cache_relationships(true);
+
+    assert_eq!(mastodon.relationships(&[1]).unwrap().len(), 1);
+    let relationships = mastodon.relationships(&[1, 2]).unwrap();
+
+    assert!(relationships[0].following);
+    assert!(!relationships[1].following);
+    assert_eq!(requests.iter().collect::<Vec<_>>(), [
+        "GET /api/v1/accounts/relationships?id=1",
+        "GET /api/v1/accounts/relationships?id=2",
+    ]);
+}
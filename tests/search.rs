@@ -0,0 +1,69 @@
+extern crate mammut;
+#[macro_use] extern crate serde_json;
+
+mod common;
+
+use mammut::entities::search_result::{Resolved, SearchResult};
+
+fn status(id: &str) -> serde_json::Value {
+    let mut status = common::status(id);
+    status["uri"] = json!(format!("https://example.com/users/mammut/statuses/{}", id));
+    status["url"] = json!(format!("https://example.com/@mammut/{}", id));
+    status
+}
+
+#[test]
+fn v2_hashtags() {
+    let result: SearchResult = serde_json::from_value(json!({
+        "accounts": [],
+        "statuses": [],
+        "hashtags": [{"name": "rust", "url": "https://example.com/tags/rust"}]
+    })).unwrap();
+
+    assert_eq!(result.hashtags[0].name, "rust");
+    assert_eq!(result.hashtags[0].url, "https://example.com/tags/rust");
+}
+
+#[test]
+fn v1_hashtags() {
+    let result: SearchResult = serde_json::from_value(json!({
+        "hashtags": ["rust"]
+    })).unwrap();
+
+    assert!(result.accounts.is_empty());
+    assert_eq!(result.hashtags[0].name, "rust");
+    assert_eq!(result.hashtags[0].url, "");
+}
+
+#[test]
+fn resolved() {
+    let result = || -> SearchResult {
+        serde_json::from_value(json!({
+            "accounts": [common::account("1"), common::account("2")],
+            "statuses": [status("1"), status("2")]
+        })).unwrap()
+    };
+
+    match result().resolved("https://example.com/@mammut/2") {
+        Some(Resolved::Status(status)) => assert_eq!(status.id, "2"),
+        other => panic!("expected the second status, got {:?}", other),
+    }
+
+    match result().resolved("https://example.com/users/mammut/statuses/2") {
+        Some(Resolved::Status(status)) => assert_eq!(status.id, "2"),
+        other => panic!("expected the second status, got {:?}", other),
+    }
+
+    match result().resolved("https://example.com/@user2") {
+        Some(Resolved::Account(account)) => assert_eq!(account.id, "2"),
+        other => panic!("expected the second account, got {:?}", other),
+    }
+
+    match result().resolved("https://example.com/@mammut/3") {
+        Some(Resolved::Status(status)) => assert_eq!(status.id, "1"),
+        other => panic!("expected the first status, got {:?}", other),
+    }
+
+    let empty: SearchResult = serde_json::from_value(json!({})).unwrap();
+    assert!(empty.resolved("https://example.com/@mammut").is_none());
+}
@@ -0,0 +1,21 @@
+extern crate mammut;
+
+use std::time::Duration;
+
+use mammut::stats::Stats;
+
+#[test]
+fn record() {
+    let mut stats = Stats::default();
+    stats.record("GET /api/v1/statuses/:id", Duration::from_millis(100), false);
+    stats.record("GET /api/v1/statuses/:id", Duration::from_millis(300), true);
+    stats.record("POST /api/v1/statuses", Duration::from_millis(50), false);
+
+    let status = &stats.endpoints["GET /api/v1/statuses/:id"];
+    assert_eq!(status.calls, 2);
+    assert_eq!(status.mean_latency(), Duration::from_millis(200));
+    assert_eq!(status.max_latency, Duration::from_millis(300));
+    assert_eq!(status.error_rate(), 0.5);
+    assert_eq!(stats.calls(), 3);
+    assert_eq!(stats.errors(), 1);
+}
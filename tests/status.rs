@@ -0,0 +1,66 @@
+extern crate mammut;
+#[macro_use] extern crate serde_json;
+
+mod common;
+
+use mammut::entities::prelude::Mention;
+use mammut::entities::status::Status;
+use mammut::entities::status_edit::StatusEdit;
+
+fn status(mentions: serde_json::Value, tags: serde_json::Value) -> Status {
+    let mut status = common::status("1");
+    status["account"]["acct"] = json!("mammut");
+    status["mentions"] = mentions;
+    status["tags"] = tags;
+
+    serde_json::from_value(status).unwrap()
+}
+
+#[test]
+fn links_to_mentions_and_tags() {
+    let status = status(
+        json!([{ "url": "https://example.com/@Gargron", "username": "Gargron", "acct": "Gargron", "id": "1" }]),
+        json!([{ "name": "rust", "url": "https://example.com/tags/rust" }]),
+    );
+
+    // The prelude's `Mention` is the one of statuses.
+    let mention: &Mention = status.mention_linked_by("https://example.com/@Gargron").unwrap();
+    assert_eq!(mention.id, "1");
+    assert!(status.mention_linked_by("https://example.com/@someone").is_none());
+    assert_eq!(status.tag_linked_by("https://example.com/tags/Rust").unwrap().name, "rust");
+    assert!(status.tag_linked_by("https://example.com/@rust").is_none());
+}
+
+#[test]
+fn reply_mentions() {
+    let status = status(
+        json!([
+            { "url": "https://example.com/@Gargron", "username": "Gargron", "acct": "Gargron", "id": "2" },
+            { "url": "https://example.com/@mammut", "username": "mammut", "acct": "mammut", "id": "1" },
+            { "url": "https://example.org/@alice", "username": "alice", "acct": "alice@example.org", "id": "3" },
+            { "url": "https://example.com/@Gargron", "username": "Gargron", "acct": "Gargron", "id": "2" },
+        ]),
+        json!([]),
+    );
+
+    assert_eq!(status.reply_mentions("3"), vec!["mammut", "Gargron"]);
+    assert_eq!(status.reply_prefix("1"), "@Gargron @alice@example.org ");
+    assert_eq!(status.reply_prefix("4"), "@mammut @Gargron @alice@example.org ");
+}
+
+#[test]
+fn status_edit() {
+    let edit: StatusEdit = serde_json::from_value(json!({
+        "content": "<p>Hello, world!</p>",
+        "spoiler_text": "",
+        "sensitive": false,
+        "created_at": "2022-09-04T23:22:13.704Z",
+        "account": common::account("1"),
+        "media_attachments": [],
+        "emojis": [],
+        "poll": { "options": [{ "title": "yes" }, { "title": "no" }] }
+    })).unwrap();
+
+    assert_eq!(edit.account.acct, "user1");
+    assert_eq!(edit.poll.unwrap().options[1].title, "no");
+}
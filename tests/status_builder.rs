@@ -0,0 +1,63 @@
+extern crate mammut;
+#[macro_use] extern crate serde_json;
+
+mod common;
+
+use mammut::{Error, StatusBuilder};
+use mammut::entities::instance::InstanceConfiguration;
+use mammut::entities::status::Status;
+use mammut::status_builder::{PollBuilder, Visibility};
+
+#[test]
+fn validate_against_character_limit() {
+    let mut configuration = InstanceConfiguration::default();
+    configuration.statuses.max_characters = 35;
+
+    let long_link = format!("See https://example.com/{} @mammut@example.com", "a".repeat(40));
+    assert!(StatusBuilder::new(long_link).validate_against(&configuration).is_ok());
+
+    match StatusBuilder::new("a".repeat(36)).validate_against(&configuration) {
+        Err(Error::InvalidStatus(_)) => {},
+        other => panic!("expected InvalidStatus, got {:?}", other),
+    }
+}
+
+#[test]
+fn validate_against_poll_limits() {
+    let configuration = InstanceConfiguration::default();
+    let mut status = StatusBuilder::new("Which one?".into());
+    status.poll = Some(PollBuilder {
+        options: vec!["this".into(), "that".into()],
+        expires_in: 3600,
+        ..PollBuilder::default()
+    });
+    assert!(status.validate_against(&configuration).is_ok());
+
+    status.media_ids = Some(vec![1]);
+    assert!(status.validate_against(&configuration).is_err());
+
+    status.media_ids = None;
+    status.poll.as_mut().unwrap().options = vec!["option".into(); 5];
+    assert!(status.validate_against(&configuration).is_err());
+}
+
+#[test]
+fn reply_to() {
+    let mut status = common::status("42");
+    status["account"]["acct"] = json!("alice@example.com");
+    status["spoiler_text"] = json!("film");
+    status["visibility"] = json!("private");
+    let status: Status = serde_json::from_value(status).unwrap();
+
+    let mut reply = StatusBuilder::new("Agreed".into());
+    reply.visibility = Some(Visibility::Public);
+    let reply = reply.reply_to(&status).unwrap();
+
+    assert_eq!(reply.status, "@alice@example.com Agreed");
+    assert_eq!(reply.in_reply_to_id, Some(42));
+    assert_eq!(reply.visibility, Some(Visibility::Private));
+    assert_eq!(reply.spoiler_text, Some("film".to_owned()));
+
+    let mentioned = StatusBuilder::new("@alice@example.com yes".into()).reply_to(&status).unwrap();
+    assert_eq!(mentioned.status, "@alice@example.com yes");
+}
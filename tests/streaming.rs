@@ -0,0 +1,150 @@
+extern crate mammut;
+#[macro_use] extern crate serde_json;
+
+mod common;
+
+use std::io::Cursor;
+use std::time::{Duration, Instant};
+
+use mammut::entities::notification::NotificationType;
+use mammut::streaming::{Event, EventStream, MentionStream, Stream};
+
+fn status() -> String {
+    let mut status = common::status("2");
+    status["content"] = json!("<p>Hello</p>");
+    status.to_string()
+}
+
+#[test]
+fn parses_events() {
+    let notification = json!({
+        "id": "3",
+        "type": "follow",
+        "created_at": "2019-01-01T00:00:00Z",
+        "account": common::account("1"),
+    });
+    let body = format!(
+        ":)\n\nevent: update\ndata: {}\n\n:thump\n\nevent: notification\ndata: {}\r\n\r\n\
+         event: delete\ndata: 2\n\nevent: announcement\ndata: {{}}\n\nevent: filters_changed\n\n",
+        status(),
+        notification,
+    );
+
+    let events: Vec<Event> = EventStream::new(Cursor::new(body))
+        .collect::<mammut::Result<_>>()
+        .unwrap();
+
+    assert_eq!(events.len(), 4);
+    match events[0] {
+        Event::Update(ref status) => assert_eq!(status.content, "<p>Hello</p>"),
+        ref event => panic!("expected an update, got {:?}", event),
+    }
+    match events[1] {
+        Event::Notification(ref notification) => {
+            assert_eq!(notification.notification_type, NotificationType::Follow)
+        },
+        ref event => panic!("expected a notification, got {:?}", event),
+    }
+    match events[2] {
+        Event::Delete(id) => assert_eq!(id, 2),
+        ref event => panic!("expected a deletion, got {:?}", event),
+    }
+    match events[3] {
+        Event::FiltersChanged => {},
+        ref event => panic!("expected a filter change, got {:?}", event),
+    }
+}
+
+#[test]
+fn invalid_event_data() {
+    let mut events = EventStream::new(Cursor::new("event: update\ndata: {\"id\": 1}\n\nevent: delete\ndata: x\n\n"));
+
+    assert!(events.next().unwrap().is_err());
+    match events.next() {
+        Some(Err(mammut::Error::ParseInt(_))) => {},
+        other => panic!("expected an invalid id, got {:?}", other),
+    }
+    assert!(events.next().is_none());
+}
+
+#[test]
+fn mentions() {
+    let notification = |id: &str, notification_type: &str, status: serde_json::Value| json!({
+        "id": id,
+        "type": notification_type,
+        "created_at": "2019-01-01T00:00:00Z",
+        "account": common::account("1"),
+        "status": status,
+    });
+    let body = format!(
+        "event: notification\ndata: {}\n\nevent: update\ndata: {}\n\n\
+         event: notification\ndata: {}\n\nevent: notification\ndata: {}\n\n",
+        notification("3", "favourite", common::status("4")),
+        status(),
+        notification("5", "mention", common::status("6")),
+        notification("7", "follow", json!(null)),
+    );
+
+    let mentions: Vec<String> = MentionStream::new(EventStream::new(Cursor::new(body)))
+        .map(|status| status.unwrap().id)
+        .collect();
+
+    assert_eq!(mentions, ["6"]);
+}
+
+#[test]
+fn reconnecting_backfills_missed_statuses() {
+    let update = |id: &str| format!("event: update\ndata: {}\n\n", common::status(id));
+    let (base, requests) = common::serve_text(vec![
+        (200, update("5")),
+        (200, json!([]).to_string()),
+        (200, update("7")),
+        (200, json!([common::status("7"), common::status("6")]).to_string()),
+    ]);
+    let mastodon = common::mastodon_at(&base);
+
+    let mut events = mastodon.reconnecting_stream(Stream::User, Some("4".into()));
+    events.reconnect_delay = Duration::from_millis(0);
+
+    let mut received = Vec::new();
+    for event in events.by_ref().take(3) {
+        match event.unwrap() {
+            Event::Update(status) => received.push(format!("update {}", status.id)),
+            Event::Backfilled(status) => received.push(format!("backfilled {}", status.id)),
+            event => panic!("expected a status, got {:?}", event),
+        }
+    }
+
+    assert_eq!(received, ["update 5", "backfilled 6", "backfilled 7"]);
+    assert_eq!(events.position(), Some("7"));
+    // Status 7 was streamed again after it was backfilled.
+    assert!(events.next().unwrap().is_err());
+
+    let requests: Vec<String> = requests.iter().collect();
+    assert_eq!(requests, [
+        "GET /api/v1/streaming/user",
+        "GET /api/v1/timelines/home?limit=40&min_id=4",
+        "GET /api/v1/streaming/user",
+        "GET /api/v1/timelines/home?limit=40&min_id=5",
+    ]);
+}
+
+#[test]
+fn reconnecting_waits_after_the_stream_ends() {
+    let update = |id: &str| format!("event: update\ndata: {}\n\n", common::status(id));
+    let (base, _) = common::serve_text(vec![
+        (200, update("1")),
+        (200, update("2")),
+        (200, json!([]).to_string()),
+    ]);
+    let mastodon = common::mastodon_at(&base);
+
+    let mut events = mastodon.reconnecting_stream(Stream::Public, None);
+    events.reconnect_delay = Duration::from_millis(200);
+
+    let start = Instant::now();
+    assert!(events.next().unwrap().is_ok());
+    assert!(start.elapsed() < Duration::from_millis(200));
+    assert!(events.next().unwrap().is_ok());
+    assert!(start.elapsed() >= Duration::from_millis(200));
+}
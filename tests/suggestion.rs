@@ -0,0 +1,22 @@
+extern crate mammut;
+#[macro_use] extern crate serde_json;
+
+mod common;
+
+use mammut::entities::suggestion::{Suggestion, SuggestionSource};
+
+#[test]
+fn suggestion_sources() {
+    let suggestion: Suggestion = serde_json::from_value(json!({
+        "source": "past_interactions",
+        "sources": ["featured", "similar_to_recently_followed", "something_new"],
+        "account": common::account("1")
+    })).unwrap();
+
+    assert_eq!(suggestion.source, SuggestionSource::PastInteractions);
+    assert_eq!(suggestion.sources, [
+        SuggestionSource::Featured,
+        SuggestionSource::SimilarToRecentlyFollowed,
+        SuggestionSource::Unknown,
+    ]);
+}
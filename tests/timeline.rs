@@ -0,0 +1,56 @@
+extern crate mammut;
+#[macro_use] extern crate serde_json;
+
+mod common;
+
+use mammut::entities::status::Status;
+use mammut::timeline::{self, Cursor};
+
+fn status(id: &str, uri: &str, created_at: &str) -> Status {
+    let mut status = common::status(id);
+    status["uri"] = json!(uri);
+    status["created_at"] = json!(created_at);
+
+    serde_json::from_value(status).unwrap()
+}
+
+#[test]
+fn merge_dedupes_and_sorts() {
+    let first = vec![
+        status("1", "https://a/1", "2018-01-01T10:00:00Z"),
+        status("2", "https://b/1", "2018-01-01T12:00:00Z"),
+    ];
+    let second = vec![
+        status("7", "https://b/1", "2018-01-01T12:00:00Z"),
+        status("8", "https://c/1", "2018-01-01T11:00:00Z"),
+    ];
+
+    let merged = timeline::merge(vec![first, second]);
+    let ids: Vec<_> = merged.iter().map(|status| &*status.id).collect();
+
+    assert_eq!(ids, ["2", "8", "1"]);
+}
+
+#[test]
+fn statuses_order_by_creation_then_id() {
+    let older = status("100", "https://a/1", "2018-01-01T10:00:00Z");
+    let newer = status("99", "https://a/2", "2018-01-01T11:00:00Z");
+    let newer_higher_id = status("100", "https://a/3", "2018-01-01T11:00:00Z");
+
+    assert!(older < newer);
+    assert!(newer < newer_higher_id);
+    assert_eq!(::std::cmp::max(older, newer_higher_id).uri, "https://a/3");
+}
+
+#[test]
+fn cursor_save_load() {
+    let path = std::env::temp_dir().join(format!("mammut-cursor-{}.json", std::process::id()));
+    assert_eq!(Cursor::load(&path).unwrap(), None);
+
+    let mut cursor = Cursor::tag("rust");
+    cursor.position = Some("103".into());
+    cursor.save(&path).unwrap();
+
+    assert_eq!(Cursor::load(&path).unwrap(), Some(cursor));
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,20 @@
+extern crate mammut;
+extern crate serde_json;
+
+use std::fs::File;
+
+use mammut::timeline::Cursor;
+use mammut::watcher::HashtagWatcher;
+
+#[test]
+fn open_restores_positions() {
+    let path = std::env::temp_dir().join(format!("mammut-watcher-{}.json", std::process::id()));
+    let mut saved = Cursor::tag("rust");
+    saved.position = Some("103".into());
+    serde_json::to_writer(File::create(&path).unwrap(), &vec![saved.clone(), Cursor::tag("go")]).unwrap();
+
+    let watcher = HashtagWatcher::open(&path, &["#Rust", "rustlang"]).unwrap();
+
+    assert_eq!(watcher.cursors(), &[saved, Cursor::tag("rustlang")][..]);
+    std::fs::remove_file(&path).unwrap();
+}